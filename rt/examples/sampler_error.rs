@@ -0,0 +1,57 @@
+// Error of the samplers against a reference render of scenes/example.json
+// cargo run --release --example sampler_error [samples] [reference samples]
+use std::env;
+use std::sync::Mutex;
+
+use rt::description::SceneDescription;
+use rt::film::{Film, TileMerger};
+use rt::tiles::{TileOrder, tiles};
+
+const WIDTH: usize = 96;
+const HEIGHT: usize = 54;
+const SEEDS: u64 = 8; // Renders averaged per sampler
+
+// Film of the example scene, rendered with `sampler` at `samples` per pixel
+fn render(sampler: &str, samples: i32, seed: u64) -> Film {
+    let mut description = SceneDescription::from_json(include_str!("../scenes/example.json")).unwrap();
+    description.image.width = WIDTH;
+    description.image.height = HEIGHT;
+    description.image.samples_per_pixel = samples;
+    description.image.samples_per_pass = samples.min(16);
+    description.image.sampler = sampler.to_string();
+    description.image.seed = seed;
+    let setup = description.build().unwrap();
+    let merger = Mutex::new(TileMerger::new(Film::new(WIDTH, HEIGHT, setup.filter.clone())));
+    let tiles = tiles(WIDTH, HEIGHT, setup.tile_size, TileOrder::Scanline);
+    setup.renderer.render_passes(&merger, &tiles, 0..setup.renderer.settings.passes(), |_, _| true);
+    merger.into_inner().unwrap().film
+}
+
+// Root mean square difference of the linear colors
+fn rmse(film: &Film, reference: &Film) -> f64 {
+    let mut sum = 0.0;
+    for j in 0..HEIGHT {
+        for i in 0..WIDTH {
+            sum += (film.pixel(i, j) - reference.pixel(i, j)).length_squared() / 3.0;
+        }
+    }
+    f64::sqrt(sum / (WIDTH * HEIGHT) as f64)
+}
+
+fn main() {
+    let mut args = env::args().skip(1).map(|a| a.parse().expect("samples per pixel"));
+    let samples = args.next().unwrap_or(16);
+    let reference_samples = args.next().unwrap_or(4096);
+
+    // Independent samples for the reference, with a seed none of the compared renders use
+    let reference = render("independent", reference_samples, SEEDS);
+    let mut independent = 0.0;
+    println!("{} spp against {} spp, {} seeds", samples, reference_samples, SEEDS);
+    for sampler in ["independent", "stratified", "halton", "sobol"] {
+        let error = (0..SEEDS).map(|seed| rmse(&render(sampler, samples, seed), &reference)).sum::<f64>() / SEEDS as f64;
+        if sampler == "independent" {
+            independent = error;
+        }
+        println!("{:12} RMSE {:.5} ({:+.1}%)", sampler, error, 100.0 * (error / independent - 1.0));
+    }
+}
@@ -0,0 +1,187 @@
+use std::ops::{Add, Mul, Sub};
+use std::sync::Arc;
+
+use crate::basics::{Point3, Vec3};
+use crate::camera::CameraParameters;
+use crate::scene::Scene;
+use crate::traits::Hittable;
+use crate::volumes::{Transform, Transformed};
+
+// How a track goes from a key to the next one
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Interpolation {
+    Linear,
+    CatmullRom, // Through the keys, with tangents from their neighbors
+    Bezier,     // Cubic, with the handles of the keys (Catmull–Rom tangents for the keys without)
+}
+
+// Value of a track at a frame; the interpolation is that of the segment starting at the key
+#[derive(Clone, Copy, Debug)]
+pub struct Key<T> {
+    pub frame: f64,
+    pub value: T,
+    pub interpolation: Interpolation,
+    pub handles: Option<(T, T)>, // Bezier control points, relative to the value: before and after the key
+}
+
+impl<T> Key<T> {
+    pub fn new(frame: f64, value: T, interpolation: Interpolation) -> Key<T> {
+        Key { frame, value, interpolation, handles: None }
+    }
+
+    pub fn with_handles(mut self, before: T, after: T) -> Key<T> {
+        self.handles = Some((before, after));
+        self
+    }
+}
+
+// Keyed values, held before the first key and after the last
+#[derive(Clone, Debug)]
+pub struct Track<T> {
+    keys: Vec<Key<T>>,
+}
+
+impl<T> Track<T>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<f64, Output = T>,
+{
+    // At least one key, at finite and distinct frames; they are sorted by frame
+    pub fn new(mut keys: Vec<Key<T>>) -> Track<T> {
+        assert!(!keys.is_empty(), "a track needs keys");
+        assert!(keys.iter().all(|key| key.frame.is_finite()), "the frames of the keys must be finite");
+        keys.sort_by(|a, b| a.frame.total_cmp(&b.frame));
+        assert!(keys.windows(2).all(|w| w[0].frame < w[1].frame), "two keys are at the same frame");
+        Track { keys }
+    }
+
+    pub fn at(&self, frame: f64) -> T {
+        let keys = &self.keys;
+        let k = keys.partition_point(|key| key.frame <= frame);
+        if k == 0 {
+            return keys[0].value;
+        }
+        if k == keys.len() {
+            return keys[k - 1].value;
+        }
+        let (a, b) = (&keys[k - 1], &keys[k]);
+        let length = b.frame - a.frame;
+        let s = (frame - a.frame) / length;
+        if a.interpolation == Interpolation::Linear {
+            return a.value + (b.value - a.value) * s;
+        }
+        // Control points of the cubic Bezier: a third of the tangents, per segment
+        let (mut p1, mut p2) = (a.value + self.tangent(k - 1) * (length / 3.0), b.value - self.tangent(k) * (length / 3.0));
+        if a.interpolation == Interpolation::Bezier {
+            if let Some((_, after)) = a.handles {
+                p1 = a.value + after;
+            }
+            if let Some((before, _)) = b.handles {
+                p2 = b.value + before;
+            }
+        }
+        let t = 1.0 - s;
+        a.value * (t * t * t) + p1 * (3.0 * t * t * s) + p2 * (3.0 * t * s * s) + b.value * (s * s * s)
+    }
+
+    // Per frame, from the neighbors (Catmull–Rom, for uneven keys), one-sided at the ends
+    fn tangent(&self, k: usize) -> T {
+        let keys = &self.keys;
+        let (before, after) = (&keys[k.saturating_sub(1)], &keys[usize::min(k + 1, keys.len() - 1)]);
+        (after.value - before.value) * (1.0 / (after.frame - before.frame))
+    }
+}
+
+// Keyed camera placement; what has no track keeps the value of the base camera
+#[derive(Clone, Default)]
+pub struct CameraAnimation {
+    pub from: Option<Track<Point3>>,
+    pub at: Option<Track<Point3>>,
+    pub vfov: Option<Track<f64>>,
+}
+
+impl CameraAnimation {
+    pub fn camera(&self, base: CameraParameters, frame: f64) -> CameraParameters {
+        CameraParameters {
+            from: self.from.as_ref().map_or(base.from, |track| track.at(frame)),
+            at: self.at.as_ref().map_or(base.at, |track| track.at(frame)),
+            vfov: self.vfov.as_ref().map_or(base.vfov, |track| track.at(frame)),
+            ..base
+        }
+    }
+}
+
+// Keyed transform of an object of the world, given by its id (place in the world's list, from 1)
+#[derive(Clone)]
+pub struct ObjectAnimation {
+    pub object_id: u32,
+    pub pivot: Point3, // Center of the rotations and scales
+    pub translation: Option<Track<Vec3>>,
+    pub rotation: Option<Track<Vec3>>, // Degrees around x, y and z
+    pub scale: Option<Track<f64>>,
+}
+
+impl ObjectAnimation {
+    pub fn new(object_id: u32, pivot: Point3) -> ObjectAnimation {
+        ObjectAnimation { object_id, pivot, translation: None, rotation: None, scale: None }
+    }
+
+    pub fn transform(&self, frame: f64) -> Transform {
+        let mut transform = Transform::new(self.pivot);
+        if let Some(track) = &self.translation {
+            transform.translation = track.at(frame);
+        }
+        if let Some(track) = &self.rotation {
+            transform.rotation = track.at(frame);
+        }
+        if let Some(track) = &self.scale {
+            transform.scale = track.at(frame);
+        }
+        transform
+    }
+}
+
+// Camera and object keyframes, rendered frame by frame with --frames
+#[derive(Clone, Default)]
+pub struct Animation {
+    pub camera: CameraAnimation,
+    pub objects: Vec<ObjectAnimation>,
+}
+
+// Puts a scene at the frames of an animation: only the objects whose transform changed are replaced,
+// the others, and the whole scene when nothing moved, are kept from the previous frame
+pub struct Animator {
+    animation: Animation,
+    originals: Vec<Arc<dyn Hittable>>, // Untransformed, one per object animation
+    transforms: Vec<Option<Transform>>, // Current ones
+}
+
+impl Animator {
+    pub fn new(animation: Animation, scene: &Scene) -> Result<Animator, String> {
+        let originals = animation
+            .objects
+            .iter()
+            .map(|object| scene.world.get(object.object_id).ok_or(format!("no object {} to animate", object.object_id)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let transforms = vec![None; originals.len()];
+        Ok(Animator { animation, originals, transforms })
+    }
+
+    // True if the scene changed; an error if a transform cannot be applied (a scale of 0)
+    pub fn set_frame(&mut self, scene: &mut Scene, frame: f64) -> Result<bool, String> {
+        let mut changed = false;
+        for (k, object) in self.animation.objects.iter().enumerate() {
+            let transform = object.transform(frame);
+            transform.check().map_err(|e| format!("object {} at frame {}: {}", object.object_id, frame, e))?;
+            if self.transforms[k] != Some(transform) {
+                scene.replace_object(object.object_id, Arc::new(Transformed::new(self.originals[k].clone(), transform)));
+                self.transforms[k] = Some(transform);
+                changed = true;
+            }
+        }
+        Ok(changed)
+    }
+
+    pub fn camera(&self, base: CameraParameters, frame: f64) -> CameraParameters {
+        self.animation.camera.camera(base, frame)
+    }
+}
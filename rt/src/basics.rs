@@ -2,8 +2,14 @@ mod vec3;
 mod point3;
 mod color;
 mod ray;
+mod spectrum;
+mod onb;
+mod sampling;
 
 pub use vec3::*;
 pub use point3::*;
 pub use color::*;
-pub use ray::*;
\ No newline at end of file
+pub use ray::*;
+pub use spectrum::*;
+pub use onb::*;
+pub use sampling::*;
\ No newline at end of file
@@ -29,4 +29,14 @@ pub fn write_color(out: &mut impl Write, pixel_color: Color, samples_per_pixel:
         (256.0 * clamp(b, 0.0, 0.999)) as i32,
     )
     .expect("writing color");
-}
\ No newline at end of file
+}
+// Components in [0, 255] of a color, as written by write_color() for one sample
+pub fn color_to_rgb8(pixel_color: Color, gamma: f64) -> [u8; 3] {
+    let byte = |c: f64| (256.0 * clamp(c.powf(1.0 / gamma), 0.0, 0.999)) as u8;
+    [byte(pixel_color.x()), byte(pixel_color.y()), byte(pixel_color.z())]
+}
+
+// Relative luminance of a linear sRGB color
+pub fn luminance(c: Color) -> f64 {
+    0.2126 * c.x() + 0.7152 * c.y() + 0.0722 * c.z()
+}
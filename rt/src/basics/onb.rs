@@ -0,0 +1,28 @@
+use crate::basics::{Vec3, cross, unit_vec};
+
+// Orthonormal basis (u, v, w) around a direction w, to express local directions in the world
+#[derive(Clone, Copy)]
+pub struct Onb {
+    pub u: Vec3,
+    pub v: Vec3,
+    pub w: Vec3,
+}
+
+impl Onb {
+    pub fn new(w: Vec3) -> Onb {
+        let w = unit_vec(w);
+        let a = if w.x().abs() > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let v = unit_vec(cross(w, a));
+        let u = cross(w, v);
+        Onb { u, v, w }
+    }
+
+    // World direction of the local direction (x, y, z), z along w
+    pub fn local(&self, d: Vec3) -> Vec3 {
+        d.x() * self.u + d.y() * self.v + d.z() * self.w
+    }
+}
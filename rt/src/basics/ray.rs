@@ -1,9 +1,12 @@
-use crate::basics::{Point3, Vec3};
+use crate::basics::{Color, Point3, Vec3};
 
 #[derive(Default)]
 pub struct Ray {
     ori: Point3,
     dir: Vec3,
+    wavelength: Option<f64>, // Nanometers, once the path is restricted to one wavelength
+    world_length: Option<f64>, // World distance per unit of t, once taken to the space of a scaled object
+    absorption: Option<Color>, // Absorption coefficient of the medium travelled, per world unit, once set by a surface
 }
 
 impl Ray {
@@ -11,6 +14,9 @@ impl Ray {
         Ray {
             ori,
             dir,
+            wavelength: None,
+            world_length: None,
+            absorption: None,
         }
     }
 
@@ -22,6 +28,32 @@ impl Ray {
         self.dir
     }
 
+    pub fn wavelength(&self) -> Option<f64> {
+        self.wavelength
+    }
+
+    pub fn set_wavelength(&mut self, wavelength: Option<f64>) {
+        self.wavelength = wavelength;
+    }
+
+    // Distances in media are measured in the world, whatever the space the ray was taken to
+    pub fn world_length(&self) -> f64 {
+        self.world_length.unwrap_or_else(|| self.dir.length())
+    }
+
+    pub fn set_world_length(&mut self, world_length: Option<f64>) {
+        self.world_length = world_length;
+    }
+
+    // Colored glass sets it on the rays going inside, and clears it (to 0) on those leaving
+    pub fn absorption(&self) -> Option<Color> {
+        self.absorption
+    }
+
+    pub fn set_absorption(&mut self, absorption: Option<Color>) {
+        self.absorption = absorption;
+    }
+
     pub fn at(&self, t: f64) -> Point3 {
         self.ori + t * self.dir
     }
@@ -0,0 +1,78 @@
+use crate::basics::{Onb, Point3, Vec3, cross};
+use crate::utils::PI;
+
+// Warps of uniform samples u in [0, 1[^2 to common distributions
+// Each returns the sampled value and its pdf (solid angle for directions, area for points)
+
+pub fn sample_uniform_sphere(u: (f64, f64)) -> (Vec3, f64) {
+    let z = 1.0 - 2.0 * u.0;
+    let r = f64::sqrt(f64::max(0.0, 1.0 - z * z));
+    let phi = 2.0 * PI * u.1;
+    (Vec3::new(r * f64::cos(phi), r * f64::sin(phi), z), uniform_sphere_pdf())
+}
+
+pub fn uniform_sphere_pdf() -> f64 {
+    1.0 / (4.0 * PI)
+}
+
+// Directions on the side of `normal`
+pub fn sample_uniform_hemisphere(normal: Vec3, u: (f64, f64)) -> (Vec3, f64) {
+    let z = u.0;
+    let r = f64::sqrt(f64::max(0.0, 1.0 - z * z));
+    let phi = 2.0 * PI * u.1;
+    let local = Vec3::new(r * f64::cos(phi), r * f64::sin(phi), z);
+    (Onb::new(normal).local(local), uniform_hemisphere_pdf())
+}
+
+pub fn uniform_hemisphere_pdf() -> f64 {
+    1.0 / (2.0 * PI)
+}
+
+// Directions on the side of `normal`, with a density proportional to the cosine (Malley's method)
+pub fn sample_cosine_hemisphere(normal: Vec3, u: (f64, f64)) -> (Vec3, f64) {
+    let ((x, y), _) = sample_concentric_disk(u);
+    let z = f64::sqrt(f64::max(0.0, 1.0 - x * x - y * y));
+    (Onb::new(normal).local(Vec3::new(x, y, z)), cosine_hemisphere_pdf(z))
+}
+
+pub fn cosine_hemisphere_pdf(cos_theta: f64) -> f64 {
+    f64::max(0.0, cos_theta) / PI
+}
+
+// Point (x, y) of the unit disk; the concentric mapping keeps the strata of u compact (Shirley-Chiu)
+pub fn sample_concentric_disk(u: (f64, f64)) -> ((f64, f64), f64) {
+    let a = 2.0 * u.0 - 1.0;
+    let b = 2.0 * u.1 - 1.0;
+    if a == 0.0 && b == 0.0 {
+        return ((0.0, 0.0), 1.0 / PI);
+    }
+    let (r, theta) = if a.abs() > b.abs() {
+        (a, PI / 4.0 * (b / a))
+    } else {
+        (b, PI / 2.0 - PI / 4.0 * (a / b))
+    };
+    ((r * f64::cos(theta), r * f64::sin(theta)), 1.0 / PI)
+}
+
+// Directions within `cos_theta_max` of `axis`
+pub fn sample_uniform_cone(axis: Vec3, cos_theta_max: f64, u: (f64, f64)) -> (Vec3, f64) {
+    let z = 1.0 + u.0 * (cos_theta_max - 1.0);
+    let r = f64::sqrt(f64::max(0.0, 1.0 - z * z));
+    let phi = 2.0 * PI * u.1;
+    let local = Vec3::new(r * f64::cos(phi), r * f64::sin(phi), z);
+    (Onb::new(axis).local(local), uniform_cone_pdf(cos_theta_max))
+}
+
+pub fn uniform_cone_pdf(cos_theta_max: f64) -> f64 {
+    1.0 / (2.0 * PI * (1.0 - cos_theta_max))
+}
+
+// Point of the triangle (p0, p1, p2), uniform over its area
+pub fn sample_triangle(p0: Point3, p1: Point3, p2: Point3, u: (f64, f64)) -> (Point3, f64) {
+    let su = f64::sqrt(u.0);
+    let b0 = 1.0 - su;
+    let b1 = u.1 * su;
+    let p = b0 * p0 + b1 * p1 + (1.0 - b0 - b1) * p2;
+    let area = 0.5 * cross(p1 - p0, p2 - p0).length();
+    (p, 1.0 / area)
+}
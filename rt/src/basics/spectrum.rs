@@ -0,0 +1,175 @@
+use std::sync::OnceLock;
+use crate::basics::{Color, Vec3};
+
+// Visible range, in nanometers
+pub const LAMBDA_MIN: f64 = 360.0;
+pub const LAMBDA_MAX: f64 = 830.0;
+
+// Piecewise gaussian used by the fit of the CIE curves
+fn lobe(lambda: f64, mu: f64, sigma_low: f64, sigma_high: f64) -> f64 {
+    let sigma = if lambda < mu { sigma_low } else { sigma_high };
+    let t = (lambda - mu) / sigma;
+    f64::exp(-0.5 * t * t)
+}
+
+// CIE 1931 color matching functions (multi-lobe fit of Wyman, Sloan and Shirley)
+pub fn cie_xyz(lambda: f64) -> Vec3 {
+    let x = 1.056 * lobe(lambda, 599.8, 37.9, 31.0) + 0.362 * lobe(lambda, 442.0, 16.0, 26.7)
+        - 0.065 * lobe(lambda, 501.1, 20.4, 26.2);
+    let y = 0.821 * lobe(lambda, 568.8, 46.9, 40.5) + 0.286 * lobe(lambda, 530.9, 16.3, 31.1);
+    let z = 1.217 * lobe(lambda, 437.0, 11.8, 36.0) + 0.681 * lobe(lambda, 459.0, 26.0, 13.8);
+    Vec3::new(x, y, z)
+}
+
+// CIE XYZ to linear sRGB (D65)
+pub fn xyz_to_rgb(xyz: Vec3) -> Color {
+    Color::new(
+        3.2404542 * xyz.x() - 1.5371385 * xyz.y() - 0.4985314 * xyz.z(),
+        -0.9692660 * xyz.x() + 1.8760108 * xyz.y() + 0.0415560 * xyz.z(),
+        0.0556434 * xyz.x() - 0.2040259 * xyz.y() + 1.0572252 * xyz.z(),
+    )
+}
+
+// Positive part of the sRGB response to a single wavelength
+fn wavelength_rgb(lambda: f64) -> Color {
+    let rgb = xyz_to_rgb(cie_xyz(lambda));
+    Color::new(rgb.x().max(0.0), rgb.y().max(0.0), rgb.z().max(0.0))
+}
+
+// Weight of a path restricted to a wavelength picked uniformly in [LAMBDA_MIN, LAMBDA_MAX]
+// Normalized so that the average over the wavelengths is white
+pub fn wavelength_rgb_weight(lambda: f64) -> Color {
+    static INTEGRAL: OnceLock<Color> = OnceLock::new();
+    let integral = INTEGRAL.get_or_init(|| {
+        let mut sum = Color::new(0.0, 0.0, 0.0);
+        let mut l = LAMBDA_MIN;
+        while l <= LAMBDA_MAX {
+            sum += wavelength_rgb(l);
+            l += 1.0;
+        }
+        sum
+    });
+    (LAMBDA_MAX - LAMBDA_MIN) * wavelength_rgb(lambda) / *integral
+}
+
+// Planck's law: spectral radiance of a black body (lambda in nanometers, temperature in kelvin)
+pub fn blackbody(lambda: f64, kelvin: f64) -> f64 {
+    const H: f64 = 6.62607015e-34; // Planck
+    const C: f64 = 299792458.0; // Speed of light
+    const KB: f64 = 1.380649e-23; // Boltzmann
+
+    let l = lambda * 1.0e-9;
+    2.0 * H * C * C / (l.powi(5) * (f64::exp(H * C / (l * KB * kelvin)) - 1.0))
+}
+
+// Color of a black body, scaled to a luminance of 1.0
+pub fn blackbody_rgb(kelvin: f64) -> Color {
+    let mut xyz = Vec3::new(0.0, 0.0, 0.0);
+    let mut lambda = LAMBDA_MIN;
+    while lambda <= LAMBDA_MAX {
+        xyz += blackbody(lambda, kelvin) * cie_xyz(lambda);
+        lambda += 1.0;
+    }
+    if xyz.y() <= 0.0 {
+        return Color::new(0.0, 0.0, 0.0);
+    }
+
+    // Out of gamut components are clipped
+    let rgb = xyz_to_rgb(xyz / xyz.y());
+    Color::new(rgb.x().max(0.0), rgb.y().max(0.0), rgb.z().max(0.0))
+}
+
+// Smits' basis spectra for RGB to spectrum upsampling: 10 bins over [380, 720] nm
+const SMITS_WHITE: [f64; 10] = [1.0000, 1.0000, 0.9999, 0.9993, 0.9992, 0.9998, 1.0000, 1.0000, 1.0000, 1.0000];
+const SMITS_CYAN: [f64; 10] = [0.9710, 0.9426, 1.0007, 1.0007, 1.0007, 1.0007, 0.1564, 0.0000, 0.0000, 0.0000];
+const SMITS_MAGENTA: [f64; 10] = [1.0000, 1.0000, 0.9685, 0.2229, 0.0000, 0.0458, 0.8369, 1.0000, 1.0000, 0.9959];
+const SMITS_YELLOW: [f64; 10] = [0.0001, 0.0000, 0.1088, 0.6651, 1.0000, 1.0000, 0.9996, 0.9586, 0.9685, 0.9840];
+const SMITS_RED: [f64; 10] = [0.1012, 0.0515, 0.0000, 0.0000, 0.0000, 0.0000, 0.8325, 1.0149, 1.0149, 1.0149];
+const SMITS_GREEN: [f64; 10] = [0.0000, 0.0000, 0.0273, 0.7937, 1.0000, 0.9418, 0.1719, 0.0000, 0.0000, 0.0025];
+const SMITS_BLUE: [f64; 10] = [1.0000, 1.0000, 0.8916, 0.3323, 0.0000, 0.0000, 0.0003, 0.0369, 0.0483, 0.0496];
+
+// Value at `lambda` of a smooth spectrum whose color is `c` (Smits); linear in c, so emission can be upsampled too
+pub fn rgb_to_spectrum(c: Color, lambda: f64) -> f64 {
+    let bin = (((lambda - 380.0) / 34.0).floor().max(0.0) as usize).min(9);
+    let (r, g, b) = (c.x(), c.y(), c.z());
+
+    if r <= g && r <= b {
+        let base = r * SMITS_WHITE[bin];
+        if g <= b {
+            base + (g - r) * SMITS_CYAN[bin] + (b - g) * SMITS_BLUE[bin]
+        } else {
+            base + (b - r) * SMITS_CYAN[bin] + (g - b) * SMITS_GREEN[bin]
+        }
+    } else if g <= r && g <= b {
+        let base = g * SMITS_WHITE[bin];
+        if r <= b {
+            base + (r - g) * SMITS_MAGENTA[bin] + (b - r) * SMITS_BLUE[bin]
+        } else {
+            base + (b - g) * SMITS_MAGENTA[bin] + (r - b) * SMITS_RED[bin]
+        }
+    } else {
+        let base = b * SMITS_WHITE[bin];
+        if r <= g {
+            base + (r - b) * SMITS_YELLOW[bin] + (g - r) * SMITS_GREEN[bin]
+        } else {
+            base + (g - b) * SMITS_YELLOW[bin] + (r - g) * SMITS_RED[bin]
+        }
+    }
+}
+
+// RGB quantity seen by a path: upsampled to its wavelength (in every component) when it has one
+pub fn to_spectral(c: Color, wavelength: Option<f64>) -> Color {
+    match wavelength {
+        Some(lambda) => {
+            let s = rgb_to_spectrum(c, lambda);
+            Color::new(s, s, s)
+        }
+        None => c,
+    }
+}
+
+// Integrals of the CIE curves over the sampled range
+fn cie_integrals() -> Vec3 {
+    static INTEGRALS: OnceLock<Vec3> = OnceLock::new();
+    *INTEGRALS.get_or_init(|| {
+        let mut sum = Vec3::new(0.0, 0.0, 0.0);
+        let mut l = LAMBDA_MIN;
+        while l <= LAMBDA_MAX {
+            sum += cie_xyz(l);
+            l += 1.0;
+        }
+        sum
+    })
+}
+
+// Contribution to the film (XYZ) of a radiance sample at a wavelength picked uniformly in [LAMBDA_MIN, LAMBDA_MAX]
+// A constant spectrum of 1.0 gives Y = 1.0 on average
+pub fn spectral_sample_xyz(value: f64, lambda: f64) -> Vec3 {
+    (LAMBDA_MAX - LAMBDA_MIN) * value / cie_integrals().y() * cie_xyz(lambda)
+}
+
+// Film XYZ to RGB, balanced so that a constant spectrum stays white
+pub fn xyz_to_film_rgb(xyz: Vec3) -> Color {
+    let white = xyz_to_rgb(cie_integrals() / cie_integrals().y());
+    xyz_to_rgb(xyz) / white
+}
+
+// Luminance of a black body's spectrum (unnormalized Planck's law)
+fn blackbody_luminance(kelvin: f64) -> f64 {
+    let mut y = 0.0;
+    let mut lambda = LAMBDA_MIN;
+    while lambda <= LAMBDA_MAX {
+        y += blackbody(lambda, kelvin) * cie_xyz(lambda).y();
+        lambda += 1.0;
+    }
+    y / cie_integrals().y()
+}
+
+// Spectrum of a black body, scaled to a luminance of 1.0 (spectral counterpart of blackbody_rgb())
+pub fn blackbody_normalized(lambda: f64, kelvin: f64) -> f64 {
+    let luminance = blackbody_luminance(kelvin);
+    if luminance <= 0.0 {
+        return 0.0;
+    }
+    blackbody(lambda, kelvin) / luminance
+}
@@ -1,10 +1,11 @@
 use std::fmt::{Display, Formatter, Result};
 use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use crate::traits::Sampler;
+use crate::basics::sample_uniform_sphere;
 
-use crate::utils::{rand_01, rand_range};
 
 // Unified struct and methods for the 3 types, easy to loop on, no overwriting a field
-#[derive(Copy,Clone, Default)]
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
 pub struct Vec3 {
 	tab: [f64; 3],
 }
@@ -13,18 +14,18 @@ impl Vec3 {
 	pub fn new(x: f64, y: f64, z: f64) -> Self {
 		Vec3 { tab: [x, y, z]}
 	}
-	pub fn rand() -> Self {
+	pub fn rand(sampler: &mut dyn Sampler) -> Self {
         Self::new(
-        	rand_01(),
-            rand_01(),
-            rand_01(),
+        	sampler.get_1d(),
+            sampler.get_1d(),
+            sampler.get_1d(),
         )
     }
-    pub fn rand_range(min: f64, max: f64) -> Self {
+    pub fn rand_range(sampler: &mut dyn Sampler, min: f64, max: f64) -> Self {
         Self::new(
-            rand_range(min, max),
-            rand_range(min, max),
-            rand_range(min, max),
+            sampler.get_range(min, max),
+            sampler.get_range(min, max),
+            sampler.get_range(min, max),
         )
     }
 
@@ -176,23 +177,33 @@ pub fn rotate(u: Vec3, v: Vec3) -> Vec3 {
 	)
 }
 
+// Inverse of rotate(): the rotation matrix is orthonormal, so it is applied transposed
+pub fn rotate_inv(u: Vec3, v: Vec3) -> Vec3 {
+	let r_matrix: Vec<Vec<f64>> = vec![
+		vec![(v.x().cos() * v.y().cos()), (v.x().cos() * v.y().sin() * v.z().sin() - v.x().sin() * v.z().cos()), (v.x().cos() * v.y().sin() * v.z().cos() + v.x().sin() * v.z().sin())],
+		vec![(v.x().sin() * v.y().cos()), (v.x().sin() * v.y().sin() * v.z().sin() + v.x().cos() * v.z().cos()), (v.x().sin() * v.y().sin() * v.z().cos() - v.x().cos() * v.z().sin())],
+		vec![(-v.y().sin()), (v.y().cos() * v.z().sin()), (v.y().cos() * v.z().cos())]
+	];
+	Vec3::new(
+		r_matrix[0][0] * u.x() + r_matrix[1][0] * u.y() + r_matrix[2][0] * u.z(),
+		r_matrix[0][1] * u.x() + r_matrix[1][1] * u.y() + r_matrix[2][1] * u.z(),
+		r_matrix[0][2] * u.x() + r_matrix[1][2] * u.y() + r_matrix[2][2] * u.z(),
+	)
+}
+
 // Unit and Random
 pub fn unit_vec(v: Vec3) -> Vec3 {
     v / v.length()
 }
 
-pub fn rand_unit_vec() -> Vec3 {
-	unit_vec(rand_in_unit_sphere())
+pub fn rand_unit_vec(sampler: &mut dyn Sampler) -> Vec3 {
+	sample_uniform_sphere(sampler.get_2d()).0
 }
 
-pub fn rand_in_unit_sphere() -> Vec3 {
-    loop {
-        let p = Vec3::rand_range(-1.0, 1.0);
-        if p.length_squared() >= 1.0 {
-            continue;
-        }
-        return p;
-    }
+// Uniform in the ball: a direction, and a radius with a density in r^2
+pub fn rand_in_unit_sphere(sampler: &mut dyn Sampler) -> Vec3 {
+    let direction = rand_unit_vec(sampler);
+    f64::cbrt(sampler.get_1d()) * direction
 }
 
 // Ray interactions
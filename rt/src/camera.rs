@@ -1,40 +1,132 @@
-use crate::{basics::{Point3, Ray, Vec3, cross, unit_vec}, utils::degrees_to_radians};
+mod perspective;
+mod orthographic;
+mod fisheye;
+mod equirectangular;
+mod cylindrical;
+mod stereo;
+mod ods;
 
-pub struct Camera {
-	pub ori: Point3,
-	pub lower_left_corner: Point3,
-	pub horizontal: Vec3,
-	pub vertical: Vec3,
+pub use perspective::*;
+pub use orthographic::*;
+pub use fisheye::*;
+pub use equirectangular::*;
+pub use cylindrical::*;
+pub use stereo::*;
+pub use ods::*;
+
+use crate::basics::{Point3, Vec3, cross, unit_vec};
+use crate::traits::Camera;
+
+// How the film is projected on the scene
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Projection {
+	Perspective,                  // Field of view: vfov of the parameters, with depth of field
+	Orthographic { height: f64 }, // View height, in world units
+	Fisheye { fov: f64 },         // Equidistant, field of view in degrees across the circle inscribed in the image
+	Equirectangular,              // 360 x 180 degrees
+	Cylindrical { hfov: f64 },    // Horizontal field of view in degrees, up to 360
 }
 
-impl Camera {
-	pub fn new(aspect_ratio: f64, vangle_fov: f64, from: Point3, to: Point3, y: Vec3) -> Camera {
-		let theta = degrees_to_radians(vangle_fov);
-		let h = f64::tan(theta / 2.0);
+// How the eyes of a stereo pair see the point at the convergence distance in front of the camera
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Convergence {
+	ToeIn,   // Both eyes turn towards it: simple, but vertical parallax away from the center
+	OffAxis, // The eyes look parallel and their films are shifted to center it (ODS: parallel rays)
+}
 
-		let viewport_height = 2.0 * h;
-		let viewport_width = aspect_ratio * viewport_height;
-		//let focal_length = 1.0;
+// Two eyes `interocular` apart across the view, in one image; the point at `convergence` appears on the screen plane
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Stereo {
+	pub interocular: f64,
+	pub convergence: f64,
+	pub mode: Convergence,
+	pub layout: StereoLayout,
+}
 
-		let w = unit_vec(from - to);
-		let u = unit_vec(cross(y, w));
-		let v = cross(w, u);
+impl Stereo {
+	// Off-axis, side by side
+	pub fn new(interocular: f64, convergence: f64) -> Stereo {
+		Stereo { interocular, convergence, mode: Convergence::OffAxis, layout: StereoLayout::SideBySide }
+	}
+}
+
+// Placement of a camera, kept to move it (viewer) or to print it back
+#[derive(Clone, Copy)]
+pub struct CameraParameters {
+	pub from: Point3,
+	pub at: Point3,
+	pub vup: Vec3,
+	pub vfov: f64, // Vertical field of view, in degrees
+	pub aperture: f64,
+	pub focus_dist: f64,
+	pub projection: Projection,
+	pub stereo: Option<Stereo>,
+}
 
-		let origin = from;
-		let horizontal = viewport_width * u;
-		let vertical = viewport_height * v;
-		let lower_left_corner = origin - horizontal / 2.0 - vertical / 2.0 - w;
+impl CameraParameters {
+	pub fn new(from: Point3, at: Point3, vup: Vec3, vfov: f64) -> CameraParameters {
+		CameraParameters { from, at, vup, vfov, aperture: 0.0, focus_dist: 1.0, projection: Projection::Perspective, stereo: None }
+	}
 
-		Camera { ori: origin,
-			lower_left_corner,
-			horizontal,
-			vertical }
+	// Depth of field, as PerspectiveCamera::thin_lens()
+	pub fn with_lens(mut self, aperture: f64, focus_dist: f64) -> CameraParameters {
+		self.aperture = aperture;
+		self.focus_dist = focus_dist;
+		self
 	}
 
-	pub fn get_ray(&self, u: f64, v: f64) -> Ray {
-        Ray::new(
-            self.ori,
-            self.lower_left_corner + u * self.horizontal + v * self.vertical - self.ori,
-        )
-    }
+	pub fn with_projection(mut self, projection: Projection) -> CameraParameters {
+		self.projection = projection;
+		self
+	}
+
+	pub fn with_stereo(mut self, stereo: Stereo) -> CameraParameters {
+		self.stereo = Some(stereo);
+		self
+	}
+
+	pub fn camera(&self, aspect_ratio: f64) -> Box<dyn Camera> {
+		let Some(stereo) = self.stereo else {
+			return self.eye(aspect_ratio, self.from, self.at);
+		};
+		let aspect_ratio = match stereo.layout {
+			StereoLayout::SideBySide => aspect_ratio / 2.0,
+			StereoLayout::OverUnder => aspect_ratio * 2.0,
+		};
+		let eye = |side: f64| -> Box<dyn Camera> {
+			let forward = unit_vec(self.at - self.from);
+			let right = unit_vec(cross(forward, self.vup));
+			let offset = side * stereo.interocular / 2.0;
+			let from = self.from + offset * right;
+			match (self.projection, stereo.mode) {
+				(Projection::Equirectangular, mode) => {
+					let convergence = (mode == Convergence::ToeIn).then_some(stereo.convergence);
+					Box::new(OdsCamera::new(self.from, self.at, self.vup, offset, convergence))
+				}
+				(_, Convergence::ToeIn) => self.eye(aspect_ratio, from, self.from + stereo.convergence * forward),
+				(Projection::Perspective, Convergence::OffAxis) => {
+					let mut camera = PerspectiveCamera::thin_lens(aspect_ratio, self.vfov, from, from + forward, self.vup, self.aperture, self.focus_dist);
+					// The film, at focus_dist, moves towards the middle of the eyes: the rays through
+					// its center cross at the convergence distance
+					camera.lower_left_corner -= offset * self.focus_dist / stereo.convergence * right;
+					Box::new(camera)
+				}
+				(_, Convergence::OffAxis) => self.eye(aspect_ratio, from, from + forward),
+			}
+		};
+		Box::new(StereoCamera::new(eye(-1.0), eye(1.0), stereo.layout))
+	}
+
+	// Camera of the projection, placed at `from` looking at `at`
+	fn eye(&self, aspect_ratio: f64, from: Point3, at: Point3) -> Box<dyn Camera> {
+		let vup = self.vup;
+		match self.projection {
+			Projection::Perspective => Box::new(PerspectiveCamera::thin_lens(aspect_ratio, self.vfov, from, at, vup, self.aperture, self.focus_dist)),
+			Projection::Orthographic { height } => Box::new(OrthographicCamera::new(aspect_ratio, height, from, at, vup)),
+			Projection::Fisheye { fov } => Box::new(FisheyeCamera::new(aspect_ratio, fov, from, at, vup)),
+			Projection::Equirectangular => Box::new(EquirectangularCamera::new(from, at, vup)),
+			Projection::Cylindrical { hfov } => Box::new(CylindricalCamera::new(aspect_ratio, hfov, from, at, vup)),
+		}
+	}
 }
+
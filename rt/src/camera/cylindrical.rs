@@ -0,0 +1,34 @@
+use crate::basics::{Point3, Ray, Vec3, cross, unit_vec};
+use crate::traits::{Camera, Sampler};
+use crate::utils::degrees_to_radians;
+
+// Panorama on a cylinder around vup: angles along the width, up to 360 degrees, and a perspective along the height,
+// which keeps the vertical lines straight; the vertical field of view follows from the aspect ratio
+#[derive(Debug)]
+pub struct CylindricalCamera {
+    origin: Point3,
+    hfov: f64,   // Radians
+    height: f64, // Of the film on the cylinder of radius 1
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+}
+
+impl CylindricalCamera {
+    // `hfov` in degrees, up to 360
+    pub fn new(aspect_ratio: f64, hfov: f64, from: Point3, to: Point3, vup: Vec3) -> CylindricalCamera {
+        let w = unit_vec(from - to);
+        let u = unit_vec(cross(vup, w));
+        let v = cross(w, u);
+        let hfov = degrees_to_radians(hfov);
+        CylindricalCamera { origin: from, hfov, height: hfov / aspect_ratio, u, v, w }
+    }
+}
+
+impl Camera for CylindricalCamera {
+    fn generate_ray(&self, s: f64, t: f64, _sampler: &mut dyn Sampler) -> Option<Ray> {
+        let angle = (s - 0.5) * self.hfov;
+        let direction = angle.sin() * self.u - angle.cos() * self.w + (t - 0.5) * self.height * self.v;
+        Some(Ray::new(self.origin, direction))
+    }
+}
@@ -0,0 +1,31 @@
+use crate::basics::{Point3, Ray, Vec3, cross, unit_vec};
+use crate::traits::{Camera, Sampler};
+use crate::utils::PI;
+
+// Every direction: longitude along the width (360 degrees, `to` in the middle), latitude along the height (180 degrees)
+// The image is usually twice as wide as high
+#[derive(Debug)]
+pub struct EquirectangularCamera {
+    origin: Point3,
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+}
+
+impl EquirectangularCamera {
+    pub fn new(from: Point3, to: Point3, vup: Vec3) -> EquirectangularCamera {
+        let w = unit_vec(from - to);
+        let u = unit_vec(cross(vup, w));
+        let v = cross(w, u);
+        EquirectangularCamera { origin: from, u, v, w }
+    }
+}
+
+impl Camera for EquirectangularCamera {
+    fn generate_ray(&self, s: f64, t: f64, _sampler: &mut dyn Sampler) -> Option<Ray> {
+        let longitude = (s - 0.5) * 2.0 * PI;
+        let latitude = (t - 0.5) * PI;
+        let direction = latitude.cos() * (longitude.sin() * self.u - longitude.cos() * self.w) + latitude.sin() * self.v;
+        Some(Ray::new(self.origin, direction))
+    }
+}
@@ -0,0 +1,41 @@
+use crate::basics::{Point3, Ray, Vec3, cross, unit_vec};
+use crate::traits::{Camera, Sampler};
+use crate::utils::degrees_to_radians;
+
+// Equidistant fisheye: the angle from the axis grows linearly with the distance from the center of the image,
+// up to half the field of view on the circle inscribed in the image height; black outside
+#[derive(Debug)]
+pub struct FisheyeCamera {
+    origin: Point3,
+    aspect_ratio: f64,
+    half_fov: f64, // Radians
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+}
+
+impl FisheyeCamera {
+    // `fov` in degrees, up to 360
+    pub fn new(aspect_ratio: f64, fov: f64, from: Point3, to: Point3, vup: Vec3) -> FisheyeCamera {
+        let w = unit_vec(from - to);
+        let u = unit_vec(cross(vup, w));
+        let v = cross(w, u);
+        FisheyeCamera { origin: from, aspect_ratio, half_fov: degrees_to_radians(fov) / 2.0, u, v, w }
+    }
+}
+
+impl Camera for FisheyeCamera {
+    fn generate_ray(&self, s: f64, t: f64, _sampler: &mut dyn Sampler) -> Option<Ray> {
+        // In units of the image height, from the center
+        let x = (s - 0.5) * self.aspect_ratio;
+        let y = t - 0.5;
+        let r = f64::sqrt(x * x + y * y);
+        if r > 0.5 {
+            return None;
+        }
+        let theta = r / 0.5 * self.half_fov;
+        let (cos_phi, sin_phi) = if r > 0.0 { (x / r, y / r) } else { (1.0, 0.0) };
+        let direction = theta.sin() * (cos_phi * self.u + sin_phi * self.v) - theta.cos() * self.w;
+        Some(Ray::new(self.origin, direction))
+    }
+}
@@ -0,0 +1,39 @@
+use crate::basics::{Point3, Ray, Vec3, cross, unit_vec};
+use crate::traits::{Camera, Sampler};
+use crate::utils::PI;
+
+// One eye of an omnidirectional stereo (ODS) panorama: an equirectangular image whose rays start on the circle
+// the eyes follow when the head turns around vup, tangent to it; the other eye has the opposite offset
+#[derive(Debug)]
+pub struct OdsCamera {
+    origin: Point3,
+    offset: f64, // Signed radius of the circle: half the interocular distance, negative for the left eye
+    convergence: Option<f64>, // Distance at which the rays of the eyes cross, parallel rays if None
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+}
+
+impl OdsCamera {
+    pub fn new(from: Point3, to: Point3, vup: Vec3, offset: f64, convergence: Option<f64>) -> OdsCamera {
+        let w = unit_vec(from - to);
+        let u = unit_vec(cross(vup, w));
+        let v = cross(w, u);
+        OdsCamera { origin: from, offset, convergence, u, v, w }
+    }
+}
+
+impl Camera for OdsCamera {
+    fn generate_ray(&self, s: f64, t: f64, _sampler: &mut dyn Sampler) -> Option<Ray> {
+        let longitude = (s - 0.5) * 2.0 * PI;
+        let latitude = (t - 0.5) * PI;
+        let direction = latitude.cos() * (longitude.sin() * self.u - longitude.cos() * self.w) + latitude.sin() * self.v;
+        // To the right of the horizontal part of the direction
+        let eye = self.offset * (longitude.cos() * self.u + longitude.sin() * self.w);
+        let direction = match self.convergence {
+            Some(distance) => distance * direction - eye,
+            None => direction,
+        };
+        Some(Ray::new(self.origin + eye, direction))
+    }
+}
@@ -0,0 +1,33 @@
+use crate::basics::{Point3, Ray, Vec3, cross, unit_vec};
+use crate::traits::{Camera, Sampler};
+
+// Parallel rays, from a film `height` high in world units: sizes do not change with the distance
+#[derive(Debug)]
+pub struct OrthographicCamera {
+    lower_left_corner: Point3,
+    horizontal: Vec3,
+    vertical: Vec3,
+    direction: Vec3,
+}
+
+impl OrthographicCamera {
+    pub fn new(aspect_ratio: f64, height: f64, from: Point3, to: Point3, vup: Vec3) -> OrthographicCamera {
+        let w = unit_vec(from - to);
+        let u = unit_vec(cross(vup, w));
+        let v = cross(w, u);
+        let horizontal = aspect_ratio * height * u;
+        let vertical = height * v;
+        OrthographicCamera {
+            lower_left_corner: from - horizontal / 2.0 - vertical / 2.0,
+            horizontal,
+            vertical,
+            direction: -w,
+        }
+    }
+}
+
+impl Camera for OrthographicCamera {
+    fn generate_ray(&self, s: f64, t: f64, _sampler: &mut dyn Sampler) -> Option<Ray> {
+        Some(Ray::new(self.lower_left_corner + s * self.horizontal + t * self.vertical, self.direction))
+    }
+}
@@ -0,0 +1,63 @@
+use crate::basics::{Point3, Ray, Vec3, cross, sample_concentric_disk, unit_vec};
+use crate::traits::{Camera, Dimension, Sampler};
+use crate::utils::degrees_to_radians;
+
+// Pinhole, or thin lens with depth of field
+#[derive(Debug)]
+pub struct PerspectiveCamera {
+	pub ori: Point3,
+	pub lower_left_corner: Point3,
+	pub horizontal: Vec3,
+	pub vertical: Vec3,
+	u: Vec3,
+	v: Vec3,
+	lens_radius: f64,
+}
+
+impl PerspectiveCamera {
+	pub fn new(aspect_ratio: f64, vangle_fov: f64, from: Point3, to: Point3, y: Vec3) -> PerspectiveCamera {
+		PerspectiveCamera::thin_lens(aspect_ratio, vangle_fov, from, to, y, 0.0, 1.0)
+	}
+
+	// Depth of field: rays start on a lens of diameter `aperture`, and are sharp at `focus_dist` from it
+	pub fn thin_lens(aspect_ratio: f64, vangle_fov: f64, from: Point3, to: Point3, y: Vec3, aperture: f64, focus_dist: f64) -> PerspectiveCamera {
+		let theta = degrees_to_radians(vangle_fov);
+		let h = f64::tan(theta / 2.0);
+
+		let viewport_height = 2.0 * h;
+		let viewport_width = aspect_ratio * viewport_height;
+
+		let w = unit_vec(from - to);
+		let u = unit_vec(cross(y, w));
+		let v = cross(w, u);
+
+		// The viewport is moved to the focus plane
+		let origin = from;
+		let horizontal = focus_dist * viewport_width * u;
+		let vertical = focus_dist * viewport_height * v;
+		let lower_left_corner = origin - horizontal / 2.0 - vertical / 2.0 - focus_dist * w;
+
+		PerspectiveCamera { ori: origin,
+			lower_left_corner,
+			horizontal,
+			vertical,
+			u,
+			v,
+			lens_radius: aperture / 2.0 }
+	}
+}
+
+impl Camera for PerspectiveCamera {
+	fn generate_ray(&self, s: f64, t: f64, sampler: &mut dyn Sampler) -> Option<Ray> {
+		let mut origin = self.ori;
+		if self.lens_radius > 0.0 {
+			sampler.start(Dimension::Lens);
+			let ((x, y), _) = sample_concentric_disk(sampler.get_2d());
+			origin += self.lens_radius * (x * self.u + y * self.v);
+		}
+        Some(Ray::new(
+            origin,
+            self.lower_left_corner + s * self.horizontal + t * self.vertical - origin,
+        ))
+    }
+}
@@ -0,0 +1,42 @@
+use crate::basics::Ray;
+use crate::traits::{Camera, Sampler};
+
+// How the two eyes are packed in the image
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StereoLayout {
+    SideBySide, // Left eye on the left half
+    OverUnder,  // Left eye on the top half
+}
+
+// Two cameras sharing the film, one half each
+#[derive(Debug)]
+pub struct StereoCamera {
+    left: Box<dyn Camera>,
+    right: Box<dyn Camera>,
+    layout: StereoLayout,
+}
+
+impl StereoCamera {
+    // The eye cameras are made for the aspect ratio of their half of the image
+    pub fn new(left: Box<dyn Camera>, right: Box<dyn Camera>, layout: StereoLayout) -> StereoCamera {
+        StereoCamera { left, right, layout }
+    }
+}
+
+impl Camera for StereoCamera {
+    fn generate_ray(&self, s: f64, t: f64, sampler: &mut dyn Sampler) -> Option<Ray> {
+        match self.layout {
+            StereoLayout::SideBySide if s < 0.5 => self.left.generate_ray(2.0 * s, t, sampler),
+            StereoLayout::SideBySide => self.right.generate_ray(2.0 * s - 1.0, t, sampler),
+            StereoLayout::OverUnder if t >= 0.5 => self.left.generate_ray(s, 2.0 * t - 1.0, sampler),
+            StereoLayout::OverUnder => self.right.generate_ray(s, 2.0 * t, sampler),
+        }
+    }
+
+    fn views(&self) -> Vec<[f64; 4]> {
+        match self.layout {
+            StereoLayout::SideBySide => vec![[0.0, 0.0, 0.5, 1.0], [0.5, 0.0, 1.0, 1.0]],
+            StereoLayout::OverUnder => vec![[0.0, 0.5, 1.0, 1.0], [0.0, 0.0, 1.0, 0.5]],
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::film::{Film, TileRect};
+use crate::renderer::RenderSettings;
+use crate::scene::Scene;
+use crate::tiles::TileOrder;
+use crate::traits::{Camera, Filter};
+use crate::utils::mix64;
+
+const MAGIC: &[u8; 4] = b"RTCK";
+const VERSION: u32 = 4;
+
+// State of a render: the film after the passes [first_pass, next_pass[ of the pixels of `region`
+// The samples only depend on the seed, the pixel and the sample index, so the film and the passes are enough to resume,
+// and renders of other regions or passes can be added to it
+pub struct Checkpoint {
+    pub scene_hash: u64, // Of everything changing the image; a checkpoint of another scene is refused
+    pub region: TileRect,
+    pub first_pass: u32,
+    pub last_pass: u32, // Passes to render; the render is complete once next_pass gets there
+    pub next_pass: u32,
+}
+
+impl Checkpoint {
+    pub fn is_complete(&self) -> bool {
+        self.next_pass >= self.last_pass
+    }
+
+    // Written next to the target, then renamed: a render killed while saving keeps the previous checkpoint
+    pub fn save(&self, path: &str, film: &Film) -> io::Result<()> {
+        let tmp = Path::new(path).with_extension("tmp");
+        let mut out = BufWriter::new(File::create(&tmp)?);
+        out.write_all(MAGIC)?;
+        out.write_all(&VERSION.to_le_bytes())?;
+        out.write_all(&self.scene_hash.to_le_bytes())?;
+        for v in [film.width, film.height, self.region.x0, self.region.y0, self.region.x1, self.region.y1] {
+            out.write_all(&(v as u64).to_le_bytes())?;
+        }
+        for v in [self.first_pass, self.last_pass, self.next_pass] {
+            out.write_all(&v.to_le_bytes())?;
+        }
+        out.write_all(&[film.has_aovs() as u8])?;
+        out.write_all(&(film.light_paths().len() as u32).to_le_bytes())?;
+        film.write_data(&mut out)?;
+        out.into_inner()?.sync_all()?;
+        fs::rename(tmp, path)
+    }
+
+    // Fills `film` from the checkpoint, if it was saved for an image of the same size,
+    // with AOVs and light path layers if the film has them
+    pub fn load(path: &str, film: &mut Film) -> io::Result<Checkpoint> {
+        let mut input = BufReader::new(File::open(path)?);
+        let mut magic = [0; 4];
+        input.read_exact(&mut magic)?;
+        if &magic != MAGIC || read_u32(&mut input)? != VERSION {
+            return Err(invalid(format!("{} is not a checkpoint of this version", path)));
+        }
+        let scene_hash = read_u64(&mut input)?;
+        let (width, height) = (read_u64(&mut input)?, read_u64(&mut input)?);
+        if (width, height) != (film.width as u64, film.height as u64) {
+            return Err(invalid(format!("{} is a {}x{} image", path, width, height)));
+        }
+        let mut region = [0; 4];
+        for v in &mut region {
+            *v = read_u64(&mut input)? as usize;
+        }
+        let [x0, y0, x1, y1] = region;
+        let checkpoint = Checkpoint {
+            scene_hash,
+            region: TileRect { x0, y0, x1, y1 },
+            first_pass: read_u32(&mut input)?,
+            last_pass: read_u32(&mut input)?,
+            next_pass: read_u32(&mut input)?,
+        };
+        let mut aovs = [0];
+        input.read_exact(&mut aovs)?;
+        if (aovs[0] != 0) != film.has_aovs() {
+            let saved = if aovs[0] != 0 { "with" } else { "without" };
+            return Err(invalid(format!("{} was saved {} AOVs", path, saved)));
+        }
+        let light_paths = read_u32(&mut input)?;
+        if light_paths as usize != film.light_paths().len() {
+            return Err(invalid(format!("{} was saved with {} light path layers", path, light_paths)));
+        }
+        film.read_data(&mut input)?;
+        Ok(checkpoint)
+    }
+
+    // Loads a checkpoint saved for the scene of `scene_hash`
+    pub fn resume(path: &str, scene_hash: u64, film: &mut Film) -> io::Result<Checkpoint> {
+        let checkpoint = Checkpoint::load(path, film)?;
+        if checkpoint.scene_hash != scene_hash {
+            return Err(invalid(format!("{} was saved for another scene or other parameters", path)));
+        }
+        Ok(checkpoint)
+    }
+
+    // Renders sharing pixels and passes would count the same samples twice
+    pub fn overlaps(&self, other: &Checkpoint) -> bool {
+        self.region.intersect(&other.region).is_some() && self.first_pass < other.next_pass && other.first_pass < self.next_pass
+    }
+}
+
+// Adds the renders saved in the checkpoints to `film`: sums and weights add up, so the pixels rendered by several
+// of them (passes split, or the filter margins of regions) get the weighted average of all their samples
+// The result matches a single render up to rounding, not bit for bit: the sums are added in another order,
+// and the statistics of split passes are merged (Chan) instead of accumulated sample by sample (Welford)
+pub fn merge_checkpoints(paths: &[String], scene_hash: u64, film: &mut Film) -> io::Result<Vec<Checkpoint>> {
+    let mut checkpoints: Vec<Checkpoint> = Vec::new();
+    let mut part = film.clone();
+    for path in paths {
+        let checkpoint = Checkpoint::resume(path, scene_hash, &mut part)?;
+        if let Some(k) = checkpoints.iter().position(|c| c.overlaps(&checkpoint)) {
+            return Err(invalid(format!("{} and {} rendered the same samples", paths[k], path)));
+        }
+        film.add(&part);
+        checkpoints.push(checkpoint);
+    }
+    Ok(checkpoints)
+}
+
+// Hash of everything the film depends on: the built scene (objects, materials, lights, atmosphere), the camera,
+// the filter and the render settings, and the tiles, which give the order of the sums
+// Everything is hashed as printed by Debug, which writes the floats exactly
+pub fn scene_hash(scene: &Scene, camera: &dyn Camera, filter: &dyn Filter, settings: &RenderSettings, tile_size: usize, tile_order: TileOrder) -> u64 {
+    let mut hasher = Fnv(0xcbf29ce484222325);
+    let text = format_args!("{:?} {:?} {:?} {:?} {} {:?}", scene, camera, filter, settings, tile_size, tile_order);
+    fmt::Write::write_fmt(&mut hasher, text).expect("hashing the scene");
+    mix64(hasher.0)
+}
+
+// FNV-1a, as hash_bytes, of text written piece by piece: large scenes are not printed in memory
+struct Fnv(u64);
+
+impl fmt::Write for Fnv {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 = s.bytes().fold(self.0, |h, b| (h ^ b as u64).wrapping_mul(0x100000001b3));
+        Ok(())
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn read_u32(input: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    input.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(input: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    input.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
@@ -0,0 +1,193 @@
+use std::time::Duration;
+
+use crate::progress::ProgressFormat;
+use crate::tiles::TileOrder;
+
+pub const USAGE: &str = "Usage: rt [options] > image.ppm
+       rt merge [--aovs <prefix>] [--light-paths <prefix>] <checkpoint>... > image.ppm
+       rt serve [--listen <address:port>]
+  --time <duration>      Stop after the last pass that fits in the budget, e.g. 90s, 10m, 1h30m
+  --snapshot <file.ppm>  Write the image rendered so far to this file as the passes go
+  --tile-size <pixels>   Side of the square tiles given to the workers
+  --tile-order <order>   scanline, spiral or hilbert
+  --threads <count>      Number of workers, all the cores by default
+  --checkpoint <file>    Save the render to this file now and then, and when it ends
+  --resume               Go on with the render saved in the checkpoint
+  --region <x0,y0,x1,y1> Render only the pixels [x0, x1[ x [y0, y1[ of the image, from its top left corner
+  --passes <first..last> Render only the passes [first, last[
+  --aovs <prefix>        Also write the image and its albedo, normal, position, depth and ids as <prefix>.<layer>.pfm
+  --denoise <file.ppm>   Also write the image denoised with the albedo and normals
+  --denoise-strength <s> Lower keeps more detail, higher smooths more: 1.0 by default
+  --heatmap <file.ppm>   Also write the samples spent per pixel, white at the maximum (adaptive sampling)
+  --frames <first..last> Render the frames [first, last[ of the animation, each to its numbered files
+  --light-paths <prefix> Also write the image split by kind of light path and by emitter as <prefix>.<layer>.pfm
+  --progress <format>    human (default) or json, for the progress and the final statistics
+  --view                 Preview in a window, with camera controls (built with --features viewer)
+  merge <checkpoint>...  Combine the checkpoints of renders split by region or passes into the image
+  serve                  Render the scenes posted as JSON to a local HTTP server, 127.0.0.1:8080 by default";
+
+// Command line options, on top of the parameters of main
+#[derive(Default, Debug)]
+pub struct Options {
+    pub time: Option<Duration>,
+    pub snapshot: Option<String>,
+    pub tile_size: Option<usize>,
+    pub tile_order: Option<TileOrder>,
+    pub threads: Option<usize>,
+    pub checkpoint: Option<String>,
+    pub resume: bool,
+    pub region: Option<[usize; 4]>,
+    pub passes: Option<(u32, u32)>,
+    pub aovs: Option<String>, // Prefix of the AOV files
+    pub denoise: Option<String>, // File of the denoised image
+    pub denoise_strength: Option<f64>,
+    pub heatmap: Option<String>, // File of the samples spent per pixel
+    pub light_paths: Option<String>, // Prefix of the light path layer files
+    pub frames: Option<(u32, u32)>, // Frames of the animation to render instead of the image
+    pub progress: Option<ProgressFormat>,
+    pub view: bool, // Interactive preview instead of the render
+    pub merge: Vec<String>, // Checkpoints to merge instead of rendering
+    pub serve: Option<String>, // Address of the render server to run instead of rendering
+}
+
+impl Options {
+    // `args` without the program name
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+        let mut options = Options::default();
+        let mut args = args.into_iter().peekable();
+        if args.next_if(|arg| arg == "merge").is_some() {
+            while let Some(arg) = args.next_if(|arg| arg == "--aovs" || arg == "--light-paths") {
+                let value = Some(args.next().ok_or(format!("missing value for {}", arg))?);
+                if arg == "--aovs" {
+                    options.aovs = value;
+                } else {
+                    options.light_paths = value;
+                }
+            }
+            options.merge = args.collect();
+            if options.merge.is_empty() {
+                return Err("merge needs checkpoint files".to_string());
+            }
+            return Ok(options);
+        }
+        if args.next_if(|arg| arg == "serve").is_some() {
+            options.serve = Some(match (args.next().as_deref(), args.next()) {
+                (None, _) => "127.0.0.1:8080".to_string(),
+                (Some("--listen"), Some(address)) => address,
+                _ => return Err("serve only takes --listen <address:port>".to_string()),
+            });
+            if args.next().is_some() {
+                return Err("serve only takes --listen <address:port>".to_string());
+            }
+            return Ok(options);
+        }
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| args.next().ok_or(format!("missing value for {}", name));
+            match arg.as_str() {
+                "--time" => options.time = Some(parse_duration(&value("--time")?)?),
+                "--snapshot" => options.snapshot = Some(value("--snapshot")?),
+                "--tile-size" => options.tile_size = Some(parse_count("--tile-size", &value("--tile-size")?)?),
+                "--tile-order" => {
+                    let order = value("--tile-order")?;
+                    options.tile_order = Some(TileOrder::parse(&order).ok_or(format!("unknown tile order {}", order))?);
+                }
+                "--threads" => options.threads = Some(parse_count("--threads", &value("--threads")?)?),
+                "--checkpoint" => options.checkpoint = Some(value("--checkpoint")?),
+                "--resume" => options.resume = true,
+                "--region" => options.region = Some(parse_region(&value("--region")?)?),
+                "--passes" => options.passes = Some(parse_range("passes", &value("--passes")?)?),
+                "--frames" => options.frames = Some(parse_range("frames", &value("--frames")?)?),
+                "--aovs" => options.aovs = Some(value("--aovs")?),
+                "--denoise" => options.denoise = Some(value("--denoise")?),
+                "--denoise-strength" => options.denoise_strength = Some(parse_positive("--denoise-strength", &value("--denoise-strength")?)?),
+                "--heatmap" => options.heatmap = Some(value("--heatmap")?),
+                "--light-paths" => options.light_paths = Some(value("--light-paths")?),
+                "--progress" => {
+                    let format = value("--progress")?;
+                    options.progress = Some(ProgressFormat::parse(&format).ok_or(format!("unknown progress format {}", format))?);
+                }
+                "--view" => options.view = true,
+                _ => return Err(format!("unknown argument {}", arg)),
+            }
+        }
+        if options.resume && options.checkpoint.is_none() {
+            return Err("--resume needs a --checkpoint file".to_string());
+        }
+        if options.denoise_strength.is_some() && options.denoise.is_none() {
+            return Err("--denoise-strength needs a --denoise file".to_string());
+        }
+        if (options.region.is_some() || options.passes.is_some()) && options.checkpoint.is_none() {
+            return Err("a partial render is saved in a --checkpoint file, to be merged".to_string());
+        }
+        if options.frames.is_some() && (options.checkpoint.is_some() || options.snapshot.is_some() || options.time.is_some() || options.view) {
+            return Err("--frames renders whole frames: no --checkpoint, --snapshot, --time or --view".to_string());
+        }
+        if options.view && (options.checkpoint.is_some() || options.snapshot.is_some() || options.time.is_some() || options.aovs.is_some() || options.denoise.is_some() || options.heatmap.is_some() || options.light_paths.is_some()) {
+            return Err("--view only takes --threads".to_string());
+        }
+        Ok(options)
+    }
+}
+
+// Positive integer
+fn parse_count(name: &str, text: &str) -> Result<usize, String> {
+    match text.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("invalid value {} for {}", text, name)),
+    }
+}
+
+// Positive finite number
+fn parse_positive(name: &str, text: &str) -> Result<f64, String> {
+    match text.parse::<f64>() {
+        Ok(x) if x > 0.0 && x.is_finite() => Ok(x),
+        _ => Err(format!("invalid value {} for {}", text, name)),
+    }
+}
+
+// "x0,y0,x1,y1", a non empty rectangle
+fn parse_region(text: &str) -> Result<[usize; 4], String> {
+    let invalid = || format!("invalid region {}", text);
+    let values: Vec<usize> = text.split(',').map(|v| v.trim().parse().map_err(|_| invalid())).collect::<Result<_, _>>()?;
+    match values[..] {
+        [x0, y0, x1, y1] if x0 < x1 && y0 < y1 => Ok([x0, y0, x1, y1]),
+        _ => Err(invalid()),
+    }
+}
+
+// "first..last", a non empty range of passes or frames
+fn parse_range(name: &str, text: &str) -> Result<(u32, u32), String> {
+    let invalid = || format!("invalid {} {}", name, text);
+    let (first, last) = text.split_once("..").ok_or_else(invalid)?;
+    match (first.trim().parse(), last.trim().parse()) {
+        (Ok(first), Ok(last)) if first < last => Ok((first, last)),
+        _ => Err(invalid()),
+    }
+}
+
+// Numbers each followed by a unit among h, m and s (seconds if none): "90", "10m", "1h30m", "2.5s"
+pub fn parse_duration(text: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid duration {}", text);
+    let mut seconds = 0.0;
+    let mut number = String::new();
+    for c in text.chars() {
+        let unit = match c {
+            'h' => 3600.0,
+            'm' => 60.0,
+            's' => 1.0,
+            _ => {
+                number.push(c);
+                continue;
+            }
+        };
+        seconds += unit * number.parse::<f64>().map_err(|_| invalid())?;
+        number.clear();
+    }
+    if !number.is_empty() {
+        seconds += number.parse::<f64>().map_err(|_| invalid())?;
+    }
+    if text.is_empty() || !seconds.is_finite() || seconds < 0.0 {
+        return Err(invalid());
+    }
+    Ok(Duration::from_secs_f64(seconds))
+}
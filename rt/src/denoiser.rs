@@ -0,0 +1,153 @@
+use rayon::prelude::*;
+
+use crate::basics::{Color, Vec3, dot, luminance, unit_vec};
+use crate::film::Film;
+
+// B3 spline, the 5 taps of each à-trous level
+const KERNEL: [f64; 5] = [1.0 / 16.0, 1.0 / 4.0, 3.0 / 8.0, 1.0 / 4.0, 1.0 / 16.0];
+// Edge-stopping: exponent of the normal weight, tolerance to albedo and depth (in gradients) differences
+const SIGMA_NORMAL: f64 = 128.0;
+const SIGMA_ALBEDO: f64 = 0.1;
+const SIGMA_DEPTH: f64 = 1.0;
+// Tolerance to luminance differences, in standard deviations, at strength 1.0
+const SIGMA_LUMINANCE: f64 = 4.0;
+
+// Edge-avoiding à-trous wavelet filter (Dammertz et al.), spatial part of SVGF (Schied et al.)
+// Albedo, normals and depth keep the edges, and the variance of each pixel sets how much its color may be averaged
+// The albedo is not divided out as in SVGF: seen through glass it is too noisy, and the quotient spreads the noise
+pub struct Denoiser {
+    pub strength: f64, // Higher smooths more
+    pub iterations: u32, // Levels, each twice as wide as the previous one: 5 covers 61 pixels
+}
+
+// What the filter knows of a pixel
+#[derive(Clone, Copy)]
+struct Texel {
+    color: Color,
+    variance: f64, // Of the luminance of `color`
+    albedo: Color,
+    normal: Vec3, // Unit, 0 where nothing was hit
+    depth: f64,
+    depth_gradient: f64, // Depth change to the next pixel
+}
+
+impl Denoiser {
+    pub fn new(strength: f64) -> Denoiser {
+        Denoiser { strength, iterations: 5 }
+    }
+
+    // Copy of the film with the denoised colors; it must have the AOVs
+    pub fn denoise(&self, film: &Film) -> Film {
+        let (width, height) = (film.width, film.height);
+        let mut texels: Vec<Texel> = (0..width * height)
+            .map(|k| {
+                let (i, j) = (k % width, k / width);
+                let a = film.aov(i, j).expect("denoising needs the AOVs");
+                Texel {
+                    color: film.pixel(i, j),
+                    variance: film.stats(i, j).mean_variance(),
+                    albedo: a.albedo,
+                    normal: if a.normal.length() > 1e-6 { unit_vec(a.normal) } else { Vec3::default() },
+                    depth: a.depth,
+                    depth_gradient: 0.0,
+                }
+            })
+            .collect();
+        for j in 0..height {
+            for i in 0..width {
+                let depth = |i: usize, j: usize| texels[j * width + i].depth;
+                let dx = f64::max((depth(i.saturating_sub(1), j) - depth(i, j)).abs(), (depth((i + 1).min(width - 1), j) - depth(i, j)).abs());
+                let dy = f64::max((depth(i, j.saturating_sub(1)) - depth(i, j)).abs(), (depth(i, (j + 1).min(height - 1)) - depth(i, j)).abs());
+                texels[j * width + i].depth_gradient = f64::max(dx, dy);
+            }
+        }
+        // Pixels with too few samples get the largest variance of the image
+        let max_variance = texels.iter().map(|t| t.variance).filter(|v| v.is_finite()).fold(0.0, f64::max);
+        for texel in &mut texels {
+            if !texel.variance.is_finite() {
+                texel.variance = max_variance;
+            }
+        }
+
+        for level in 0..self.iterations {
+            texels = self.filter_level(&texels, width, height, 1 << level);
+        }
+
+        let mut denoised = film.clone();
+        for j in 0..height {
+            for i in 0..width {
+                denoised.set_pixel(i, j, texels[j * width + i].color);
+            }
+        }
+        denoised
+    }
+
+    // One à-trous level: 5 x 5 taps `step` pixels apart
+    fn filter_level(&self, texels: &[Texel], width: usize, height: usize, step: usize) -> Vec<Texel> {
+        let variances = blurred_variances(texels, width, height);
+        let sigma_luminance = SIGMA_LUMINANCE * self.strength;
+        (0..width * height)
+            .into_par_iter()
+            .map(|k| {
+                let (i, j) = (k % width, k / width);
+                let p = texels[k];
+                let luminance_p = luminance(p.color);
+                let luminance_tolerance = sigma_luminance * variances[k].sqrt() + 1e-6;
+                let mut color = Color::default();
+                let mut variance = 0.0;
+                let mut weights = 0.0;
+                for (dy, ky) in KERNEL.iter().enumerate() {
+                    for (dx, kx) in KERNEL.iter().enumerate() {
+                        let x = i as isize + (dx as isize - 2) * step as isize;
+                        let y = j as isize + (dy as isize - 2) * step as isize;
+                        if x < 0 || y < 0 || x >= width as isize || y >= height as isize {
+                            continue;
+                        }
+                        let q = texels[y as usize * width + x as usize];
+                        let offset = (((dx as f64 - 2.0).powi(2) + (dy as f64 - 2.0).powi(2)).sqrt()) * step as f64;
+
+                        // Background only mixes with background
+                        let normal_weight = match (p.normal.length() > 0.0, q.normal.length() > 0.0) {
+                            (true, true) => dot(p.normal, q.normal).max(0.0).powf(SIGMA_NORMAL),
+                            (false, false) => 1.0,
+                            _ => 0.0,
+                        };
+                        let albedo_weight = f64::exp(-(p.albedo - q.albedo).length_squared() / (SIGMA_ALBEDO * SIGMA_ALBEDO));
+                        let depth_weight = f64::exp(-(p.depth - q.depth).abs() / (SIGMA_DEPTH * p.depth_gradient * offset + 1e-6));
+                        let luminance_weight = f64::exp(-(luminance_p - luminance(q.color)).abs() / luminance_tolerance);
+                        let w = kx * ky * albedo_weight * normal_weight * depth_weight * luminance_weight;
+                        color += w * q.color;
+                        variance += w * w * q.variance;
+                        weights += w;
+                    }
+                }
+                // The center tap has weight kx * ky > 0
+                Texel { color: color / weights, variance: variance / (weights * weights), ..p }
+            })
+            .collect()
+    }
+}
+
+// Variances smoothed by a 3 x 3 Gaussian, more reliable for the edge-stopping
+// The background does not take the variance of the surfaces around it, nor the opposite
+fn blurred_variances(texels: &[Texel], width: usize, height: usize) -> Vec<f64> {
+    const GAUSSIAN: [f64; 3] = [0.25, 0.5, 0.25];
+    (0..width * height)
+        .map(|k| {
+            let (i, j) = (k % width, k / width);
+            let hit = texels[k].normal.length() > 0.0;
+            let mut sum = 0.0;
+            let mut weights = 0.0;
+            for (dy, ky) in GAUSSIAN.iter().enumerate() {
+                for (dx, kx) in GAUSSIAN.iter().enumerate() {
+                    let (x, y) = ((i + dx).wrapping_sub(1), (j + dy).wrapping_sub(1));
+                    if x < width && y < height && (texels[y * width + x].normal.length() > 0.0) == hit {
+                        sum += kx * ky * texels[y * width + x].variance;
+                        weights += kx * ky;
+                    }
+                }
+            }
+            sum / weights
+        })
+        .collect()
+}
@@ -0,0 +1,5 @@
+mod noise;
+mod grid;
+
+pub use noise::*;
+pub use grid::*;
\ No newline at end of file
@@ -0,0 +1,108 @@
+use std::fs;
+use std::io;
+use crate::basics::{Point3, Vec3};
+use crate::traits::DensityField;
+
+// Dense voxel grid stretched over a box, read from a file:
+// an ASCII header line "RTVOL nx ny nz", then nx * ny * nz little-endian f32, x varying first, then y, then z
+#[derive(Debug)]
+pub struct VoxelGrid {
+    nx: usize,
+    ny: usize,
+    nz: usize,
+    values: Vec<f64>,
+    max: f64,
+    min_corner: Point3,
+    size: Vec3,
+}
+
+impl VoxelGrid {
+    // At least one voxel along each axis, and nx * ny * nz values
+    pub fn new(nx: usize, ny: usize, nz: usize, values: Vec<f64>, min_corner: Point3, max_corner: Point3) -> VoxelGrid {
+        assert!(nx >= 1 && ny >= 1 && nz >= 1, "a voxel grid needs voxels along each axis");
+        assert!(nx.checked_mul(ny).and_then(|n| n.checked_mul(nz)) == Some(values.len()), "a {}x{}x{} grid needs as many values", nx, ny, nz);
+        let max = values.iter().cloned().fold(0.0, f64::max);
+        VoxelGrid {
+            nx,
+            ny,
+            nz,
+            values,
+            max,
+            min_corner,
+            size: max_corner - min_corner,
+        }
+    }
+
+    pub fn load(path: &str, min_corner: Point3, max_corner: Point3) -> io::Result<VoxelGrid> {
+        let data = fs::read(path)?;
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path, msg));
+
+        let header_end = data.iter().position(|&b| b == b'\n').ok_or_else(|| invalid("missing header"))?;
+        let header = String::from_utf8_lossy(&data[..header_end]).to_string();
+        let fields: Vec<&str> = header.split_ascii_whitespace().collect();
+        if fields.len() != 4 || fields[0] != "RTVOL" {
+            return Err(invalid("expected \"RTVOL nx ny nz\""));
+        }
+        let dim = |s: &str| s.parse::<usize>().map_err(|_| invalid("bad grid size"));
+        let (nx, ny, nz) = (dim(fields[1])?, dim(fields[2])?, dim(fields[3])?);
+
+        let body = &data[header_end + 1..];
+        let count = nx.checked_mul(ny).and_then(|n| n.checked_mul(nz)).filter(|&n| n > 0 && n.checked_mul(4).is_some());
+        let count = count.ok_or_else(|| invalid("bad grid size"))?;
+        if body.len() < 4 * count {
+            return Err(invalid("missing voxels"));
+        }
+        let values = body
+            .chunks_exact(4)
+            .take(count)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]).max(0.0) as f64)
+            .collect();
+        Ok(VoxelGrid::new(nx, ny, nz, values, min_corner, max_corner))
+    }
+
+    fn voxel(&self, i: usize, j: usize, k: usize) -> f64 {
+        self.values[(k * self.ny + j) * self.nx + i]
+    }
+}
+
+impl DensityField for VoxelGrid {
+    // Trilinear interpolation between voxel centers, 0.0 outside of the box
+    fn density(&self, p: Point3) -> f64 {
+        let rel = p - self.min_corner;
+        let coords = [
+            (rel.x() / self.size.x(), self.nx),
+            (rel.y() / self.size.y(), self.ny),
+            (rel.z() / self.size.z(), self.nz),
+        ];
+        let mut index = [0usize; 3];
+        let mut frac = [0.0; 3];
+        for (axis, &(c, n)) in coords.iter().enumerate() {
+            if !(0.0..=1.0).contains(&c) {
+                return 0.0;
+            }
+            let x = (c * n as f64 - 0.5).clamp(0.0, (n - 1) as f64);
+            index[axis] = (x.floor() as usize).min(n.saturating_sub(2));
+            frac[axis] = if n > 1 { x - index[axis] as f64 } else { 0.0 };
+        }
+
+        let mut accum = 0.0;
+        for dk in 0..2 {
+            for dj in 0..2 {
+                for di in 0..2 {
+                    let i = (index[0] + di).min(self.nx - 1);
+                    let j = (index[1] + dj).min(self.ny - 1);
+                    let k = (index[2] + dk).min(self.nz - 1);
+                    let weight = (if di == 1 { frac[0] } else { 1.0 - frac[0] })
+                        * (if dj == 1 { frac[1] } else { 1.0 - frac[1] })
+                        * (if dk == 1 { frac[2] } else { 1.0 - frac[2] });
+                    accum += weight * self.voxel(i, j, k);
+                }
+            }
+        }
+        accum
+    }
+
+    fn max_density(&self) -> f64 {
+        self.max
+    }
+}
@@ -0,0 +1,40 @@
+use crate::basics::Point3;
+use crate::textures::Perlin;
+use crate::traits::DensityField;
+
+// Cloud-like density from turbulent noise, in [0, 1]
+#[derive(Debug)]
+pub struct NoiseDensity {
+    noise: Perlin,
+    frequency: f64, // Features per unit of distance
+    octaves: usize,
+    threshold: f64, // Turbulence below it is empty space, giving separated puffs
+}
+
+impl NoiseDensity {
+    pub fn new(frequency: f64, octaves: usize, threshold: f64) -> NoiseDensity {
+        NoiseDensity {
+            noise: Perlin::default(),
+            frequency,
+            octaves: octaves.max(1),
+            threshold: threshold.clamp(0.0, 0.99),
+        }
+    }
+
+    // Another shape of cloud
+    pub fn with_seed(mut self, seed: u64) -> NoiseDensity {
+        self.noise = Perlin::new(seed);
+        self
+    }
+}
+
+impl DensityField for NoiseDensity {
+    fn density(&self, p: Point3) -> f64 {
+        let turbulence = self.noise.turbulence(self.frequency * p, self.octaves);
+        ((turbulence - self.threshold) / (1.0 - self.threshold)).clamp(0.0, 1.0)
+    }
+
+    fn max_density(&self) -> f64 {
+        1.0
+    }
+}
@@ -0,0 +1,488 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::basics::{Vec3, cross};
+use crate::camera::{CameraParameters, Convergence, Projection, Stereo, StereoLayout};
+use crate::filters::{BoxFilter, GaussianFilter, LanczosFilter, MitchellFilter, TentFilter};
+use crate::lights::{DirectionalLight, DiskLight, PointLight, RectLight, SpotLight};
+use crate::materials::{Dielectric, DiffuseLight, Isotropic, Lambertian, Metal};
+use crate::renderer::{RenderSettings, Renderer};
+use crate::samplers::SamplerKind;
+use crate::scene::Scene;
+use crate::traits::{Filter, Hittable, Light, Material};
+use crate::volumes::{Atmosphere, ConstantMedium, Cube, Cylinder, CylinderTube, Disk, HittableList, Plane, Sphere, Square};
+
+// Largest image and sample count accepted, so that one description cannot take all the memory or time
+pub const MAX_PIXELS: usize = 4096 * 4096;
+pub const MAX_SAMPLES_PER_PIXEL: i32 = 1 << 16;
+
+// Scene and render parameters as JSON, e.g. for the render server; vectors are [x, y, z]
+// Objects refer to the materials by name
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct SceneDescription {
+    #[serde(default)]
+    pub image: ImageDescription,
+    pub camera: CameraDescription,
+    pub materials: HashMap<String, MaterialDescription>,
+    pub objects: Vec<ObjectDescription>,
+    #[serde(default)]
+    pub lights: Vec<LightDescription>,
+    #[serde(default)]
+    pub atmosphere: Option<AtmosphereDescription>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct ImageDescription {
+    pub width: usize,
+    pub height: usize,
+    pub samples_per_pixel: i32,
+    pub samples_per_pass: i32,
+    pub min_samples_per_pixel: i32,
+    pub noise_threshold: f64,
+    pub max_depth: i32,
+    pub gamma: f64,
+    pub seed: u64,
+    pub sampler: String,
+    pub spectral: bool,
+    pub filter: FilterDescription,
+    pub tile_size: usize,
+}
+
+impl Default for ImageDescription {
+    fn default() -> ImageDescription {
+        ImageDescription {
+            width: 400,
+            height: 225,
+            samples_per_pixel: 64,
+            samples_per_pass: 4,
+            min_samples_per_pixel: 16,
+            noise_threshold: 0.0,
+            max_depth: 50,
+            gamma: 2.0,
+            seed: 0,
+            sampler: "sobol".to_string(),
+            spectral: false,
+            filter: FilterDescription::Box { radius: 0.5 },
+            tile_size: 32,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum FilterDescription {
+    Box { radius: f64 },
+    Tent { radius: f64 },
+    Gaussian { radius: f64, sigma: f64 },
+    Mitchell { radius: f64, b: f64, c: f64 },
+    Lanczos { radius: f64 },
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CameraDescription {
+    pub from: [f64; 3],
+    pub to: [f64; 3],
+    #[serde(default = "default_up")]
+    pub up: [f64; 3],
+    #[serde(default)]
+    pub vfov: Option<f64>, // Perspective only
+    #[serde(default)]
+    pub aperture: f64,
+    #[serde(default = "default_focus_distance")]
+    pub focus_distance: f64,
+    #[serde(default)]
+    pub projection: ProjectionDescription,
+    #[serde(default)]
+    pub stereo: Option<StereoDescription>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct StereoDescription {
+    pub interocular: f64,
+    pub convergence: f64,
+    #[serde(default)]
+    pub mode: ConvergenceDescription,
+    #[serde(default)]
+    pub layout: StereoLayoutDescription,
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ConvergenceDescription {
+    ToeIn,
+    #[default]
+    OffAxis,
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum StereoLayoutDescription {
+    #[default]
+    SideBySide,
+    OverUnder,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ProjectionDescription {
+    #[default]
+    Perspective,
+    Orthographic { height: f64 },
+    Fisheye { fov: f64 },
+    Equirectangular,
+    Cylindrical {
+        #[serde(default = "default_hfov")]
+        hfov: f64,
+    },
+}
+
+fn default_hfov() -> f64 {
+    360.0
+}
+
+fn default_up() -> [f64; 3] {
+    [0.0, 1.0, 0.0]
+}
+
+fn default_focus_distance() -> f64 {
+    1.0
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum MaterialDescription {
+    Lambertian {
+        albedo: [f64; 3],
+    },
+    Metal {
+        albedo: [f64; 3],
+        #[serde(default)]
+        fuzz: f64,
+    },
+    Dielectric {
+        ior: f64,
+        #[serde(default)]
+        fuzz: f64,
+        // Transmittance after `absorption_distance` inside
+        #[serde(default)]
+        transmittance: Option<[f64; 3]>,
+        #[serde(default = "default_absorption_distance")]
+        absorption_distance: f64,
+        #[serde(default)]
+        abbe: Option<f64>,
+    },
+    Light {
+        #[serde(default)]
+        color: Option<[f64; 3]>,
+        // Black body, instead of the color
+        #[serde(default)]
+        kelvin: Option<f64>,
+        #[serde(default = "default_luminance")]
+        luminance: f64,
+    },
+    Isotropic {
+        albedo: [f64; 3],
+    },
+}
+
+fn default_absorption_distance() -> f64 {
+    1.0
+}
+
+fn default_luminance() -> f64 {
+    1.0
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ObjectDescription {
+    Sphere { center: [f64; 3], radius: f64, material: String },
+    Plane { normal: [f64; 3], distance: f64, material: String },
+    Cube { center: [f64; 3], size: f64, #[serde(default)] rotation: [f64; 3], material: String },
+    Cylinder { base: [f64; 3], length: f64, radius: f64, orientation: [f64; 3], material: String },
+    Tube { base: [f64; 3], length: f64, radius: f64, orientation: [f64; 3], material: String },
+    Disk { normal: [f64; 3], distance: f64, center: [f64; 3], radius: f64, material: String },
+    Square { normal: [f64; 3], distance: f64, center: [f64; 3], size: f64, #[serde(default)] angle: f64, material: String },
+    // Smoke filling the boundary, whose material is not used
+    Medium { boundary: Box<ObjectDescription>, density: f64, albedo: [f64; 3] },
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum LightDescription {
+    Point { position: [f64; 3], intensity: [f64; 3] },
+    Spot { position: [f64; 3], direction: [f64; 3], intensity: [f64; 3], inner_angle: f64, outer_angle: f64 },
+    Directional { direction: [f64; 3], irradiance: [f64; 3], #[serde(default)] angular_diameter: f64 },
+    Rect { corner: [f64; 3], edge_u: [f64; 3], edge_v: [f64; 3], radiance: [f64; 3] },
+    Disk { center: [f64; 3], normal: [f64; 3], radius: f64, radiance: [f64; 3] },
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AtmosphereDescription {
+    pub absorption: f64,
+    pub scattering: f64,
+    pub extent: f64,
+}
+
+// What a description gives: the renderer, and how its film is reconstructed and displayed
+pub struct SceneSetup {
+    pub renderer: Renderer,
+    pub filter: Arc<dyn Filter>,
+    pub gamma: f64,
+    pub tile_size: usize,
+}
+
+impl SceneDescription {
+    pub fn from_json(text: &str) -> Result<SceneDescription, String> {
+        serde_json::from_str(text).map_err(|e| e.to_string())
+    }
+
+    // The values are checked here, the constructors expecting sensible ones
+    pub fn build(&self) -> Result<SceneSetup, String> {
+        let image = &self.image;
+        if image.width < 2 || image.height < 2 || image.width.checked_mul(image.height).is_none_or(|n| n > MAX_PIXELS) {
+            return Err(format!("the image must be at least 2x2 and at most {} pixels", MAX_PIXELS));
+        }
+        if !(1..=MAX_SAMPLES_PER_PIXEL).contains(&image.samples_per_pixel) || !(1..=image.samples_per_pixel).contains(&image.samples_per_pass) {
+            return Err(format!("samples_per_pixel must be in [1, {}], samples_per_pass in [1, samples_per_pixel]", MAX_SAMPLES_PER_PIXEL));
+        }
+        if image.max_depth < 1 || image.max_depth > 1000 || image.tile_size == 0 || image.gamma <= 0.0 {
+            return Err("max_depth must be in [1, 1000], tile_size and gamma positive".to_string());
+        }
+        let sampler = SamplerKind::parse(&image.sampler).ok_or(format!("unknown sampler {}", image.sampler))?;
+        let filter = image.filter.build()?;
+
+        let mut materials: HashMap<&str, Arc<dyn Material>> = HashMap::new();
+        for (name, material) in &self.materials {
+            materials.insert(name, material.build()?);
+        }
+        let mut world = HittableList::new();
+        for object in &self.objects {
+            world.add(object.build(&materials)?);
+        }
+        let lights = self.lights.iter().map(LightDescription::build).collect::<Result<Vec<_>, _>>()?;
+        let mut scene = Scene::new(world, lights);
+        if let Some(atmosphere) = &self.atmosphere {
+            if atmosphere.absorption < 0.0 || atmosphere.scattering < 0.0 || atmosphere.extent <= 0.0 {
+                return Err("the atmosphere needs non-negative coefficients and a positive extent".to_string());
+            }
+            scene.atmosphere = Some(Atmosphere::new(atmosphere.absorption, atmosphere.scattering, atmosphere.extent));
+        }
+
+        let camera = &self.camera;
+        let (from, to, up) = (vec(camera.from), vec(camera.to), vec(camera.up));
+        if (from - to).length() == 0.0 || camera.focus_distance <= 0.0 || camera.aperture < 0.0 {
+            return Err("the camera needs distinct from and to, a positive focus distance".to_string());
+        }
+        let projection = camera.projection.build()?;
+        let vfov = match (projection, camera.vfov) {
+            (Projection::Perspective, Some(vfov)) if vfov > 0.0 && vfov < 180.0 => vfov,
+            (Projection::Perspective, _) => return Err("the perspective camera needs a vfov in ]0, 180[".to_string()),
+            (_, Some(_)) => return Err("only the perspective camera takes a vfov".to_string()),
+            (_, None) => 90.0,
+        };
+        let mut parameters = CameraParameters::new(from, to, up, vfov).with_lens(camera.aperture, camera.focus_distance).with_projection(projection);
+        if let Some(stereo) = &camera.stereo {
+            let stereo = stereo.build()?;
+            // Each eye gets whole pixels
+            let split = match stereo.layout {
+                StereoLayout::SideBySide => image.width,
+                StereoLayout::OverUnder => image.height,
+            };
+            if split % 2 != 0 {
+                return Err("the image must split into two halves of whole pixels for the stereo layout".to_string());
+            }
+            parameters = parameters.with_stereo(stereo);
+        }
+        let aspect_ratio = image.width as f64 / image.height as f64;
+        let camera = parameters.camera(aspect_ratio);
+
+        let settings = RenderSettings {
+            width: image.width,
+            height: image.height,
+            samples_per_pixel: image.samples_per_pixel,
+            samples_per_pass: image.samples_per_pass,
+            min_samples_per_pixel: image.min_samples_per_pixel,
+            noise_threshold: image.noise_threshold,
+            max_depth: image.max_depth,
+            spectral: image.spectral,
+            sampler,
+            seed: image.seed,
+        };
+        Ok(SceneSetup {
+            renderer: Renderer::new(settings, Arc::new(scene), camera),
+            filter,
+            gamma: image.gamma,
+            tile_size: image.tile_size,
+        })
+    }
+}
+
+impl ProjectionDescription {
+    fn build(&self) -> Result<Projection, String> {
+        match *self {
+            ProjectionDescription::Perspective => Ok(Projection::Perspective),
+            ProjectionDescription::Orthographic { height } if height > 0.0 => Ok(Projection::Orthographic { height }),
+            ProjectionDescription::Fisheye { fov } if fov > 0.0 && fov <= 360.0 => Ok(Projection::Fisheye { fov }),
+            ProjectionDescription::Equirectangular => Ok(Projection::Equirectangular),
+            ProjectionDescription::Cylindrical { hfov } if hfov > 0.0 && hfov <= 360.0 => Ok(Projection::Cylindrical { hfov }),
+            ProjectionDescription::Orthographic { .. } => Err("the orthographic camera needs a positive height".to_string()),
+            _ => Err("the field of view of the projection must be in ]0, 360]".to_string()),
+        }
+    }
+}
+
+impl StereoDescription {
+    fn build(&self) -> Result<Stereo, String> {
+        // Written so that NaN fails
+        if !(self.interocular >= 0.0 && self.interocular.is_finite() && self.convergence > 0.0 && self.convergence.is_finite()) {
+            return Err("stereo needs a finite non-negative interocular distance and a finite positive convergence distance".to_string());
+        }
+        let mode = match self.mode {
+            ConvergenceDescription::ToeIn => Convergence::ToeIn,
+            ConvergenceDescription::OffAxis => Convergence::OffAxis,
+        };
+        let layout = match self.layout {
+            StereoLayoutDescription::SideBySide => StereoLayout::SideBySide,
+            StereoLayoutDescription::OverUnder => StereoLayout::OverUnder,
+        };
+        Ok(Stereo { mode, layout, ..Stereo::new(self.interocular, self.convergence) })
+    }
+}
+
+impl FilterDescription {
+    fn build(&self) -> Result<Arc<dyn Filter>, String> {
+        let radius = match *self {
+            FilterDescription::Box { radius }
+            | FilterDescription::Tent { radius }
+            | FilterDescription::Gaussian { radius, .. }
+            | FilterDescription::Mitchell { radius, .. }
+            | FilterDescription::Lanczos { radius } => radius,
+        };
+        if !(radius > 0.0 && radius <= 8.0) {
+            return Err("the filter radius must be in ]0, 8]".to_string());
+        }
+        Ok(match *self {
+            FilterDescription::Box { radius } => Arc::new(BoxFilter::new(radius)),
+            FilterDescription::Tent { radius } => Arc::new(TentFilter::new(radius)),
+            FilterDescription::Gaussian { radius, sigma } => Arc::new(GaussianFilter::new(radius, positive(sigma, "sigma")?)),
+            FilterDescription::Mitchell { radius, b, c } => Arc::new(MitchellFilter::new(radius, b, c)),
+            FilterDescription::Lanczos { radius } => Arc::new(LanczosFilter::new(radius)),
+        })
+    }
+}
+
+impl MaterialDescription {
+    fn build(&self) -> Result<Arc<dyn Material>, String> {
+        Ok(match *self {
+            MaterialDescription::Lambertian { albedo } => Arc::new(Lambertian::new(vec(albedo))),
+            MaterialDescription::Metal { albedo, fuzz } => Arc::new(Metal::new(vec(albedo), fuzz)),
+            MaterialDescription::Dielectric { ior, fuzz, transmittance, absorption_distance, abbe } => {
+                let mut glass = Dielectric::new(positive(ior, "ior")?, fuzz);
+                if let Some(transmittance) = transmittance {
+                    glass = glass.with_absorption(vec(transmittance), positive(absorption_distance, "absorption_distance")?);
+                }
+                if let Some(abbe) = abbe {
+                    glass = glass.with_abbe(positive(abbe, "abbe")?);
+                }
+                Arc::new(glass)
+            }
+            MaterialDescription::Light { color, kelvin, luminance } => match (color, kelvin) {
+                (Some(color), None) => Arc::new(DiffuseLight::new(vec(color))),
+                (None, Some(kelvin)) => Arc::new(DiffuseLight::blackbody(positive(kelvin, "kelvin")?, luminance)),
+                _ => return Err("a light needs either a color or a temperature in kelvin".to_string()),
+            },
+            MaterialDescription::Isotropic { albedo } => Arc::new(Isotropic::new(vec(albedo))),
+        })
+    }
+}
+
+impl ObjectDescription {
+    fn build(&self, materials: &HashMap<&str, Arc<dyn Material>>) -> Result<Box<dyn Hittable>, String> {
+        let material = |name: &String| materials.get(name.as_str()).cloned().ok_or(format!("unknown material {}", name));
+        Ok(match self {
+            ObjectDescription::Sphere { center, radius, material: m } => Box::new(Sphere::new(vec(*center), positive(*radius, "radius")?, material(m)?)),
+            ObjectDescription::Plane { normal, distance, material: m } => Box::new(Plane::new(direction(*normal)?, *distance, material(m)?)),
+            ObjectDescription::Cube { center, size, rotation, material: m } => {
+                Box::new(Cube::new(vec(*center), positive(*size, "size")?, vec(*rotation), material(m)?))
+            }
+            ObjectDescription::Cylinder { base, length, radius, orientation, material: m } => Box::new(Cylinder::new(
+                vec(*base),
+                positive(*length, "length")?,
+                positive(*radius, "radius")?,
+                direction(*orientation)?,
+                material(m)?,
+            )),
+            ObjectDescription::Tube { base, length, radius, orientation, material: m } => Box::new(CylinderTube::new(
+                vec(*base),
+                positive(*length, "length")?,
+                positive(*radius, "radius")?,
+                direction(*orientation)?,
+                material(m)?,
+            )),
+            ObjectDescription::Disk { normal, distance, center, radius, material: m } => {
+                Box::new(Disk::new(direction(*normal)?, *distance, vec(*center), positive(*radius, "radius")?, material(m)?))
+            }
+            ObjectDescription::Square { normal, distance, center, size, angle, material: m } => {
+                Box::new(Square::new(direction(*normal)?, *distance, vec(*center), positive(*size, "size")?, *angle, material(m)?))
+            }
+            ObjectDescription::Medium { boundary, density, albedo } => {
+                Box::new(ConstantMedium::new(boundary.build(materials)?, positive(*density, "density")?, vec(*albedo)))
+            }
+        })
+    }
+}
+
+impl LightDescription {
+    fn build(&self) -> Result<Box<dyn Light>, String> {
+        Ok(match *self {
+            LightDescription::Point { position, intensity } => Box::new(PointLight::new(vec(position), vec(intensity))),
+            LightDescription::Spot { position, direction: d, intensity, inner_angle, outer_angle } => {
+                if !(0.0..=outer_angle).contains(&inner_angle) || outer_angle >= 180.0 {
+                    return Err("a spot needs 0 <= inner_angle <= outer_angle < 180".to_string());
+                }
+                Box::new(SpotLight::new(vec(position), direction(d)?, vec(intensity), inner_angle, outer_angle))
+            }
+            LightDescription::Directional { direction: d, irradiance, angular_diameter } => {
+                Box::new(DirectionalLight::new(direction(d)?, vec(irradiance), angular_diameter.max(0.0)))
+            }
+            LightDescription::Rect { corner, edge_u, edge_v, radiance } => {
+                if cross(vec(edge_u), vec(edge_v)).length() == 0.0 {
+                    return Err("a rect light needs two independent edges".to_string());
+                }
+                Box::new(RectLight::new(vec(corner), vec(edge_u), vec(edge_v), vec(radiance)))
+            }
+            LightDescription::Disk { center, normal, radius, radiance } => {
+                Box::new(DiskLight::new(vec(center), direction(normal)?, positive(radius, "radius")?, vec(radiance)))
+            }
+        })
+    }
+}
+
+fn vec(v: [f64; 3]) -> Vec3 {
+    Vec3::new(v[0], v[1], v[2])
+}
+
+// Non-zero vector
+fn direction(v: [f64; 3]) -> Result<Vec3, String> {
+    let d = vec(v);
+    if d.length() > 0.0 && d.length().is_finite() {
+        Ok(d)
+    } else {
+        Err(format!("invalid direction {:?}", v))
+    }
+}
+
+fn positive(x: f64, name: &str) -> Result<f64, String> {
+    if x > 0.0 && x.is_finite() {
+        Ok(x)
+    } else {
+        Err(format!("{} must be positive", name))
+    }
+}
@@ -0,0 +1,667 @@
+use std::collections::BTreeMap;
+use std::io::{self, Read, Write};
+use std::sync::Arc;
+
+use crate::basics::{Color, Point3, Vec3, color_to_rgb8, luminance, write_color};
+use crate::traits::Filter;
+
+// Sum of the weighted samples reaching a pixel
+#[derive(Clone, Copy, Default)]
+pub struct FilmPixel {
+    pub sum: Color,
+    pub weight: f64,
+    pub stats: SampleStats, // Of the samples taken in the pixel itself
+}
+
+// Auxiliary values (AOVs) of a camera ray, or of a pixel: the average of its samples
+#[derive(Clone, Copy, Default)]
+pub struct Aov {
+    pub albedo: Color, // Of the first surface that is not a mirror or glass, white for lights
+    pub normal: Vec3,  // World space, facing the camera
+    pub position: Point3,
+    pub depth: f64, // Distance from the camera
+    pub object_id: u32, // 0 for nothing
+    pub material_id: u32,
+    pub hit: bool,
+}
+
+// Sums of the AOVs of the samples taken in a pixel
+// Averaging ids makes no sense: a pixel has those of its first sample
+#[derive(Clone, Copy)]
+struct AovPixel {
+    albedo: Color,
+    normal: Vec3,
+    position: Point3,
+    depth: f64,
+    count: u32,
+    hits: u32,
+    object_id: u32,
+    material_id: u32,
+    id_sample: u32, // Index of the sample giving the ids
+}
+
+impl Default for AovPixel {
+    fn default() -> AovPixel {
+        AovPixel {
+            albedo: Color::default(),
+            normal: Vec3::default(),
+            position: Point3::default(),
+            depth: 0.0,
+            count: 0,
+            hits: 0,
+            object_id: 0,
+            material_id: 0,
+            id_sample: u32::MAX,
+        }
+    }
+}
+
+impl AovPixel {
+    fn add(&mut self, sample: u32, aov: &Aov) {
+        self.albedo += aov.albedo;
+        self.normal += aov.normal;
+        self.count += 1;
+        if aov.hit {
+            self.position += aov.position;
+            self.depth += aov.depth;
+            self.hits += 1;
+        }
+        if sample < self.id_sample {
+            self.object_id = aov.object_id;
+            self.material_id = aov.material_id;
+            self.id_sample = sample;
+        }
+    }
+
+    fn merge(&mut self, other: &AovPixel) {
+        self.albedo += other.albedo;
+        self.normal += other.normal;
+        self.position += other.position;
+        self.depth += other.depth;
+        self.count += other.count;
+        self.hits += other.hits;
+        if other.id_sample < self.id_sample {
+            self.object_id = other.object_id;
+            self.material_id = other.material_id;
+            self.id_sample = other.id_sample;
+        }
+    }
+
+    // Position and depth are averaged over the samples hitting something, 0 if none did
+    fn average(&self) -> Aov {
+        let n = self.count.max(1) as f64;
+        let hits = self.hits.max(1) as f64;
+        Aov {
+            albedo: self.albedo / n,
+            normal: self.normal / n,
+            position: self.position / hits,
+            depth: self.depth / hits,
+            object_id: self.object_id,
+            material_id: self.material_id,
+            hit: self.hits > 0,
+        }
+    }
+}
+
+// Layers written by write_pfm(): the image, and the AOVs of a film made with_aovs()
+pub const LAYERS: [&str; 7] = ["beauty", "albedo", "normal", "position", "depth", "object_id", "material_id"];
+
+// Image being rendered: each sample is splatted on the pixels within the filter radius,
+// and a pixel is the weighted average of those samples
+// Pixel (i, j) covers [i, i + 1[ x [j, j + 1[ in film coordinates, j going up as the camera v
+#[derive(Clone)]
+pub struct Film {
+    pub width: usize,
+    pub height: usize,
+    filter: Arc<dyn Filter>,
+    pixels: Vec<FilmPixel>,
+    aovs: Option<Vec<AovPixel>>,
+    light_paths: Vec<String>, // Names of the light path layers
+    path_sums: Vec<Color>,    // Their weighted sums, filtered like `pixels`: light_paths.len() per pixel
+}
+
+impl Film {
+    pub fn new(width: usize, height: usize, filter: Arc<dyn Filter>) -> Film {
+        Film {
+            width,
+            height,
+            filter,
+            pixels: vec![FilmPixel::default(); width * height],
+            aovs: None,
+            light_paths: Vec::new(),
+            path_sums: Vec::new(),
+        }
+    }
+
+    // The renderer also collects the AOVs of the samples
+    pub fn with_aovs(mut self) -> Film {
+        self.aovs = Some(vec![AovPixel::default(); self.width * self.height]);
+        self
+    }
+
+    pub fn has_aovs(&self) -> bool {
+        self.aovs.is_some()
+    }
+
+    // The renderer also splits the samples into light path layers (integrator::PATH_KINDS, then the light groups)
+    pub fn with_light_paths(mut self, names: Vec<String>) -> Film {
+        self.path_sums = vec![Color::default(); self.width * self.height * names.len()];
+        self.light_paths = names;
+        self
+    }
+
+    pub fn light_paths(&self) -> &[String] {
+        &self.light_paths
+    }
+
+    // Tile receiving the samples taken in the pixels of `rect`, splatted on them and their neighbors within the radius
+    // It starts with the statistics of its own pixels, which no other tile changes
+    pub fn tile(&self, rect: TileRect) -> FilmTile {
+        let margin = self.filter.radius().ceil() as usize;
+        let x0 = rect.x0.saturating_sub(margin);
+        let y0 = rect.y0.saturating_sub(margin);
+        let x1 = usize::min(rect.x1 + margin, self.width);
+        let y1 = usize::min(rect.y1 + margin, self.height);
+        let mut pixels = vec![FilmPixel::default(); (x1 - x0) * (y1 - y0)];
+        for y in rect.y0..rect.y1 {
+            for x in rect.x0..rect.x1 {
+                pixels[(y - y0) * (x1 - x0) + (x - x0)].stats = self.stats(x, y);
+            }
+        }
+        // AOVs are not filtered: only the tile's own pixels
+        let aovs = self.aovs.as_ref().map(|aovs| {
+            (rect.y0..rect.y1).flat_map(|y| aovs[y * self.width + rect.x0..y * self.width + rect.x1].iter().copied()).collect()
+        });
+        let layers = self.light_paths.len();
+        FilmTile {
+            rect,
+            x0,
+            y0,
+            x1,
+            y1,
+            filter: self.filter.clone(),
+            pixels,
+            aovs,
+            layers,
+            path_sums: vec![Color::default(); (x1 - x0) * (y1 - y0) * layers],
+            views: Vec::new(),
+        }
+    }
+
+    // Tiles overlap by the filter radius: they must be merged in a fixed order for the sums not to depend on the threads
+    pub fn merge(&mut self, tile: FilmTile) {
+        for y in tile.y0..tile.y1 {
+            for x in tile.x0..tile.x1 {
+                let src = tile.pixels[(y - tile.y0) * (tile.x1 - tile.x0) + (x - tile.x0)];
+                let dst = &mut self.pixels[y * self.width + x];
+                dst.sum += src.sum;
+                dst.weight += src.weight;
+                if tile.rect.contains(x, y) {
+                    dst.stats = src.stats;
+                }
+                let (k, n) = ((y - tile.y0) * (tile.x1 - tile.x0) + (x - tile.x0), tile.layers);
+                for (dst, src) in self.path_sums[(y * self.width + x) * n..][..n].iter_mut().zip(&tile.path_sums[k * n..(k + 1) * n]) {
+                    *dst += *src;
+                }
+            }
+        }
+        if let (Some(aovs), Some(tile_aovs)) = (&mut self.aovs, &tile.aovs) {
+            let rect = tile.rect;
+            for y in rect.y0..rect.y1 {
+                let row = &tile_aovs[(y - rect.y0) * (rect.x1 - rect.x0)..(y - rect.y0 + 1) * (rect.x1 - rect.x0)];
+                aovs[y * self.width + rect.x0..y * self.width + rect.x1].copy_from_slice(row);
+            }
+        }
+    }
+
+    // Adds the samples of another render of the same image, e.g. of other passes or another region
+    // Equal to rendering both into one film up to rounding: the order of the additions differs
+    pub fn add(&mut self, other: &Film) {
+        for (dst, src) in self.pixels.iter_mut().zip(&other.pixels) {
+            dst.sum += src.sum;
+            dst.weight += src.weight;
+            dst.stats.merge(&src.stats);
+        }
+        for (dst, src) in self.path_sums.iter_mut().zip(&other.path_sums) {
+            *dst += *src;
+        }
+        if let (Some(aovs), Some(other_aovs)) = (&mut self.aovs, &other.aovs) {
+            for (dst, src) in aovs.iter_mut().zip(other_aovs) {
+                dst.merge(src);
+            }
+        }
+    }
+
+    // Reconstructed color; negative lobes may give negative values, clamped to black
+    pub fn pixel(&self, i: usize, j: usize) -> Color {
+        let p = self.pixels[j * self.width + i];
+        if p.weight <= 0.0 {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+        let c = p.sum / p.weight;
+        Color::new(f64::max(0.0, c.x()), f64::max(0.0, c.y()), f64::max(0.0, c.z()))
+    }
+
+    // Replaces the reconstructed color, e.g. by a denoised one
+    pub fn set_pixel(&mut self, i: usize, j: usize, color: Color) {
+        let p = &mut self.pixels[j * self.width + i];
+        p.sum = color;
+        p.weight = 1.0;
+    }
+
+    pub fn samples(&self, i: usize, j: usize) -> u32 {
+        self.pixels[j * self.width + i].stats.count()
+    }
+
+    pub fn stats(&self, i: usize, j: usize) -> SampleStats {
+        self.pixels[j * self.width + i].stats
+    }
+
+    pub fn aov(&self, i: usize, j: usize) -> Option<Aov> {
+        self.aovs.as_ref().map(|aovs| aovs[j * self.width + i].average())
+    }
+
+    // Reconstructed light path layer `layer`; not clamped, for the layers to sum to the unclamped color
+    pub fn light_path(&self, i: usize, j: usize, layer: usize) -> Color {
+        let weight = self.pixels[j * self.width + i].weight;
+        if weight <= 0.0 {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+        self.path_sums[(j * self.width + i) * self.light_paths.len() + layer] / weight
+    }
+
+    // Raw accumulated values, in native precision: reading them back gives the same film
+    pub fn write_data(&self, out: &mut impl Write) -> io::Result<()> {
+        for p in &self.pixels {
+            for v in [p.sum.x(), p.sum.y(), p.sum.z(), p.weight, p.stats.mean, p.stats.m2] {
+                out.write_all(&v.to_le_bytes())?;
+            }
+            out.write_all(&p.stats.count.to_le_bytes())?;
+        }
+        for a in self.aovs.iter().flatten() {
+            for v in [a.albedo.x(), a.albedo.y(), a.albedo.z(), a.normal.x(), a.normal.y(), a.normal.z()] {
+                out.write_all(&v.to_le_bytes())?;
+            }
+            for v in [a.position.x(), a.position.y(), a.position.z(), a.depth] {
+                out.write_all(&v.to_le_bytes())?;
+            }
+            for v in [a.count, a.hits, a.object_id, a.material_id, a.id_sample] {
+                out.write_all(&v.to_le_bytes())?;
+            }
+        }
+        for c in &self.path_sums {
+            for v in [c.x(), c.y(), c.z()] {
+                out.write_all(&v.to_le_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    // Replaces the values of the film by those written by write_data() for a film of the same size
+    pub fn read_data(&mut self, input: &mut impl Read) -> io::Result<()> {
+        for p in &mut self.pixels {
+            let mut v = [0.0; 6];
+            for x in &mut v {
+                *x = read_f64(input)?;
+            }
+            let mut count = [0; 4];
+            input.read_exact(&mut count)?;
+            *p = FilmPixel {
+                sum: Color::new(v[0], v[1], v[2]),
+                weight: v[3],
+                stats: SampleStats {
+                    count: u32::from_le_bytes(count),
+                    mean: v[4],
+                    m2: v[5],
+                },
+            };
+        }
+        for a in self.aovs.iter_mut().flatten() {
+            let mut v = [0.0; 10];
+            for x in &mut v {
+                *x = read_f64(input)?;
+            }
+            let mut n = [0; 5];
+            for x in &mut n {
+                let mut bytes = [0; 4];
+                input.read_exact(&mut bytes)?;
+                *x = u32::from_le_bytes(bytes);
+            }
+            *a = AovPixel {
+                albedo: Color::new(v[0], v[1], v[2]),
+                normal: Vec3::new(v[3], v[4], v[5]),
+                position: Point3::new(v[6], v[7], v[8]),
+                depth: v[9],
+                count: n[0],
+                hits: n[1],
+                object_id: n[2],
+                material_id: n[3],
+                id_sample: n[4],
+            };
+        }
+        for c in &mut self.path_sums {
+            *c = Color::new(read_f64(input)?, read_f64(input)?, read_f64(input)?);
+        }
+        Ok(())
+    }
+
+    // Current image as a .ppm, top row first
+    pub fn write_ppm(&self, out: &mut impl Write, color_filter: Color, gamma: f64) -> io::Result<()> {
+        writeln!(out, "P3\n{} {}\n255", self.width, self.height)?;
+        for j in (0..self.height).rev() {
+            for i in 0..self.width {
+                write_color(out, self.pixel(i, j) * color_filter, 1, gamma);
+            }
+        }
+        out.flush()
+    }
+
+    // Current image, 3 bytes per pixel, top row first
+    pub fn to_rgb8(&self, color_filter: Color, gamma: f64) -> Vec<u8> {
+        let mut data = Vec::with_capacity(3 * self.width * self.height);
+        for j in (0..self.height).rev() {
+            for i in 0..self.width {
+                data.extend(color_to_rgb8(self.pixel(i, j) * color_filter, gamma));
+            }
+        }
+        data
+    }
+
+    // Current image as a .png, for previews
+    pub fn write_png(&self, out: impl Write, color_filter: Color, gamma: f64) -> io::Result<()> {
+        let data = self.to_rgb8(color_filter, gamma);
+        let mut encoder = png::Encoder::new(out, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        writer.write_image_data(&data).map_err(io::Error::other)
+    }
+
+    // Layer of LAYERS or of the light paths as a Portable Float Map: linear values, bottom row first like the film
+    // Scalars (depth, ids) are written in the three channels
+    pub fn write_pfm(&self, out: &mut impl Write, layer: &str, color_filter: Color) -> io::Result<()> {
+        let light_path = self.light_paths.iter().position(|name| name == layer);
+        if !LAYERS.contains(&layer) && light_path.is_none() {
+            return Err(io::Error::other(format!("unknown layer {}", layer)));
+        }
+        if layer != "beauty" && light_path.is_none() && !self.has_aovs() {
+            return Err(io::Error::other("the film has no AOVs"));
+        }
+        let value = |i: usize, j: usize| -> Color {
+            if let Some(k) = light_path {
+                return self.light_path(i, j, k) * color_filter;
+            }
+            let aov = self.aov(i, j).unwrap_or_default();
+            let scalar = |v: f64| Color::new(v, v, v);
+            match layer {
+                "beauty" => self.pixel(i, j) * color_filter,
+                "albedo" => aov.albedo,
+                "normal" => aov.normal,
+                "position" => aov.position,
+                "depth" => scalar(aov.depth),
+                "object_id" => scalar(aov.object_id as f64),
+                "material_id" => scalar(aov.material_id as f64),
+                _ => unreachable!("unknown layer {}", layer),
+            }
+        };
+        write!(out, "PF\n{} {}\n-1.0\n", self.width, self.height)?;
+        for j in 0..self.height {
+            for i in 0..self.width {
+                let c = value(i, j);
+                for v in [c.x(), c.y(), c.z()] {
+                    out.write_all(&(v as f32).to_le_bytes())?;
+                }
+            }
+        }
+        out.flush()
+    }
+
+    // Samples spent per pixel, as a .ppm from black (none) through blue, green and red to white (max_samples)
+    pub fn write_heatmap(&self, out: &mut impl Write, max_samples: u32) -> io::Result<()> {
+        writeln!(out, "P3\n{} {}\n255", self.width, self.height)?;
+        for j in (0..self.height).rev() {
+            for i in 0..self.width {
+                let c = heat_color(self.samples(i, j) as f64 / max_samples.max(1) as f64);
+                writeln!(out, "{} {} {}", (255.0 * c.x()) as u8, (255.0 * c.y()) as u8, (255.0 * c.z()) as u8)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn read_f64(input: &mut impl Read) -> io::Result<f64> {
+    let mut bytes = [0; 8];
+    input.read_exact(&mut bytes)?;
+    Ok(f64::from_le_bytes(bytes))
+}
+
+// Color ramp over [0, 1]
+fn heat_color(t: f64) -> Color {
+    const RAMP: [(f64, f64, f64); 5] = [(0.0, 0.0, 0.0), (0.0, 0.0, 1.0), (0.0, 1.0, 0.0), (1.0, 0.0, 0.0), (1.0, 1.0, 1.0)];
+    let x = t.clamp(0.0, 1.0) * (RAMP.len() - 1) as f64;
+    let k = usize::min(x as usize, RAMP.len() - 2);
+    let f = x - k as f64;
+    let (a, b) = (RAMP[k], RAMP[k + 1]);
+    Color::new(a.0 + f * (b.0 - a.0), a.1 + f * (b.1 - a.1), a.2 + f * (b.2 - a.2))
+}
+
+// Running mean and variance of the luminance of a pixel's samples (Welford)
+#[derive(Clone, Copy, Default)]
+pub struct SampleStats {
+    count: u32,
+    mean: f64,
+    m2: f64,
+}
+
+impl SampleStats {
+    pub fn add(&mut self, color: Color) {
+        let y = luminance(color);
+        self.count += 1;
+        let delta = y - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (y - self.mean);
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    // Statistics of both sets of samples (Chan et al.)
+    pub fn merge(&mut self, other: &SampleStats) {
+        if other.count == 0 {
+            return;
+        }
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        self.mean += delta * other.count as f64 / count as f64;
+        self.m2 += other.m2 + delta * delta * self.count as f64 * other.count as f64 / count as f64;
+        self.count = count;
+    }
+
+    pub fn merged(mut self, other: &SampleStats) -> SampleStats {
+        self.merge(other);
+        self
+    }
+
+    // Variance of the mean luminance; infinite before 2 samples
+    pub fn mean_variance(&self) -> f64 {
+        if self.count < 2 {
+            return f64::INFINITY;
+        }
+        self.m2 / (self.count - 1) as f64 / self.count as f64
+    }
+
+    // Mean luminance
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    // Half-width of the 95% confidence interval of the mean; infinite before 2 samples
+    pub fn error(&self) -> f64 {
+        1.96 * f64::sqrt(self.mean_variance())
+    }
+}
+
+// Pixels [x0, x1[ x [y0, y1[ of the film
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TileRect {
+    pub x0: usize,
+    pub y0: usize,
+    pub x1: usize,
+    pub y1: usize,
+}
+
+impl TileRect {
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x >= self.x0 && x < self.x1 && y >= self.y0 && y < self.y1
+    }
+
+    pub fn intersect(&self, other: &TileRect) -> Option<TileRect> {
+        let rect = TileRect {
+            x0: usize::max(self.x0, other.x0),
+            y0: usize::max(self.y0, other.y0),
+            x1: usize::min(self.x1, other.x1),
+            y1: usize::min(self.y1, other.y1),
+        };
+        (rect.x0 < rect.x1 && rect.y0 < rect.y1).then_some(rect)
+    }
+}
+
+// Part of the film written by one worker
+pub struct FilmTile {
+    pub rect: TileRect, // Pixels sampled by the tile, without the margin
+    x0: usize,
+    y0: usize,
+    x1: usize,
+    y1: usize,
+    filter: Arc<dyn Filter>,
+    pixels: Vec<FilmPixel>,
+    aovs: Option<Vec<AovPixel>>, // Of the pixels of `rect`
+    layers: usize,               // Light path layers
+    path_sums: Vec<Color>,       // `layers` per pixel
+    views: Vec<TileRect>,        // A sample only reaches the pixels of the view of its own pixel
+}
+
+impl FilmTile {
+    pub fn with_views(mut self, views: &[TileRect]) -> FilmTile {
+        self.views = views.to_vec();
+        self
+    }
+
+    pub fn has_aovs(&self) -> bool {
+        self.aovs.is_some()
+    }
+
+    pub fn light_path_count(&self) -> usize {
+        self.layers
+    }
+
+    // AOVs of sample `sample` of pixel (i, j) of the tile's rect
+    pub fn add_aov(&mut self, i: usize, j: usize, sample: u32, aov: &Aov) {
+        let rect = self.rect;
+        if let Some(aovs) = &mut self.aovs {
+            aovs[(j - rect.y0) * (rect.x1 - rect.x0) + (i - rect.x0)].add(sample, aov);
+        }
+    }
+
+    // Statistics of all the samples taken in pixel (i, j) of the tile
+    pub fn stats(&self, i: usize, j: usize) -> SampleStats {
+        self.pixels[(j - self.y0) * (self.x1 - self.x0) + (i - self.x0)].stats
+    }
+
+    // Sample at (x, y) in film coordinates, with its light path layers if the film has them
+    pub fn add_sample(&mut self, x: f64, y: f64, color: Color, light_paths: &[Color]) {
+        let (px, py) = (x.floor(), y.floor());
+        if px >= 0.0 && py >= 0.0 && self.rect.contains(px as usize, py as usize) {
+            self.pixels[(py as usize - self.y0) * (self.x1 - self.x0) + (px as usize - self.x0)].stats.add(color);
+        }
+
+        let radius = self.filter.radius();
+        let (mut x0, mut y0, mut x1, mut y1) = (self.x0, self.y0, self.x1, self.y1);
+        if let Some(view) = self.views.iter().find(|view| px >= 0.0 && py >= 0.0 && view.contains(px as usize, py as usize)) {
+            (x0, y0) = (usize::max(x0, view.x0), usize::max(y0, view.y0));
+            (x1, y1) = (usize::min(x1, view.x1), usize::min(y1, view.y1));
+        }
+        // Pixels whose center is within the radius
+        let i_min = f64::max((x - 0.5 - radius).ceil(), x0 as f64) as usize;
+        let i_max = f64::min((x - 0.5 + radius).floor(), x1 as f64 - 1.0);
+        let j_min = f64::max((y - 0.5 - radius).ceil(), y0 as f64) as usize;
+        let j_max = f64::min((y - 0.5 + radius).floor(), y1 as f64 - 1.0);
+        if i_max < 0.0 || j_max < 0.0 {
+            return;
+        }
+
+        for j in j_min..=j_max as usize {
+            for i in i_min..=i_max as usize {
+                let weight = self.filter.evaluate(x - (i as f64 + 0.5), y - (j as f64 + 0.5));
+                if weight == 0.0 {
+                    continue;
+                }
+                let k = (j - self.y0) * (self.x1 - self.x0) + (i - self.x0);
+                let p = &mut self.pixels[k];
+                p.sum += weight * color;
+                p.weight += weight;
+                for (sum, c) in self.path_sums[k * self.layers..(k + 1) * self.layers].iter_mut().zip(light_paths) {
+                    *sum += weight * *c;
+                }
+            }
+        }
+    }
+}
+
+// Merges the tiles in their index order, whatever the order the workers finish them in
+pub struct TileMerger {
+    pub film: Film,
+    next: usize,
+    end: usize, // Tiles from this index on are dropped
+    pending: BTreeMap<usize, FilmTile>,
+}
+
+impl TileMerger {
+    pub fn new(film: Film) -> TileMerger {
+        TileMerger {
+            film,
+            next: 0,
+            end: usize::MAX,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    pub fn submit(&mut self, index: usize, tile: FilmTile) {
+        if index < self.end {
+            self.pending.insert(index, tile);
+        }
+    }
+
+    // Merges the next tile if it was submitted: `submit(index, tile)` then `while merge_next() {}` merges all they can
+    pub fn merge_next(&mut self) -> bool {
+        let Some(tile) = self.pending.remove(&self.next) else {
+            return false;
+        };
+        self.film.merge(tile);
+        self.next += 1;
+        true
+    }
+
+    // Tiles merged: all those before this index
+    pub fn merged(&self) -> usize {
+        self.next
+    }
+
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    // The film keeps the tiles merged so far, the others are dropped
+    pub fn stop(&mut self) {
+        self.end = self.next;
+        self.pending.clear();
+    }
+
+    // Indexes start again from 0, for another render into the same film
+    pub fn restart(&mut self) {
+        self.next = 0;
+        self.end = usize::MAX;
+        self.pending.clear();
+    }
+}
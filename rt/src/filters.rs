@@ -0,0 +1,11 @@
+mod box_filter;
+mod tent;
+mod gaussian;
+mod mitchell;
+mod lanczos;
+
+pub use box_filter::*;
+pub use tent::*;
+pub use gaussian::*;
+pub use mitchell::*;
+pub use lanczos::*;
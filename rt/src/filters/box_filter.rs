@@ -0,0 +1,28 @@
+use crate::traits::Filter;
+
+// Same weight for every sample in the square; a radius of 0.5 averages the samples of each pixel
+#[derive(Debug)]
+pub struct BoxFilter {
+    radius: f64,
+}
+
+impl BoxFilter {
+    pub fn new(radius: f64) -> BoxFilter {
+        BoxFilter { radius }
+    }
+}
+
+impl Filter for BoxFilter {
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    // Half-open, so that a sample on the border of two pixels counts once
+    fn evaluate(&self, x: f64, y: f64) -> f64 {
+        if (-self.radius..self.radius).contains(&x) && (-self.radius..self.radius).contains(&y) {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
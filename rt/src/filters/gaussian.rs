@@ -0,0 +1,33 @@
+use crate::traits::Filter;
+
+// Gaussian of standard deviation sigma, shifted to reach 0 at the radius
+#[derive(Debug)]
+pub struct GaussianFilter {
+    radius: f64,
+    sigma: f64,
+    exp_radius: f64,
+}
+
+impl GaussianFilter {
+    pub fn new(radius: f64, sigma: f64) -> GaussianFilter {
+        GaussianFilter {
+            radius,
+            sigma,
+            exp_radius: gaussian(radius, sigma),
+        }
+    }
+}
+
+fn gaussian(x: f64, sigma: f64) -> f64 {
+    f64::exp(-x * x / (2.0 * sigma * sigma))
+}
+
+impl Filter for GaussianFilter {
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn evaluate(&self, x: f64, y: f64) -> f64 {
+        f64::max(0.0, gaussian(x, self.sigma) - self.exp_radius) * f64::max(0.0, gaussian(y, self.sigma) - self.exp_radius)
+    }
+}
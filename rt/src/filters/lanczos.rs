@@ -0,0 +1,38 @@
+use crate::traits::Filter;
+use crate::utils::PI;
+
+// Sinc windowed by a wider sinc (Lanczos), with as many lobes as the radius
+#[derive(Debug)]
+pub struct LanczosFilter {
+    radius: f64,
+}
+
+impl LanczosFilter {
+    pub fn new(radius: f64) -> LanczosFilter {
+        LanczosFilter { radius }
+    }
+
+    fn lanczos_1d(&self, x: f64) -> f64 {
+        if x.abs() >= self.radius {
+            return 0.0;
+        }
+        sinc(x) * sinc(x / self.radius)
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1.0e-5 {
+        return 1.0;
+    }
+    f64::sin(PI * x) / (PI * x)
+}
+
+impl Filter for LanczosFilter {
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn evaluate(&self, x: f64, y: f64) -> f64 {
+        self.lanczos_1d(x) * self.lanczos_1d(y)
+    }
+}
@@ -0,0 +1,23 @@
+use crate::traits::Filter;
+
+// Weight decreasing linearly to 0 at the radius (bilinear)
+#[derive(Debug)]
+pub struct TentFilter {
+    radius: f64,
+}
+
+impl TentFilter {
+    pub fn new(radius: f64) -> TentFilter {
+        TentFilter { radius }
+    }
+}
+
+impl Filter for TentFilter {
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn evaluate(&self, x: f64, y: f64) -> f64 {
+        f64::max(0.0, self.radius - x.abs()) * f64::max(0.0, self.radius - y.abs())
+    }
+}
@@ -0,0 +1,276 @@
+use crate::basics::{Color, Ray, dot, to_spectral, unit_vec};
+use crate::film::Aov;
+use crate::scene::Scene;
+use crate::traits::{Dimension, HitRecord, Hittable, Material, Sampler};
+use crate::utils::{INFINITY, power_heuristic};
+
+// Counts of the rays traced, for the statistics of a render
+#[derive(Clone, Copy, Default, Debug)]
+pub struct PathStats {
+    pub samples: u64,        // Paths, one primary ray each
+    pub rays: u64,           // Primary and secondary
+    pub shadow_rays: u64,
+    pub max_depth_cut: u64,  // Paths stopped by the maximum depth
+}
+
+impl PathStats {
+    pub fn merge(&mut self, other: &PathStats) {
+        self.samples += other.samples;
+        self.rays += other.rays;
+        self.shadow_rays += other.shadow_rays;
+        self.max_depth_cut += other.max_depth_cut;
+    }
+
+    // Segments per path
+    pub fn average_depth(&self) -> f64 {
+        if self.samples == 0 { 0.0 } else { self.rays as f64 / self.samples as f64 }
+    }
+}
+
+// Light path layers: kinds of paths, after the first scattering event of the camera path
+// Emission is the light seen directly, direct diffuse the light reaching the camera after one diffuse bounce
+pub const PATH_KINDS: [&str; 5] = ["emission", "direct_diffuse", "indirect_diffuse", "specular", "transmission"];
+
+// First scattering event of a camera path; the scattering in media counts as diffuse
+#[derive(Clone, Copy)]
+enum Lobe {
+    Diffuse,
+    Reflection,   // Specular
+    Transmission, // Specular
+}
+
+// What a path carries besides its ray
+pub struct PathState<'a> {
+    pub stats: &'a mut PathStats,
+    // Contributions by kind (PATH_KINDS), then by light group (Scene::light_groups): each split sums to the color
+    layers: Option<&'a mut [Color]>,
+    throughput: Color, // From the camera to the current vertex
+    vertex: u32,       // 0 at the first hit
+    lobe: Option<Lobe>,
+}
+
+impl<'a> PathState<'a> {
+    pub fn new(stats: &'a mut PathStats, layers: Option<&'a mut [Color]>) -> PathState<'a> {
+        PathState { stats, layers, throughput: Color::new(1.0, 1.0, 1.0), vertex: 0, lobe: None }
+    }
+
+    // Contributions recorded so far, none without light path layers
+    pub fn layers_mut(&mut self) -> &mut [Color] {
+        self.layers.as_deref_mut().unwrap_or_default()
+    }
+
+    // Emission of light group `group` reaching the path at `vertex`, weighted at the current vertex: times its throughput
+    fn record(&mut self, group: usize, vertex: u32, value: Color) {
+        if let Some(layers) = &mut self.layers {
+            let kind = match (vertex, self.lobe) {
+                (0, _) | (_, None) => 0,
+                (1, Some(Lobe::Diffuse)) => 1,
+                (_, Some(Lobe::Diffuse)) => 2,
+                (_, Some(Lobe::Reflection)) => 3,
+                (_, Some(Lobe::Transmission)) => 4,
+            };
+            let contribution = self.throughput * value;
+            layers[kind] += contribution;
+            layers[PATH_KINDS.len() + group] += contribution;
+        }
+    }
+}
+
+// Verify each hit for the trajectory of the ray
+// `bsdf_pdf` is the pdf of the previous bounce, when it was not specular: emission found
+// by the bounce is then weighted against light sampling (multiple importance sampling)
+pub fn ray_color(r: &Ray, scene: &Scene, depth: i32, bsdf_pdf: Option<f64>, sampler: &mut dyn Sampler, path: &mut PathState) -> Color {
+    if depth <= 0 {
+        path.stats.max_depth_cut += 1;
+        return Color::new(0.0, 0.0, 0.0);
+    }
+    path.stats.rays += 1;
+    let vertex = path.vertex;
+
+    let mis_weight = match bsdf_pdf {
+        Some(bsdf_pdf) => power_heuristic(bsdf_pdf, scene.light_pdf(r.origin(), r.direction(), sampler)),
+        None => 1.0,
+    };
+
+    sampler.start(Dimension::Medium);
+    let mut rec = HitRecord::new();
+    let mut hit = scene.world.hit(r, 0.001, INFINITY, &mut rec, sampler);
+
+    // Collision with the global fog before the object: it scatters there instead
+    if let Some(atmosphere) = &scene.atmosphere {
+        let t_max = if hit { rec.t } else { INFINITY };
+        if let Some(t) = atmosphere.sample_event(r, t_max, sampler) {
+            rec = HitRecord::new();
+            rec.t = t;
+            rec.p = r.at(t);
+            rec.normal = -unit_vec(r.direction());
+            rec.front = true;
+            rec.mat = Some(atmosphere.phase_function());
+            hit = true;
+        }
+    }
+
+    // Inside colored glass: what reaches the origin of the ray was absorbed along the way (Beer-Lambert)
+    let absorbed = match r.absorption() {
+        Some(sigma) if sigma.length_squared() > 0.0 => {
+            let distance = if hit { rec.t * r.world_length() } else { INFINITY };
+            let transmittance = |sigma: f64| if sigma > 0.0 { (-sigma * distance).exp() } else { 1.0 };
+            to_spectral(Color::new(transmittance(sigma.x()), transmittance(sigma.y()), transmittance(sigma.z())), r.wavelength())
+        }
+        _ => Color::new(1.0, 1.0, 1.0),
+    };
+    path.throughput = path.throughput * absorbed;
+
+    if hit {
+        let mat = rec.mat.clone().unwrap();
+        // Emission of media (fire) is only found by scattered rays: no MIS
+        let surface_weight = if mat.is_emissive() { mis_weight } else { 1.0 };
+        let mut emitted = surface_weight * mat.emission(r, &rec);
+        path.record(scene.object_light_group(rec.object_id), vertex, emitted);
+        emitted += mis_weight * scene.lights_emitted(r, rec.t, &mut |group, radiance| path.record(group, vertex, mis_weight * radiance));
+
+        let mut attenuation = Color::default();
+        let mut scattered = Ray::default();
+        sampler.start(Dimension::Bsdf);
+        if !mat.scatter(r, &rec, &mut attenuation, &mut scattered, sampler) {
+            return absorbed * emitted;
+        }
+        // The path keeps the wavelength it was restricted to (spectral mode, dispersion), and the medium it is in
+        if scattered.wavelength().is_none() {
+            scattered.set_wavelength(r.wavelength());
+        }
+        if scattered.absorption().is_none() {
+            scattered.set_absorption(r.absorption());
+        }
+        attenuation = to_spectral(attenuation, r.wavelength());
+
+        let pdf = mat.scattering_pdf(r, &rec, &scattered);
+        if vertex == 0 {
+            path.lobe = Some(if pdf > 0.0 {
+                Lobe::Diffuse
+            } else if dot(scattered.direction(), rec.normal) > 0.0 {
+                Lobe::Reflection
+            } else {
+                Lobe::Transmission
+            });
+        }
+        if pdf <= 0.0 || scene.light_count() == 0 {
+            // Specular: only the scattered ray can find the lights
+            sampler.next_bounce();
+            path.throughput = path.throughput * attenuation;
+            path.vertex += 1;
+            return absorbed * (emitted + attenuation * ray_color(&scattered, scene, depth - 1, None, sampler, path));
+        }
+
+        sampler.start(Dimension::Light);
+        let direct = sample_lights(r, &rec, mat.as_ref(), attenuation, scene, sampler, path);
+        sampler.next_bounce();
+        path.throughput = path.throughput * attenuation;
+        path.vertex += 1;
+        return absorbed * (emitted + direct + attenuation * ray_color(&scattered, scene, depth - 1, Some(pdf), sampler, path));
+    }
+    // Background color can be inputed here (currently sky-like or black):
+
+    // Sky
+    /* let unit_direction = unit_vec(r.direction());
+    let t = 0.5 * (unit_direction.y() + 1.0);
+    (1.0 - t) * Color::new(1.0, 1.0, 1.0) + t * Color::new(0.5, 0.7, 1.0) */
+
+    // Black, plus the lights seen in that direction (sun)
+    absorbed * mis_weight * scene.lights_emitted(r, INFINITY, &mut |group, radiance| path.record(group, vertex, mis_weight * radiance))
+}
+
+// Specular bounces followed to find the albedo of the first non-specular surface
+const MAX_SPECULAR_BOUNCES: i32 = 8;
+
+// Auxiliary values of the first hit of a camera ray, for compositing and denoising
+pub fn first_hit(r: &Ray, scene: &Scene, sampler: &mut dyn Sampler) -> Aov {
+    sampler.start(Dimension::Medium);
+    let mut rec = HitRecord::new();
+    if !scene.world.hit(r, 0.001, INFINITY, &mut rec, sampler) {
+        return Aov::default();
+    }
+    let mut aov = Aov {
+        albedo: Color::new(0.0, 0.0, 0.0),
+        normal: rec.normal,
+        position: rec.p,
+        depth: rec.t * r.direction().length(),
+        object_id: rec.object_id,
+        material_id: scene.material_id(rec.mat.as_ref().unwrap()),
+        hit: true,
+    };
+
+    // Through mirrors and glass, tinted by them
+    let mut throughput = Color::new(1.0, 1.0, 1.0);
+    let mut r = Ray::new(r.origin(), r.direction());
+    for _ in 0..MAX_SPECULAR_BOUNCES {
+        let mat = rec.mat.clone().unwrap();
+        let mut attenuation = Color::default();
+        let mut scattered = Ray::default();
+        sampler.start(Dimension::Bsdf);
+        if !mat.scatter(&r, &rec, &mut attenuation, &mut scattered, sampler) {
+            // Lights are white
+            aov.albedo = throughput;
+            break;
+        }
+        if mat.scattering_pdf(&r, &rec, &scattered) > 0.0 {
+            aov.albedo = throughput * attenuation;
+            break;
+        }
+        throughput = throughput * attenuation;
+        sampler.next_bounce();
+        sampler.start(Dimension::Medium);
+        rec = HitRecord::new();
+        if !scene.world.hit(&scattered, 0.001, INFINITY, &mut rec, sampler) {
+            break;
+        }
+        r = scattered;
+    }
+    aov
+}
+
+// Next-event estimation: a shadow ray towards one of the emissive objects or lights
+fn sample_lights(r: &Ray, rec: &HitRecord, mat: &dyn Material, attenuation: Color, scene: &Scene, sampler: &mut dyn Sampler, path: &mut PathState) -> Color {
+    let n = scene.light_count();
+    // The light reaches the path at the next vertex
+    let vertex = path.vertex + 1;
+    let index = sampler.get_index(n);
+
+    let direction = if index < scene.emitters.len() {
+        scene.emitters.random(rec.p, sampler)
+    } else {
+        let light_index = index - scene.emitters.len();
+        let light = &scene.lights[light_index];
+        let Some(sample) = light.sample_li(rec.p, sampler) else {
+            return Color::new(0.0, 0.0, 0.0);
+        };
+
+        if light.is_delta() {
+            // No other way to reach the light: no MIS, only the choice of the light is weighted
+            let light_ray = Ray::new(rec.p, sample.wi);
+            let bsdf_pdf = mat.scattering_pdf(r, rec, &light_ray);
+            if bsdf_pdf <= 0.0 {
+                return Color::new(0.0, 0.0, 0.0);
+            }
+            let li = to_spectral(sample.li, r.wavelength());
+            path.stats.shadow_rays += 1;
+            let direct = n as f64 * scene.transmittance(&light_ray, sample.dist, sampler) * bsdf_pdf * attenuation * li;
+            path.record(scene.light_group(light_index), vertex, direct);
+            return direct;
+        }
+        sample.wi
+    };
+
+    let mut light_ray = Ray::new(rec.p, direction);
+    light_ray.set_wavelength(r.wavelength());
+    let light_pdf = scene.light_pdf(rec.p, direction, sampler);
+    let bsdf_pdf = mat.scattering_pdf(r, rec, &light_ray);
+    if light_pdf <= 0.0 || bsdf_pdf <= 0.0 {
+        return Color::new(0.0, 0.0, 0.0);
+    }
+
+    // attenuation * bsdf_pdf is the BSDF times the cosine term
+    path.stats.shadow_rays += 1;
+    let weight = power_heuristic(light_pdf, bsdf_pdf) * bsdf_pdf / light_pdf;
+    weight * attenuation * scene.emission_along(&light_ray, sampler, &mut |group, radiance| path.record(group, vertex, weight * attenuation * radiance))
+}
@@ -0,0 +1,25 @@
+pub mod basics;
+pub mod traits;
+pub mod volumes;
+pub mod materials;
+pub mod textures;
+pub mod densities;
+pub mod lights;
+pub mod samplers;
+pub mod filters;
+pub mod utils;
+pub mod camera;
+pub mod animation;
+pub mod film;
+pub mod denoiser;
+pub mod scene;
+pub mod integrator;
+pub mod renderer;
+pub mod description;
+pub mod server;
+#[cfg(feature = "viewer")]
+pub mod viewer;
+pub mod tiles;
+pub mod checkpoint;
+pub mod progress;
+pub mod cli;
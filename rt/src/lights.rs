@@ -0,0 +1,9 @@
+mod point;
+mod spot;
+mod directional;
+mod area;
+
+pub use point::*;
+pub use spot::*;
+pub use directional::*;
+pub use area::*;
@@ -0,0 +1,165 @@
+use crate::basics::{Color, Point3, Ray, Vec3, cross, dot, sample_concentric_disk, unit_vec};
+use crate::traits::{Light, LightSample, Sampler};
+use crate::utils::{INFINITY, PI, near_zero};
+
+// Rectangular and disk lights emit on the front side only, and do not block rays
+
+// Parallelogram light: emits on the side of cross(edge_u, edge_v)
+#[derive(Debug)]
+pub struct RectLight {
+    corner: Point3,
+    edge_u: Vec3,
+    edge_v: Vec3,
+    norm: Vec3,
+    area: f64,
+    radiance: Color,
+}
+
+impl RectLight {
+    pub fn new(corner: Point3, edge_u: Vec3, edge_v: Vec3, radiance: Color) -> RectLight {
+        let n = cross(edge_u, edge_v);
+        RectLight {
+            corner,
+            edge_u,
+            edge_v,
+            norm: unit_vec(n),
+            area: n.length(),
+            radiance,
+        }
+    }
+
+    // Distance along the ray to the front side of the light
+    fn intersect(&self, r: &Ray, t_max: f64) -> Option<f64> {
+        let t = front_plane_hit(r, self.corner, self.norm, t_max)?;
+        let rel = r.at(t) - self.corner;
+
+        // Coordinates of the hit point in the (edge_u, edge_v) base
+        let n = cross(self.edge_u, self.edge_v);
+        let w = n / dot(n, n);
+        let a = dot(w, cross(rel, self.edge_v));
+        let b = dot(w, cross(self.edge_u, rel));
+        if !(0.0..=1.0).contains(&a) || !(0.0..=1.0).contains(&b) {
+            return None;
+        }
+        Some(t)
+    }
+}
+
+impl Light for RectLight {
+    fn sample_li(&self, p: Point3, sampler: &mut dyn Sampler) -> Option<LightSample> {
+        let q = self.corner + sampler.get_1d() * self.edge_u + sampler.get_1d() * self.edge_v;
+        area_sample(p, q, self.norm, self.radiance)
+    }
+
+    fn emitted_along(&self, r: &Ray, t_max: f64) -> Option<(Color, f64)> {
+        self.intersect(r, t_max).map(|t| (self.radiance, t))
+    }
+
+    fn pdf_li(&self, origin: Point3, direction: Vec3) -> f64 {
+        match self.intersect(&Ray::new(origin, direction), INFINITY) {
+            Some(t) => area_pdf(t, direction, self.norm, self.area),
+            None => 0.0,
+        }
+    }
+}
+
+// Disk light: emits on the side of its normal
+#[derive(Debug)]
+pub struct DiskLight {
+    center: Point3,
+    norm: Vec3,
+    radius: f64,
+    base_u: Vec3,
+    base_v: Vec3,
+    radiance: Color,
+}
+
+impl DiskLight {
+    pub fn new(center: Point3, norm: Vec3, radius: f64, radiance: Color) -> DiskLight {
+        let unit_norm = unit_vec(norm);
+        let temp = if unit_norm.x().abs() > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let base_u = unit_vec(cross(unit_norm, temp));
+        let base_v = cross(unit_norm, base_u);
+
+        DiskLight {
+            center,
+            norm: unit_norm,
+            radius,
+            base_u,
+            base_v,
+            radiance,
+        }
+    }
+
+    fn intersect(&self, r: &Ray, t_max: f64) -> Option<f64> {
+        let t = front_plane_hit(r, self.center, self.norm, t_max)?;
+        if (r.at(t) - self.center).length_squared() > self.radius * self.radius {
+            return None;
+        }
+        Some(t)
+    }
+
+    fn area(&self) -> f64 {
+        PI * self.radius * self.radius
+    }
+}
+
+impl Light for DiskLight {
+    fn sample_li(&self, p: Point3, sampler: &mut dyn Sampler) -> Option<LightSample> {
+        let ((x, y), _) = sample_concentric_disk(sampler.get_2d());
+        let q = self.center + self.radius * (x * self.base_u + y * self.base_v);
+        area_sample(p, q, self.norm, self.radiance)
+    }
+
+    fn emitted_along(&self, r: &Ray, t_max: f64) -> Option<(Color, f64)> {
+        self.intersect(r, t_max).map(|t| (self.radiance, t))
+    }
+
+    fn pdf_li(&self, origin: Point3, direction: Vec3) -> f64 {
+        match self.intersect(&Ray::new(origin, direction), INFINITY) {
+            Some(t) => area_pdf(t, direction, self.norm, self.area()),
+            None => 0.0,
+        }
+    }
+}
+
+// Hit of the front side of the plane through `point`, in ]0.001, t_max[
+fn front_plane_hit(r: &Ray, point: Point3, norm: Vec3, t_max: f64) -> Option<f64> {
+    let denom = dot(norm, r.direction());
+    if near_zero(denom) || denom > 0.0 {
+        return None;
+    }
+    let t = dot(norm, point - r.origin()) / denom;
+    if t <= 0.001 || t >= t_max {
+        return None;
+    }
+    Some(t)
+}
+
+// Area pdf converted to solid angle
+fn area_pdf(t: f64, direction: Vec3, norm: Vec3, area: f64) -> f64 {
+    let dist_squared = t * t * direction.length_squared();
+    let cosine = f64::abs(dot(direction, norm) / direction.length());
+    dist_squared / (cosine * area)
+}
+
+// Light sample towards the point `q` of the light
+fn area_sample(p: Point3, q: Point3, norm: Vec3, radiance: Color) -> Option<LightSample> {
+    let to_light = q - p;
+    let dist = to_light.length();
+    let wi = to_light / dist;
+    let cosine = dot(-wi, norm);
+    if cosine <= 0.0 {
+        return None;
+    }
+
+    Some(LightSample {
+        wi,
+        li: radiance,
+        dist,
+    })
+}
@@ -0,0 +1,67 @@
+use crate::basics::{Color, Point3, Ray, Vec3, dot, sample_uniform_cone, unit_vec};
+use crate::traits::{Light, LightSample, Sampler};
+use crate::utils::{INFINITY, PI, degrees_to_radians};
+
+// Light from infinitely far away, like the sun
+// With an angular diameter, the light comes from a small disk of the sky and gives soft shadows
+#[derive(Debug)]
+pub struct DirectionalLight {
+    to_light: Vec3,
+    irradiance: Color, // Received by a surface facing the light
+    cos_theta_max: f64,
+}
+
+impl DirectionalLight {
+    // `direction` is where the light travels to; angular diameter in degrees, 0.0 for sharp shadows
+    pub fn new(direction: Vec3, irradiance: Color, angular_diameter: f64) -> DirectionalLight {
+        DirectionalLight {
+            to_light: -unit_vec(direction),
+            irradiance,
+            cos_theta_max: degrees_to_radians(angular_diameter / 2.0).cos(),
+        }
+    }
+
+    fn solid_angle(&self) -> f64 {
+        2.0 * PI * (1.0 - self.cos_theta_max)
+    }
+}
+
+impl Light for DirectionalLight {
+    fn sample_li(&self, _p: Point3, sampler: &mut dyn Sampler) -> Option<LightSample> {
+        if self.is_delta() {
+            return Some(LightSample {
+                wi: self.to_light,
+                li: self.irradiance,
+                dist: INFINITY,
+            });
+        }
+
+        // Uniform direction in the cone of the sun's disk
+        let (wi, _) = sample_uniform_cone(self.to_light, self.cos_theta_max, sampler.get_2d());
+
+        Some(LightSample {
+            wi,
+            li: self.irradiance / self.solid_angle(),
+            dist: INFINITY,
+        })
+    }
+
+    fn is_delta(&self) -> bool {
+        self.cos_theta_max >= 1.0
+    }
+
+    // Only rays escaping the world see the sky
+    fn emitted_along(&self, r: &Ray, t_max: f64) -> Option<(Color, f64)> {
+        if self.is_delta() || t_max < INFINITY || dot(unit_vec(r.direction()), self.to_light) < self.cos_theta_max {
+            return None;
+        }
+        Some((self.irradiance / self.solid_angle(), INFINITY))
+    }
+
+    fn pdf_li(&self, _origin: Point3, direction: Vec3) -> f64 {
+        if self.is_delta() || dot(unit_vec(direction), self.to_light) < self.cos_theta_max {
+            return 0.0;
+        }
+        1.0 / self.solid_angle()
+    }
+}
@@ -0,0 +1,35 @@
+use crate::basics::{Color, Point3, unit_vec};
+use crate::traits::{Light, LightSample, Sampler};
+
+// Isotropic point light, with inverse-square falloff
+#[derive(Debug)]
+pub struct PointLight {
+    position: Point3,
+    intensity: Color,
+}
+
+impl PointLight {
+    pub fn new(position: Point3, intensity: Color) -> PointLight {
+        PointLight { position, intensity }
+    }
+}
+
+impl Light for PointLight {
+    fn sample_li(&self, p: Point3, _sampler: &mut dyn Sampler) -> Option<LightSample> {
+        let to_light = self.position - p;
+        let dist_squared = to_light.length_squared();
+        if dist_squared == 0.0 {
+            return None;
+        }
+
+        Some(LightSample {
+            wi: unit_vec(to_light),
+            li: self.intensity / dist_squared,
+            dist: dist_squared.sqrt(),
+        })
+    }
+
+    fn is_delta(&self) -> bool {
+        true
+    }
+}
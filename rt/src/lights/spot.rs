@@ -0,0 +1,65 @@
+use crate::basics::{Color, Point3, Vec3, dot, unit_vec};
+use crate::traits::{Light, LightSample, Sampler};
+use crate::utils::{clamp, degrees_to_radians};
+
+// Point light restricted to a cone, fully lit inside the inner angle and fading to the outer one
+#[derive(Debug)]
+pub struct SpotLight {
+    position: Point3,
+    direction: Vec3,
+    intensity: Color,
+    cos_inner: f64,
+    cos_outer: f64,
+}
+
+impl SpotLight {
+    // Angles in degrees, from the axis of the cone
+    pub fn new(position: Point3, direction: Vec3, intensity: Color, inner_angle: f64, outer_angle: f64) -> SpotLight {
+        let outer = f64::max(inner_angle, outer_angle);
+        SpotLight {
+            position,
+            direction: unit_vec(direction),
+            intensity,
+            cos_inner: degrees_to_radians(inner_angle).cos(),
+            cos_outer: degrees_to_radians(outer).cos(),
+        }
+    }
+
+    fn falloff(&self, cos_theta: f64) -> f64 {
+        if cos_theta >= self.cos_inner {
+            return 1.0;
+        }
+        if self.cos_inner <= self.cos_outer {
+            return 0.0;
+        }
+        // Smoothstep between the two cones
+        let t = clamp((cos_theta - self.cos_outer) / (self.cos_inner - self.cos_outer), 0.0, 1.0);
+        t * t * (3.0 - 2.0 * t)
+    }
+}
+
+impl Light for SpotLight {
+    fn sample_li(&self, p: Point3, _sampler: &mut dyn Sampler) -> Option<LightSample> {
+        let to_light = self.position - p;
+        let dist_squared = to_light.length_squared();
+        if dist_squared == 0.0 {
+            return None;
+        }
+
+        let wi = unit_vec(to_light);
+        let falloff = self.falloff(dot(-wi, self.direction));
+        if falloff <= 0.0 {
+            return None;
+        }
+
+        Some(LightSample {
+            wi,
+            li: falloff * self.intensity / dist_squared,
+            dist: dist_squared.sqrt(),
+        })
+    }
+
+    fn is_delta(&self) -> bool {
+        true
+    }
+}
@@ -1,109 +1,352 @@
-mod basics;
-mod traits;
-mod volumes;
-mod materials;
-mod utils;
-mod camera;
-
-use rayon::prelude::*;
+use std::fs::File;
 use std::io;
-use std::sync::Arc;
-
-use crate::basics::*;
-use crate::traits::*;
-use crate::volumes::*;
-use crate::materials::*;
-use crate::utils::*;
-use camera::*;
-
-// Verify each hit for the trajectory of the ray
-fn ray_color(r: &Ray, world: &dyn Hittable, depth: i32) -> Color {
-    if depth <= 0 {
-        return Color::new(0.0, 0.0, 0.0);
-    }
-
-    let mut rec = HitRecord::new();
-    if world.hit(r, 0.001, INFINITY, &mut rec) {
-        let mut attenuation = Color::default();
-        let mut scattered = Ray::default();
-        let emitted = rec.mat.as_ref().unwrap().emitted();
-        if rec
-            .mat
-            .as_ref()
-            .unwrap()
-            .scatter(r, &rec, &mut attenuation, &mut scattered)
-        {
-            return emitted + attenuation * ray_color(&scattered, world, depth + 1);
-        }
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-        return emitted;
+use rt::basics::*;
+use rt::traits::*;
+use rt::volumes::*;
+use rt::materials::*;
+use rt::samplers::*;
+use rt::scene::*;
+use rt::renderer::*;
+use rt::integrator::PATH_KINDS;
+use rt::camera::*;
+use rt::animation::*;
+use rt::film::*;
+use rt::denoiser::*;
+use rt::filters::*;
+use rt::tiles::*;
+use rt::checkpoint::*;
+use rt::progress::*;
+use rt::server::*;
+use rt::cli::*;
+
+// Each layer of the film as <prefix>.<layer>.pfm
+fn write_layers<'a>(film: &Film, prefix: &str, layers: impl IntoIterator<Item = &'a str>, color_filter: Color) -> io::Result<()> {
+    for layer in layers {
+        let mut file = io::BufWriter::new(File::create(format!("{}.{}.pfm", prefix, layer))?);
+        film.write_pfm(&mut file, layer, color_filter)?;
     }
-    // Background color can be inputed here (currently sky-like or black):
+    Ok(())
+}
 
-    // Sky
-    /* let unit_direction = unit_vec(r.direction());
-    let t = 0.5 * (unit_direction.y() + 1.0);
-    (1.0 - t) * Color::new(1.0, 1.0, 1.0) + t * Color::new(0.5, 0.7, 1.0) */
+// Path with a frame number before its extension: frame.ppm gives frame_0007.ppm
+fn numbered(path: &str, frame: u32) -> String {
+    match Path::new(path).extension() {
+        Some(extension) => format!("{}_{:04}.{}", &path[..path.len() - extension.len() - 1], frame, extension.to_string_lossy()),
+        None => format!("{}_{:04}", path, frame),
+    }
+}
 
-    // Black
-    Color::new(0.0, 0.0, 0.0)
+// Written next to the target, then renamed: a viewer never sees a partial image
+fn write_snapshot(film: &Film, path: &str, color_filter: Color, gamma: f64) -> io::Result<()> {
+    let tmp = Path::new(path).with_extension("ppm.tmp");
+    let mut file = io::BufWriter::new(File::create(&tmp)?);
+    film.write_ppm(&mut file, color_filter, gamma)?;
+    drop(file);
+    std::fs::rename(tmp, path)
 }
 
 fn main() {
+    let options = Options::parse(std::env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("{}\n{}", e, USAGE);
+        std::process::exit(2);
+    });
+    if let Some(address) = &options.serve {
+        if let Err(e) = serve(address) {
+            eprintln!("Cannot serve on {}: {}", address, e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     // Image
     const ASPECT_RATIO: f64 = 16.0 / 9.0; // Image format
     const IMAGE_WIDTH: i32 = 1000; // Horizontal Size
     const IMAGE_HEIGHT: i32 = (IMAGE_WIDTH as f64 / ASPECT_RATIO) as i32;
-    const SAMPLES_PER_PIXEL: i32 = 1500; // Anti-aliasing sharpness; Base Samples : 100
+    const SAMPLES_PER_PIXEL: i32 = 1500; // Anti-aliasing sharpness, maximum with adaptive sampling; Base Samples : 100
+    const SAMPLES_PER_PASS: i32 = 4; // Progressive rendering: samples added to each pixel by a pass; Base Samples Per Pass : 4
+    const SNAPSHOT_PASSES: i32 = 8; // With --snapshot, the image is written every SNAPSHOT_PASSES passes; Base Snapshot Passes : 8
+    const SNAPSHOT_SECONDS: f64 = 10.0; // or after SNAPSHOT_SECONDS since the last one; Base Snapshot Seconds : 10.0
+    const TILE_SIZE: usize = 32; // Side of the tiles rendered by the workers, in pixels; Base Tile Size : 32
+    const TILE_ORDER: TileOrder = TileOrder::Spiral; // Scanline, Spiral or Hilbert; Base Tile Order : TileOrder::Spiral
+    const CHECKPOINT_SECONDS: f64 = 60.0; // With --checkpoint, the render is saved every CHECKPOINT_SECONDS; Base Checkpoint Seconds : 60.0
+    const MIN_SAMPLES_PER_PIXEL: i32 = 16; // Adaptive sampling: samples before the error is estimated; Base Min Samples : 16
+    const NOISE_THRESHOLD: f64 = 0.0; // Adaptive sampling: relative error at which a pixel stops, 0.0 to disable; Base Threshold : 0.0
+    const DENOISE_STRENGTH: f64 = 1.0; // With --denoise, unless --denoise-strength: lower keeps more detail, higher smooths more; Base Denoise Strength : 1.0
     const MAX_DEPTH: i32 = 50; // Number of bounces of a ray; Base Max_Depth : 50
     const GAMMA: f64 = 2.0; // Base Gamma : 2.0
+    const SEED: u64 = 0; // The same seed gives the same image; Base Seed : 0
+    const SAMPLER: SamplerKind = SamplerKind::Sobol; // Sample values: Independent, Stratified, Halton or Sobol; Base Sampler : SamplerKind::Sobol
+    const SPECTRAL: bool = false; // One wavelength per path, accumulated as XYZ; Base Spectral : false
+    const FRAME_FILE: &str = "frame.ppm"; // With --frames, frame n is written to frame_<n>.ppm; Base Frame File : "frame.ppm"
+    // Reconstruction: BoxFilter::new(0.5) (average of the pixel's samples), TentFilter::new(1.0), GaussianFilter::new(1.5, 0.5),
+    // MitchellFilter::new(2.0, 1.0 / 3.0, 1.0 / 3.0), LanczosFilter::new(3.0)
+    let filter: Arc<dyn Filter> = Arc::new(BoxFilter::new(0.5));
     let color_filter: Color = Color::new(1.0, 1.0, 1.0); // Base Filter : Color::new(1.0, 1.0, 1.0)
 
     // World
+    let build_start = Instant::now();
     let mut world = HittableList::new();
 
     let mat_diffus1 = Arc::new(Lambertian::new(Color::new(0.8, 0.8, 0.2)));
     let mat_diffus2 = Arc::new(Lambertian::new(Color::new(0.7, 0.3, 0.3)));
-    let mat_metal1 = Arc::new(Metal::new(Color::new(0.8, 0.8, 0.8), 0.0));
-    let mat_metal2 = Arc::new(Metal::new(Color::new(0.8, 0.8, 0.8), 0.2));
+    let _mat_metal1 = Arc::new(Metal::new(Color::new(0.8, 0.8, 0.8), 0.0));
+    let _mat_metal2 = Arc::new(Metal::new(Color::new(0.8, 0.8, 0.8), 0.2));
     let mat_glass = Arc::new(Dielectric::new(1.5, 0.05));
+    // Colored flint glass: Dielectric::new(1.62, 0.0).with_absorption(Color::new(0.3, 0.8, 0.5), 1.0).with_abbe(36.0)
     let mat_light = Arc::new(DiffuseLight::new(Color::new(10.0, 10.0, 10.0)));
 
     world.add(Box::new(Plane::new(Vec3::new(0.0, 1.0, 0.0), 1.0, mat_diffus1)));
     world.add(Box::new(Sphere::new(Point3::new(0.0, 3.2, 3.0), 1.5, mat_light)));
     world.add(Box::new(Cube::new(Point3::new(4.0, 0.0, 3.0), 1.0, Vec3::new(0.0, 45.0, 0.0), mat_diffus2)));
     world.add(Box::new(Cylinder::new(Point3::new(-4.0, -1.0, 3.0), 2.0, 1.0, Vec3::new(0.0, 1.0, 0.0), mat_glass)));
+    // Smoke in a sphere (ConstantMedium) and fog on the whole scene (scene.atmosphere) are set up in scenes/media.json
+
+    // Lights without geometry; emissive objects of the world are lights as well
+    // Point, spot, directional, rect and disk lights (rt::lights) are set up in scenes/lights.json
+    let lights: Vec<Box<dyn Light>> = Vec::new();
+
+    // Emissive objects and lights are sampled directly at each diffuse bounce
+    let scene = Scene::new(world, lights);
+    let scene_build = build_start.elapsed();
 
     // Camera
-    let cam = Camera::new(
-        ASPECT_RATIO,
-        90.0,
-        Point3::new(0.0, 0.0, -1.0),
-        Point3::new(0.0, 0.0, 1.0),
-        Vec3::new(0.0, 1.0, 0.0),
+    let camera = CameraParameters::new(
+        Point3::new(0.0, 0.0, -1.0), // From
+        Point3::new(0.0, 0.0, 1.0),  // At
+        Vec3::new(0.0, 1.0, 0.0),    // Vup
+        90.0,                        // Vertical field of view
     );
+    // Depth of field: camera.with_lens(aperture, focus distance)
+    // Other projections: camera.with_projection(Projection::Orthographic { height }), Fisheye { fov }, Equirectangular, Cylindrical { hfov }
+    // Stereo, both eyes in the image: camera.with_stereo(Stereo::new(interocular, convergence)), off-axis and side by side
+    let cam = camera.camera(ASPECT_RATIO);
+
+    // Animation, rendered with --frames: keys of the camera and of the objects (by id: place in the world's list, from 1)
+    // Key::new(frame, value, Interpolation::Linear, CatmullRom or Bezier), the interpolation going to the next key; e.g. a turntable:
+    // animation.objects.push(ObjectAnimation { rotation: Some(Track::new(vec![Key::new(0.0, Vec3::new(0.0, 0.0, 0.0), Interpolation::Linear),
+    //     Key::new(48.0, Vec3::new(0.0, 360.0, 0.0), Interpolation::Linear)])), ..ObjectAnimation::new(3, Point3::new(4.0, 0.0, 3.0)) });
+    // animation.camera.from = Some(Track::new(vec![...])), and camera.at, camera.vfov
+    let animation = Animation::default();
+
+    // Render, in passes adding SAMPLES_PER_PASS samples to each pixel, tile by tile
+    if let Some(threads) = options.threads {
+        rayon::ThreadPoolBuilder::new().num_threads(threads).build_global().expect("creating the workers");
+    }
+    let tile_size = options.tile_size.unwrap_or(TILE_SIZE);
+    let tile_order = options.tile_order.unwrap_or(TILE_ORDER);
+    let settings = RenderSettings {
+        width: IMAGE_WIDTH as usize,
+        height: IMAGE_HEIGHT as usize,
+        samples_per_pixel: SAMPLES_PER_PIXEL,
+        samples_per_pass: SAMPLES_PER_PASS,
+        min_samples_per_pixel: MIN_SAMPLES_PER_PIXEL,
+        noise_threshold: NOISE_THRESHOLD,
+        max_depth: MAX_DEPTH,
+        spectral: SPECTRAL,
+        sampler: SAMPLER,
+        seed: SEED,
+    };
+    let fail = |message: String| -> ! {
+        eprintln!("{}", message);
+        std::process::exit(1);
+    };
+
+    // Preview: one sample per pass, for a quick first image after each camera move
+    if options.view {
+        #[cfg(feature = "viewer")]
+        {
+            let settings = RenderSettings { samples_per_pass: 1, ..settings };
+            rt::viewer::view(settings, Arc::new(scene), camera, filter, color_filter, GAMMA).unwrap_or_else(|e| fail(e));
+            return;
+        }
+        #[cfg(not(feature = "viewer"))]
+        fail("The viewer is not built: cargo run --release --features viewer -- --view".to_string());
+    }
+    let passes = settings.passes();
+    // Checkpoints: of this scene and these parameters only
+    let scene_hash = scene_hash(&scene, cam.as_ref(), filter.as_ref(), &settings, tile_size, tile_order);
+    let mut film = Film::new(IMAGE_WIDTH as usize, IMAGE_HEIGHT as usize, filter);
+    // The denoiser is guided by the albedo and the normals
+    if options.aovs.is_some() || options.denoise.is_some() {
+        film = film.with_aovs();
+    }
+    if options.light_paths.is_some() {
+        film = film.with_light_paths(PATH_KINDS.iter().map(|kind| kind.to_string()).chain(scene.light_groups()).collect());
+    }
 
-    // Render
-    print!("P3\n{} {}\n255\n", IMAGE_WIDTH, IMAGE_HEIGHT); // .ppm header
-
-    for j in (0..IMAGE_HEIGHT).rev() {
-        eprint!("\rScanlines remaining: {} ", j);
-        let pixel_colors: Vec<_> = (0..IMAGE_WIDTH)
-            .into_par_iter()
-            .map(|i| {
-                let mut pixel_color = Color::new(0.0, 0.0, 0.0);
-                for _ in 0..SAMPLES_PER_PIXEL {
-                    let u = ((i as f64) + rand_01()) / (IMAGE_WIDTH - 1) as f64;
-                    let v = ((j as f64) + rand_01()) / (IMAGE_HEIGHT - 1) as f64;
-                    let r = cam.get_ray(u, v);
-                    pixel_color += ray_color(&r, &world, MAX_DEPTH);
-                }
-                pixel_color
-            })
-            .collect();
-        for pixel_color in pixel_colors {
-            write_color(&mut io::stdout(), pixel_color * color_filter, SAMPLES_PER_PIXEL, GAMMA);
+    // Renders split across processes are added up
+    if !options.merge.is_empty() {
+        let checkpoints = merge_checkpoints(&options.merge, scene_hash, &mut film).unwrap_or_else(|e| fail(format!("Cannot merge: {}", e)));
+        for (path, checkpoint) in options.merge.iter().zip(&checkpoints) {
+            if !checkpoint.is_complete() {
+                eprintln!("{} stopped after {} of the passes {}..{}", path, checkpoint.next_pass, checkpoint.first_pass, checkpoint.last_pass);
+            }
+        }
+        let mut out = io::BufWriter::new(io::stdout());
+        film.write_ppm(&mut out, color_filter, GAMMA).expect("writing the image");
+        if let Some(prefix) = &options.aovs {
+            write_layers(&film, prefix, LAYERS, color_filter).expect("writing the AOVs");
+        }
+        if let Some(prefix) = &options.light_paths {
+            write_layers(&film, prefix, film.light_paths().iter().map(String::as_str), color_filter).expect("writing the light paths");
+        }
+        return;
+    }
+
+    // Files asked for besides the image, numbered for the frames of an animation
+    let write_extras = |film: &Film, frame: Option<u32>| {
+        let name = |path: &str| frame.map_or(path.to_string(), |frame| numbered(path, frame));
+        if let Some(prefix) = &options.aovs {
+            write_layers(film, &name(prefix), LAYERS, color_filter).expect("writing the AOVs");
+        }
+        if let Some(prefix) = &options.light_paths {
+            write_layers(film, &name(prefix), film.light_paths().iter().map(String::as_str), color_filter).expect("writing the light paths");
         }
+        if let Some(path) = &options.denoise {
+            let denoised = Denoiser::new(options.denoise_strength.unwrap_or(DENOISE_STRENGTH)).denoise(film);
+            let mut file = io::BufWriter::new(File::create(name(path)).expect("creating the denoised image"));
+            denoised.write_ppm(&mut file, color_filter, GAMMA).expect("writing the denoised image");
+        }
+        if let Some(path) = &options.heatmap {
+            let mut file = io::BufWriter::new(File::create(name(path)).expect("creating the heatmap"));
+            film.write_heatmap(&mut file, SAMPLES_PER_PIXEL as u32).expect("writing the heatmap");
+        }
+    };
+
+    // Animation: each frame rendered in full; the scene is only updated for the objects that moved
+    if let Some((first_frame, last_frame)) = options.frames {
+        let mut scene = Arc::new(scene);
+        let mut animator = Animator::new(animation, &scene).unwrap_or_else(|e| fail(format!("Cannot animate: {}", e)));
+        let tiles = tiles(IMAGE_WIDTH as usize, IMAGE_HEIGHT as usize, tile_size, tile_order);
+        let format = options.progress.unwrap_or(ProgressFormat::Human);
+        for frame in first_frame..last_frame {
+            let update_start = Instant::now();
+            // The renderer of the previous frame is gone with its share of the scene
+            let changed = animator
+                .set_frame(Arc::get_mut(&mut scene).expect("the scene is still shared"), frame as f64)
+                .unwrap_or_else(|e| fail(format!("Cannot animate: {}", e)));
+            let scene_update = update_start.elapsed() + if frame == first_frame { scene_build } else { Duration::ZERO };
+            let frame_camera = animator.camera(camera, frame as f64).camera(ASPECT_RATIO);
+            let renderer = Renderer::new(settings.clone(), scene.clone(), frame_camera);
+            let progress = Progress::new(format, 0, 0, passes);
+            progress.message(&format!("Frame {}{}", frame, if changed || frame == first_frame { "" } else { ", scene unchanged" }));
+            let merger = Mutex::new(TileMerger::new(film.clone()));
+            renderer.render_passes(&merger, &tiles, 0..passes, |pass, _| {
+                progress.pass_done(pass, &renderer.stats());
+                true
+            });
+            progress.summary(passes, &renderer.stats(), scene_update);
+            let film = merger.into_inner().unwrap().film;
+            let mut file = io::BufWriter::new(File::create(numbered(FRAME_FILE, frame)).expect("creating the frame"));
+            film.write_ppm(&mut file, color_filter, GAMMA).expect("writing the frame");
+            write_extras(&film, Some(frame));
+        }
+        return;
+    }
+
+    // Partial render, to be merged: a region of the image (given from its top left corner) and a range of passes
+    let region = match options.region {
+        Some([x0, y0, x1, y1]) => TileRect {
+            x0,
+            y0: (IMAGE_HEIGHT as usize).saturating_sub(y1),
+            x1: usize::min(x1, IMAGE_WIDTH as usize),
+            y1: (IMAGE_HEIGHT as usize).saturating_sub(y0),
+        },
+        None => TileRect { x0: 0, y0: 0, x1: IMAGE_WIDTH as usize, y1: IMAGE_HEIGHT as usize },
+    };
+    if region.x0 >= region.x1 || region.y0 >= region.y1 {
+        fail(format!("The region is outside the {}x{} image", IMAGE_WIDTH, IMAGE_HEIGHT));
+    }
+    let (first_pass, last_pass) = match options.passes {
+        Some((first, last)) => (first as i32, i32::min(last as i32, passes)),
+        None => (0, passes),
+    };
+    if first_pass >= last_pass {
+        fail(format!("The render has {} passes", passes));
+    }
+    // The error of a pixel is estimated from its first samples
+    if NOISE_THRESHOLD > 0.0 && first_pass > 0 {
+        fail("Adaptive sampling needs all the passes of a pixel: split the render by region".to_string());
+    }
+    let renderer = Renderer::new(settings, Arc::new(scene), cam);
+    let tiles: Vec<TileRect> = tiles(IMAGE_WIDTH as usize, IMAGE_HEIGHT as usize, tile_size, tile_order)
+        .iter()
+        .filter_map(|tile| tile.intersect(&region))
+        .collect();
+
+    let mut resumed_pass = first_pass;
+    if options.resume {
+        let path = options.checkpoint.as_deref().unwrap();
+        let checkpoint = Checkpoint::resume(path, scene_hash, &mut film).unwrap_or_else(|e| fail(format!("Cannot resume from {}: {}", path, e)));
+        if (checkpoint.region, checkpoint.first_pass, checkpoint.last_pass) != (region, first_pass as u32, last_pass as u32) {
+            fail(format!("Cannot resume from {}: it was saved for another region or other passes", path));
+        }
+        resumed_pass = checkpoint.next_pass as i32;
+    }
+    let progress = Progress::new(options.progress.unwrap_or(ProgressFormat::Human), first_pass, resumed_pass, last_pass);
+    if options.resume {
+        progress.message(&format!("Resuming after {} passes", resumed_pass - first_pass));
+    }
+    let merger = Mutex::new(TileMerger::new(film));
+    let save_checkpoint = |path: &str, film: &Film, next_pass: i32| {
+        let checkpoint = Checkpoint {
+            scene_hash,
+            region,
+            first_pass: first_pass as u32,
+            last_pass: last_pass as u32,
+            next_pass: next_pass as u32,
+        };
+        checkpoint.save(path, film).expect("writing the checkpoint");
+    };
+
+    let start = Instant::now();
+    let mut last_snapshot = start;
+    let mut last_checkpoint = start;
+    let next_pass = renderer.render_passes(&merger, &tiles, resumed_pass..last_pass, |pass, film| {
+        let next_pass = pass + 1;
+        progress.pass_done(pass, &renderer.stats());
+
+        // Snapshot of the image so far
+        if let Some(path) = &options.snapshot
+            && (next_pass % SNAPSHOT_PASSES == 0 || last_snapshot.elapsed().as_secs_f64() >= SNAPSHOT_SECONDS)
+        {
+            write_snapshot(film, path, color_filter, GAMMA).expect("writing the snapshot");
+            last_snapshot = Instant::now();
+        }
+        if let Some(path) = &options.checkpoint
+            && last_checkpoint.elapsed().as_secs_f64() >= CHECKPOINT_SECONDS
+        {
+            save_checkpoint(path, film, next_pass);
+            last_checkpoint = Instant::now();
+        }
+
+        // Time budget: only full passes, so the samples stay evenly spread over the image
+        let elapsed = start.elapsed();
+        if let Some(budget) = options.time
+            && next_pass < last_pass
+            && elapsed + elapsed / (next_pass - resumed_pass) as u32 > budget
+        {
+            progress.message(&format!("Time budget reached after {} passes", next_pass));
+            return false;
+        }
+        true
+    });
+
+    progress.summary(next_pass, &renderer.stats(), scene_build);
+
+    let film = merger.into_inner().unwrap().film;
+    // Also after a time budget, for a later run to go on with more time
+    if let Some(path) = &options.checkpoint {
+        save_checkpoint(path, &film, next_pass);
+    }
+    let mut out = io::BufWriter::new(io::stdout());
+    film.write_ppm(&mut out, color_filter, GAMMA).expect("writing the image");
+    if let Some(path) = &options.snapshot {
+        write_snapshot(&film, path, color_filter, GAMMA).expect("writing the snapshot");
     }
-    eprint!("\nDone.\n");
+    write_extras(&film, None);
 }
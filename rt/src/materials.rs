@@ -2,8 +2,12 @@ mod lambertian;
 mod metal;
 mod dielectric;
 mod light;
+mod isotropic;
+mod henyey_greenstein;
 
 pub use lambertian::*;
 pub use metal::*;
 pub use dielectric::*;
-pub use light::*;
\ No newline at end of file
+pub use light::*;
+pub use isotropic::*;
+pub use henyey_greenstein::*;
\ No newline at end of file
@@ -1,22 +1,57 @@
-use crate::{basics::{Color, Ray, dot, rand_in_unit_sphere, reflect, refract, unit_vec}, traits::{HitRecord, Material}, utils::rand_01};
+use crate::{basics::{Color, Ray, dot, rand_in_unit_sphere, reflect, refract, unit_vec, wavelength_rgb_weight, LAMBDA_MAX, LAMBDA_MIN}, traits::{HitRecord, Material, Sampler}};
+
+// Fraunhofer lines used to define the Abbe number (nanometers)
+const LAMBDA_F: f64 = 486.1;
+const LAMBDA_D: f64 = 587.6;
+const LAMBDA_C: f64 = 656.3;
 
 // Glass-like
+#[derive(Debug)]
 pub struct Dielectric {
     ir: f64, // Index of refraction
 	fuzz: f64,
+	absorption: Color,                // Coefficient inside, per unit of distance
+	cauchy: Option<(f64, f64)>,       // Dispersion: ir(lambda) = a + b / lambda^2, lambda in micrometers
 }
 
 impl Dielectric {
     pub fn new(ir: f64, f: f64) -> Dielectric {
         Dielectric {
-            ir: ir,
+            ir,
 			fuzz: if f < 1.0 {
 				f
 			} else {
 				1.0
-			}
+			},
+			absorption: Color::new(0.0, 0.0, 0.0),
+			cauchy: None,
         }
     }
+
+    // Colored glass: the light keeps `transmittance` of each component after `distance` inside (Beer-Lambert)
+    pub fn with_absorption(mut self, transmittance: Color, distance: f64) -> Dielectric {
+        self.absorption = Color::new(
+            -transmittance.x().ln() / distance,
+            -transmittance.y().ln() / distance,
+            -transmittance.z().ln() / distance,
+        );
+        self
+    }
+
+    // Dispersion from the Abbe number (lower is stronger: ~64 crown glass, ~36 flint, ~55 diamond)
+    // The index of refraction given to new() is the one at the d line (587.6 nm)
+    pub fn with_abbe(self, abbe: f64) -> Dielectric {
+        let (lf, ld, lc) = (LAMBDA_F / 1000.0, LAMBDA_D / 1000.0, LAMBDA_C / 1000.0);
+        let b = (self.ir - 1.0) / (abbe * (1.0 / (lf * lf) - 1.0 / (lc * lc)));
+        let a = self.ir - b / (ld * ld);
+        self.with_cauchy(a, b)
+    }
+
+    // Dispersion from Cauchy's coefficients, b in square micrometers (BK7: 1.5046, 0.00420)
+    pub fn with_cauchy(mut self, a: f64, b: f64) -> Dielectric {
+        self.cauchy = Some((a, b));
+        self
+    }
 }
 
 impl Material for Dielectric {
@@ -26,11 +61,33 @@ impl Material for Dielectric {
         rec: &HitRecord,
         attenuation: &mut Color,
         scattered: &mut Ray,
+        sampler: &mut dyn Sampler,
     ) -> bool {
+        *attenuation = Color::new(1.0, 1.0, 1.0);
+
+        // A dispersive glass restricts the path to one wavelength, if not done already
+        let mut wavelength = r_in.wavelength();
+        let ir = match self.cauchy {
+            Some((a, b)) => {
+                let lambda = match wavelength {
+                    Some(lambda) => lambda,
+                    None => {
+                        let lambda = sampler.get_range(LAMBDA_MIN, LAMBDA_MAX);
+                        *attenuation = wavelength_rgb_weight(lambda);
+                        lambda
+                    }
+                };
+                wavelength = Some(lambda);
+                let l = lambda / 1000.0;
+                a + b / (l * l)
+            }
+            None => self.ir,
+        };
+
         let refraction_ratio = if rec.front {
-            1.0 / self.ir
+            1.0 / ir
         } else {
-            self.ir
+            ir
         };
 
         let unit_direction = unit_vec(r_in.direction());
@@ -39,14 +96,18 @@ impl Material for Dielectric {
         let sin_theta = f64::sqrt(1.0 - cos_theta * cos_theta);
 
         let cannot_refract = refraction_ratio * sin_theta > 1.0;
-        let direction = if cannot_refract || reflectance(cos_theta, self.ir) > rand_01() {
+        let direction = if cannot_refract || reflectance(cos_theta, ir) > sampler.get_1d() {
             reflect(unit_direction, rec.normal)
         } else {
             refract(unit_direction, rec.normal, refraction_ratio)
         };
 
-        *attenuation = Color::new(1.0, 1.0, 1.0);
-        *scattered = Ray::new(rec.p, direction + self.fuzz * rand_in_unit_sphere());
+        *scattered = Ray::new(rec.p, direction + self.fuzz * rand_in_unit_sphere(sampler));
+        scattered.set_wavelength(wavelength);
+        // The light is absorbed along the segments inside, not counting glass nested in glass
+        let outward = if rec.front { rec.normal } else { -rec.normal };
+        let inside = dot(scattered.direction(), outward) < 0.0;
+        scattered.set_absorption(Some(if inside { self.absorption } else { Color::new(0.0, 0.0, 0.0) }));
         true
     }
 }
@@ -56,4 +117,4 @@ fn reflectance(cos_theta: f64, ref_idx: f64) -> f64 {
 	let mut r0 = (1.0 - ref_idx) / (1.0 + ref_idx);
 	r0 *= r0;
 	r0 + (1.0 - r0) * f64::powf(1.0 - cos_theta, 5.0)
-}
\ No newline at end of file
+}
@@ -0,0 +1,76 @@
+use std::sync::Arc;
+use crate::basics::{Color, Onb, Ray, Vec3, dot, unit_vec};
+use crate::traits::{HitRecord, Material, Texture, Sampler};
+use crate::utils::PI;
+
+// Anisotropic phase function: g > 0 scatters forward (clouds), g < 0 backward, g = 0 is isotropic
+// An emission texture, evaluated at the hit point, makes the medium glow (fire)
+#[derive(Debug)]
+pub struct HenyeyGreenstein {
+    albedo: Color,
+    g: f64,
+    emit: Option<Arc<dyn Texture>>,
+}
+
+impl HenyeyGreenstein {
+    pub fn new(albedo: Color, g: f64) -> HenyeyGreenstein {
+        HenyeyGreenstein {
+            albedo,
+            g: g.clamp(-0.99, 0.99),
+            emit: None,
+        }
+    }
+
+    pub fn with_emission(mut self, emit: Arc<dyn Texture>) -> HenyeyGreenstein {
+        self.emit = Some(emit);
+        self
+    }
+
+    // Pdf of the angle between the incoming and the scattered directions
+    fn phase(&self, cos_theta: f64) -> f64 {
+        let denom = 1.0 + self.g * self.g - 2.0 * self.g * cos_theta;
+        (1.0 - self.g * self.g) / (4.0 * PI * denom * f64::sqrt(denom))
+    }
+}
+
+impl Material for HenyeyGreenstein {
+    fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        attenuation: &mut Color,
+        scattered: &mut Ray,
+        sampler: &mut dyn Sampler,
+    ) -> bool {
+        // Inversion of the cumulative distribution of cos_theta
+        let (u, u_phi) = sampler.get_2d();
+        let cos_theta = if self.g.abs() < 1.0e-3 {
+            1.0 - 2.0 * u
+        } else {
+            let sq = (1.0 - self.g * self.g) / (1.0 - self.g + 2.0 * self.g * u);
+            (1.0 + self.g * self.g - sq * sq) / (2.0 * self.g)
+        };
+        let sin_theta = f64::sqrt(f64::max(0.0, 1.0 - cos_theta * cos_theta));
+        let phi = 2.0 * PI * u_phi;
+
+        // Around the incoming direction
+        let local = Vec3::new(sin_theta * f64::cos(phi), sin_theta * f64::sin(phi), cos_theta);
+
+        *attenuation = self.albedo;
+        *scattered = Ray::new(rec.p, Onb::new(r_in.direction()).local(local));
+        true
+    }
+
+    fn scattering_pdf(&self, r_in: &Ray, _rec: &HitRecord, scattered: &Ray) -> f64 {
+        let cos_theta = dot(unit_vec(r_in.direction()), unit_vec(scattered.direction()));
+        self.phase(cos_theta)
+    }
+
+    // Emission of the absorbed fraction of the collisions
+    fn emitted(&self, _r_in: &Ray, rec: &HitRecord) -> Color {
+        match &self.emit {
+            Some(emit) => (Color::new(1.0, 1.0, 1.0) - self.albedo) * emit.value(rec.u, rec.v, rec.p),
+            None => Color::new(0.0, 0.0, 0.0),
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use crate::basics::{Color, Ray, sample_uniform_sphere, uniform_sphere_pdf};
+use crate::traits::{HitRecord, Material, Sampler};
+
+// Phase function of fog and smoke: scatters in every direction with the same probability
+#[derive(Debug)]
+pub struct Isotropic {
+    albedo: Color,
+}
+
+impl Isotropic {
+    pub fn new(a: Color) -> Isotropic {
+        Isotropic { albedo: a }
+    }
+}
+
+impl Material for Isotropic {
+    fn scatter(
+        &self,
+        _r_in: &Ray,
+        rec: &HitRecord,
+        attenuation: &mut Color,
+        scattered: &mut Ray,
+        sampler: &mut dyn Sampler,
+    ) -> bool {
+        *attenuation = self.albedo;
+        *scattered = Ray::new(rec.p, sample_uniform_sphere(sampler.get_2d()).0);
+        true
+    }
+
+    fn scattering_pdf(&self, _r_in: &Ray, _rec: &HitRecord, _scattered: &Ray) -> f64 {
+        uniform_sphere_pdf()
+    }
+}
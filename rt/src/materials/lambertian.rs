@@ -1,7 +1,8 @@
-use crate::basics::{Color, Ray, rand_unit_vec};
-use crate::traits::{HitRecord, Material};
+use crate::basics::{Color, Ray, cosine_hemisphere_pdf, dot, sample_cosine_hemisphere, unit_vec};
+use crate::traits::{HitRecord, Material, Sampler};
 
 // Any mat with diffuse reflection
+#[derive(Debug)]
 pub struct Lambertian {
     albedo: Color,
 }
@@ -19,16 +20,17 @@ impl Material for Lambertian {
         rec: &HitRecord,
         attenuation: &mut Color,
         scattered: &mut Ray,
+        sampler: &mut dyn Sampler,
     ) -> bool {
-		// Diffuse Reflection (Tangential sphere)
-        let mut scatter_direction = rec.normal + rand_unit_vec();
+		// Diffuse Reflection (cosine-weighted)
+        let (scatter_direction, _) = sample_cosine_hemisphere(rec.normal, sampler.get_2d());
 
-		// Exclude Vec almost null case
-		if scatter_direction.near_zero() {
-			scatter_direction = rec.normal;
-		}
         *attenuation = self.albedo;
         *scattered = Ray::new(rec.p, scatter_direction);
         true
     }
+
+    fn scattering_pdf(&self, _r_in: &Ray, rec: &HitRecord, scattered: &Ray) -> f64 {
+        cosine_hemisphere_pdf(dot(rec.normal, unit_vec(scattered.direction())))
+    }
 }
\ No newline at end of file
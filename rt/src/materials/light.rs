@@ -1,14 +1,61 @@
-use crate::basics::{Color, Ray};
-use crate::traits::{HitRecord, Material};
+use std::sync::Arc;
+use crate::basics::{Color, Ray, blackbody_normalized, blackbody_rgb, dot, rgb_to_spectrum, unit_vec};
+use crate::traits::{HitRecord, Material, Texture, Sampler};
+use crate::textures::SolidColor;
 
 // Light
+#[derive(Debug)]
 pub struct DiffuseLight {
-    emit: Color,
+    emit: Arc<dyn Texture>,
+    two_sided: bool,
+    profile: Option<AngularProfile>,
+    blackbody: Option<(f64, f64)>, // Kelvin and luminance, for the exact spectrum
 }
 
 impl DiffuseLight {
     pub fn new(c: Color) -> DiffuseLight {
-        DiffuseLight { emit: c }
+        DiffuseLight::textured(Arc::new(SolidColor::new(c)))
+    }
+
+    // Light panels, screens: the emission follows the (u, v) coordinates of the surface
+    pub fn textured(tex: Arc<dyn Texture>) -> DiffuseLight {
+        DiffuseLight {
+            emit: tex,
+            two_sided: true,
+            profile: None,
+            blackbody: None,
+        }
+    }
+
+    // Color of a black body at a temperature in kelvin (1900 candle, 6500 daylight), with the given luminance
+    pub fn blackbody(kelvin: f64, luminance: f64) -> DiffuseLight {
+        let mut light = DiffuseLight::new(luminance * blackbody_rgb(kelvin));
+        light.blackbody = Some((kelvin, luminance));
+        light
+    }
+
+    // Emit from the front face only (outside of closed shapes, normal side of planes)
+    pub fn one_sided(mut self) -> DiffuseLight {
+        self.two_sided = false;
+        self
+    }
+
+    pub fn with_profile(mut self, profile: AngularProfile) -> DiffuseLight {
+        self.profile = Some(profile);
+        self
+    }
+}
+
+impl DiffuseLight {
+    // Dependence of the emission on the side and the direction
+    fn factor(&self, r_in: &Ray, rec: &HitRecord) -> f64 {
+        if !self.two_sided && !rec.front {
+            return 0.0;
+        }
+        match &self.profile {
+            Some(profile) => profile.value(dot(-unit_vec(r_in.direction()), rec.normal)),
+            None => 1.0,
+        }
     }
 }
 
@@ -19,11 +66,72 @@ impl Material for DiffuseLight {
         _rec: &HitRecord,
         _attenuation: &mut Color,
         _scattered: &mut Ray,
+        _sampler: &mut dyn Sampler,
     ) -> bool {
         false
     }
 
-    fn emitted(&self) -> Color {
-        self.emit
+    fn emitted(&self, r_in: &Ray, rec: &HitRecord) -> Color {
+        self.factor(r_in, rec) * self.emit.value(rec.u, rec.v, rec.p)
+    }
+
+    fn emitted_spectral(&self, r_in: &Ray, rec: &HitRecord, lambda: f64) -> f64 {
+        match self.blackbody {
+            Some((kelvin, luminance)) => self.factor(r_in, rec) * luminance * blackbody_normalized(lambda, kelvin),
+            None => rgb_to_spectrum(self.emitted(r_in, rec), lambda),
+        }
+    }
+
+    fn is_emissive(&self) -> bool {
+        true
+    }
+}
+
+// IES-like intensity factor, depending on the angle between the emitted direction and the normal
+#[derive(Debug)]
+pub struct AngularProfile {
+    table: Vec<(f64, f64)>, // (angle in degrees, factor), sorted by angle
+}
+
+impl AngularProfile {
+    // Linear interpolation between the given angles; beyond the last one its factor is kept
+    pub fn new(mut table: Vec<(f64, f64)>) -> AngularProfile {
+        table.sort_by(|a, b| a.0.total_cmp(&b.0));
+        AngularProfile { table }
+    }
+
+    // Full emission up to `inner` degrees, fading to nothing at `outer` degrees
+    pub fn spot(inner: f64, outer: f64) -> AngularProfile {
+        let outer = f64::max(inner, outer);
+        let mut table = vec![(0.0, 1.0), (inner, 1.0)];
+        const STEPS: usize = 16;
+        for i in 1..=STEPS {
+            let t = i as f64 / STEPS as f64;
+            let smooth = 1.0 - t * t * (3.0 - 2.0 * t);
+            table.push((inner + t * (outer - inner), smooth));
+        }
+        AngularProfile::new(table)
+    }
+
+    pub fn value(&self, cos_theta: f64) -> f64 {
+        let angle = f64::acos(cos_theta.clamp(-1.0, 1.0)).to_degrees();
+        let Some(&(first_angle, first_value)) = self.table.first() else {
+            return 1.0;
+        };
+        if angle <= first_angle {
+            return first_value;
+        }
+
+        for pair in self.table.windows(2) {
+            let (a0, v0) = pair[0];
+            let (a1, v1) = pair[1];
+            if angle <= a1 {
+                if a1 <= a0 {
+                    return v1;
+                }
+                return v0 + (angle - a0) / (a1 - a0) * (v1 - v0);
+            }
+        }
+        self.table[self.table.len() - 1].1
     }
 }
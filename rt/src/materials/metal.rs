@@ -1,7 +1,8 @@
 use crate::basics::{Color, Ray, dot, rand_in_unit_sphere, reflect, unit_vec};
-use crate::traits::{HitRecord, Material};
+use crate::traits::{HitRecord, Material, Sampler};
 
 // Metallic/Mirror
+#[derive(Debug)]
 pub struct Metal {
     albedo: Color,
 	fuzz: f64,
@@ -27,11 +28,12 @@ impl Material for Metal {
         rec: &HitRecord,
         attenuation: &mut Color,
         scattered: &mut Ray,
+        sampler: &mut dyn Sampler,
     ) -> bool {
         let reflected = reflect(unit_vec(r_in.direction()), rec.normal);
 
         *attenuation = self.albedo;
-		*scattered = Ray::new(rec.p, reflected + self.fuzz * rand_in_unit_sphere());
+		*scattered = Ray::new(rec.p, reflected + self.fuzz * rand_in_unit_sphere(sampler));
 
         dot(scattered.direction(), rec.normal) > 0.0
     }
@@ -0,0 +1,158 @@
+use serde_json::json;
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::integrator::PathStats;
+
+// How the progress and the statistics are written, to stderr by default
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ProgressFormat {
+    Human, // A line updated in place, then a table
+    Json,  // One object per line, for dashboards
+}
+
+impl ProgressFormat {
+    pub fn parse(name: &str) -> Option<ProgressFormat> {
+        match name {
+            "human" => Some(ProgressFormat::Human),
+            "json" => Some(ProgressFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+// Reports the passes [first_pass, last_pass[ of a render, this run starting at resumed_pass
+pub struct Progress {
+    format: ProgressFormat,
+    start: Instant,
+    first_pass: i32,
+    resumed_pass: i32,
+    last_pass: i32,
+    line: AtomicBool, // Human: a progress line is waiting for its end
+    output: Mutex<Box<dyn Write + Send>>,
+}
+
+impl Progress {
+    pub fn new(format: ProgressFormat, first_pass: i32, resumed_pass: i32, last_pass: i32) -> Progress {
+        Progress {
+            format,
+            start: Instant::now(),
+            first_pass,
+            resumed_pass,
+            last_pass,
+            line: AtomicBool::new(false),
+            output: Mutex::new(Box::new(io::stderr())),
+        }
+    }
+
+    pub fn with_output(mut self, output: impl Write + Send + 'static) -> Progress {
+        self.output = Mutex::new(Box::new(output));
+        self
+    }
+
+    // Progress is not worth failing the render for
+    fn print(&self, text: fmt::Arguments) {
+        let _ = self.output.lock().unwrap().write_fmt(text);
+    }
+
+    pub fn message(&self, text: &str) {
+        match self.format {
+            ProgressFormat::Human => {
+                if self.line.swap(false, Ordering::Relaxed) {
+                    self.print(format_args!("\n"));
+                }
+                self.print(format_args!("{}\n", text));
+            }
+            ProgressFormat::Json => self.print(format_args!("{}\n", json!({ "event": "message", "message": text }))),
+        }
+    }
+
+    // `stats` are those of the passes rendered by this run
+    pub fn pass_done(&self, pass: i32, stats: &PathStats) {
+        let elapsed = self.start.elapsed().as_secs_f64();
+        let done = pass + 1;
+        let percent = 100.0 * (done - self.first_pass) as f64 / (self.last_pass - self.first_pass) as f64;
+        // From the average duration of the passes so far
+        let eta = elapsed / (done - self.resumed_pass) as f64 * (self.last_pass - done) as f64;
+        let rays_per_second = (stats.rays + stats.shadow_rays) as f64 / elapsed;
+        let samples_per_second = stats.samples as f64 / elapsed;
+        match self.format {
+            ProgressFormat::Human => {
+                self.line.store(true, Ordering::Relaxed);
+                self.print(format_args!(
+                    "\rPass {}/{} {:5.1}% | {} elapsed, ETA {} | {:.2} Mrays/s, {:.2} Msamples/s ",
+                    done,
+                    self.last_pass,
+                    percent,
+                    format_duration(elapsed),
+                    format_duration(eta),
+                    rays_per_second / 1e6,
+                    samples_per_second / 1e6
+                ));
+            }
+            ProgressFormat::Json => self.print(format_args!(
+                "{}\n",
+                json!({
+                    "event": "progress",
+                    "pass": done,
+                    "passes": self.last_pass,
+                    "percent": percent,
+                    "elapsed_seconds": elapsed,
+                    "eta_seconds": eta,
+                    "rays_per_second": rays_per_second,
+                    "samples_per_second": samples_per_second,
+                })
+            )),
+        }
+    }
+
+    // At the end of the render; there is no BVH: the objects are tested in a list, and the build time is that of the scene
+    pub fn summary(&self, passes_done: i32, stats: &PathStats, scene_build: Duration) {
+        let render = self.start.elapsed().as_secs_f64();
+        let primary = stats.samples;
+        let secondary = stats.rays - stats.samples;
+        let rays_per_second = (stats.rays + stats.shadow_rays) as f64 / render;
+        let cut = 100.0 * stats.max_depth_cut as f64 / u64::max(stats.samples, 1) as f64;
+        match self.format {
+            ProgressFormat::Human => {
+                if self.line.swap(false, Ordering::Relaxed) {
+                    self.print(format_args!("\n"));
+                }
+                self.print(format_args!("Passes                  {}\n", passes_done - self.resumed_pass));
+                self.print(format_args!("Primary rays            {}\n", primary));
+                self.print(format_args!("Secondary rays          {}\n", secondary));
+                self.print(format_args!("Shadow rays             {}\n", stats.shadow_rays));
+                self.print(format_args!("Average path depth      {:.3}\n", stats.average_depth()));
+                self.print(format_args!("Paths cut by max depth  {} ({:.3}%)\n", stats.max_depth_cut, cut));
+                self.print(format_args!("Scene build (no BVH)    {:.3} s\n", scene_build.as_secs_f64()));
+                self.print(format_args!("Rendering               {:.3} s\n", render));
+                self.print(format_args!("Rays per second         {:.0}\n", rays_per_second));
+            }
+            ProgressFormat::Json => self.print(format_args!(
+                "{}\n",
+                json!({
+                    "event": "summary",
+                    "passes": passes_done - self.resumed_pass,
+                    "primary_rays": primary,
+                    "secondary_rays": secondary,
+                    "shadow_rays": stats.shadow_rays,
+                    "average_path_depth": stats.average_depth(),
+                    "max_depth_cut": stats.max_depth_cut,
+                    "scene_build_seconds": scene_build.as_secs_f64(),
+                    "bvh": false,
+                    "render_seconds": render,
+                    "rays_per_second": rays_per_second,
+                })
+            )),
+        }
+    }
+}
+
+// h:mm:ss
+fn format_duration(seconds: f64) -> String {
+    let seconds = seconds.round() as u64;
+    format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
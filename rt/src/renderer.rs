@@ -0,0 +1,227 @@
+use std::ops::Range;
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::thread;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use crate::basics::{Color, LAMBDA_MAX, LAMBDA_MIN, spectral_sample_xyz, xyz_to_film_rgb};
+use crate::film::{Aov, Film, FilmTile, SampleStats, TileMerger, TileRect};
+use crate::integrator::{PathState, PathStats, first_hit, ray_color};
+use crate::samplers::SamplerKind;
+use crate::scene::Scene;
+use crate::traits::{Camera, Dimension, Sampler};
+
+// Luminance under which the adaptive sampling error is absolute
+const MIN_LUMINANCE: f64 = 1.0e-3;
+
+// Parameters of a render, besides the scene, the camera and the film
+#[derive(Clone, Debug)]
+pub struct RenderSettings {
+    pub width: usize,
+    pub height: usize,
+    pub samples_per_pixel: i32, // Maximum with adaptive sampling
+    pub samples_per_pass: i32,
+    pub min_samples_per_pixel: i32, // Adaptive sampling: samples before the error is estimated
+    pub noise_threshold: f64,       // Adaptive sampling: relative error at which a pixel stops, 0.0 to disable
+    pub max_depth: i32,
+    pub spectral: bool,
+    pub sampler: SamplerKind,
+    pub seed: u64,
+}
+
+impl RenderSettings {
+    pub fn passes(&self) -> i32 {
+        (self.samples_per_pixel + self.samples_per_pass - 1) / self.samples_per_pass
+    }
+
+    // Adaptive sampling: a pixel stops once it is known precisely enough, relative to its luminance
+    // Below MIN_LUMINANCE the error is absolute: a black pixel without variance stops at the minimum samples
+    // Checked at powers of two only, which keeps the low discrepancy sets whole
+    pub fn converged(&self, stats: SampleStats) -> bool {
+        let n = stats.count();
+        self.noise_threshold > 0.0
+            && n >= self.min_samples_per_pixel as u32
+            && n.is_power_of_two()
+            && stats.error() < self.noise_threshold * f64::max(stats.mean(), MIN_LUMINANCE)
+    }
+}
+
+// A worker that panics lets the others go: they could be waiting for its tile
+struct Release<'a>(&'a Mutex<TileMerger>, &'a Condvar);
+
+impl Drop for Release<'_> {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            self.0.lock().unwrap_or_else(PoisonError::into_inner).stop();
+            self.1.notify_all();
+        }
+    }
+}
+
+// Renders a scene pass by pass, tile by tile, on the rayon workers
+pub struct Renderer {
+    pub settings: RenderSettings,
+    pub scene: Arc<Scene>, // Shared by the renders of several cameras (viewer)
+    pub camera: Box<dyn Camera>,
+    cancelled: AtomicBool,
+    stats: Mutex<PathStats>, // Of all the passes rendered
+}
+
+impl Renderer {
+    pub fn new(settings: RenderSettings, scene: Arc<Scene>, camera: Box<dyn Camera>) -> Renderer {
+        Renderer {
+            settings,
+            scene,
+            camera,
+            cancelled: AtomicBool::new(false),
+            stats: Mutex::new(PathStats::default()),
+        }
+    }
+
+    pub fn stats(&self) -> PathStats {
+        *self.stats.lock().unwrap()
+    }
+
+    // The render stops at the next tile
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    // Adds the passes of the tiles to the film of the merger, and calls `pass_done` with the film after each of them
+    // until it returns false; returns the pass after the last one merged
+    // Workers take the tiles in order, pass after pass, without waiting for the others at the end of a pass:
+    // a tile only waits for the same tile of the previous pass, for its pixels' statistics (adaptive sampling)
+    // The merger puts the tiles in the film in that order, and drops those of the passes after a stop
+    pub fn render_passes<F>(&self, merger: &Mutex<TileMerger>, tiles: &[TileRect], passes: Range<i32>, pass_done: F) -> i32
+    where
+        F: FnMut(i32, &Film) -> bool + Send,
+    {
+        let views = self.views();
+        let count = tiles.len();
+        let total = count * passes.len();
+        let next = AtomicUsize::new(0);
+        let merged = Condvar::new();
+        let pass_done = Mutex::new(pass_done);
+        merger.lock().unwrap().restart();
+        rayon::broadcast(|_| {
+            let _release = Release(merger, &merged);
+            loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                if index >= total || self.is_cancelled() {
+                    break;
+                }
+                let (pass, rect) = (passes.start + (index / count) as i32, tiles[index % count]);
+                let m = merged.wait_while(merger.lock().unwrap(), |m| m.merged() + count <= index && index < m.end()).unwrap();
+                if index >= m.end() {
+                    break;
+                }
+                let mut tile = m.film.tile(rect).with_views(&views);
+                drop(m);
+
+                self.render_tile(&mut tile, pass);
+                let mut m = merger.lock().unwrap();
+                m.submit(index, tile);
+                // Up to the end of each pass completed, by this tile or by those that waited for it
+                while m.merge_next() {
+                    let done = passes.start + (m.merged() / count) as i32;
+                    if m.merged().is_multiple_of(count) && !(pass_done.lock().unwrap())(done - 1, &m.film) {
+                        m.stop();
+                    }
+                }
+                merged.notify_all();
+            }
+            // The others may wait for a tile that will not come
+            if self.is_cancelled() {
+                merger.lock().unwrap().stop();
+                merged.notify_all();
+            }
+        });
+        passes.start + (merger.lock().unwrap().merged() / count.max(1)) as i32
+    }
+
+    // Pixels of the views of the camera
+    fn views(&self) -> Vec<TileRect> {
+        let (width, height) = (self.settings.width as f64, self.settings.height as f64);
+        let views = self.camera.views().into_iter().map(|[u0, v0, u1, v1]| TileRect {
+            x0: (u0 * width).round() as usize,
+            y0: (v0 * height).round() as usize,
+            x1: (u1 * width).round() as usize,
+            y1: (v1 * height).round() as usize,
+        });
+        views.collect()
+    }
+
+    fn render_tile(&self, tile: &mut FilmTile, pass: i32) {
+        let settings = &self.settings;
+        let first_sample = pass * settings.samples_per_pass;
+        let last_sample = i32::min(first_sample + settings.samples_per_pass, settings.samples_per_pixel);
+        let mut sampler = settings.sampler.create(settings.samples_per_pixel as u32, settings.seed);
+        let mut stats = PathStats::default();
+        let mut light_paths = vec![Color::default(); tile.light_path_count()];
+        let rect = tile.rect;
+        for j in rect.y0..rect.y1 {
+            for i in rect.x0..rect.x1 {
+                if settings.converged(tile.stats(i, j)) {
+                    continue;
+                }
+                for s in first_sample..last_sample {
+                    sampler.start_pixel_sample(i as u32, j as u32, s as u32);
+                    sampler.start(Dimension::Pixel);
+                    let (du, dv) = sampler.get_2d();
+                    let x = i as f64 + du;
+                    let y = j as f64 + dv;
+                    light_paths.fill(Color::default());
+                    let layers = (!light_paths.is_empty()).then_some(light_paths.as_mut_slice());
+                    let color = self.sample(x, y, sampler.as_mut(), &mut PathState::new(&mut stats, layers));
+                    tile.add_sample(x, y, color, &light_paths);
+                    // The same camera ray, again
+                    if tile.has_aovs() {
+                        sampler.start_pixel_sample(i as u32, j as u32, s as u32);
+                        sampler.start(Dimension::Pixel);
+                        sampler.get_2d();
+                        let aov = self.aov(x, y, sampler.as_mut());
+                        tile.add_aov(i, j, s as u32, &aov);
+                    }
+                    if settings.converged(tile.stats(i, j)) {
+                        break;
+                    }
+                }
+            }
+        }
+        self.stats.lock().unwrap().merge(&stats);
+    }
+
+    // AOVs of the camera ray through the film position (x, y)
+    fn aov(&self, x: f64, y: f64, sampler: &mut dyn Sampler) -> Aov {
+        let settings = &self.settings;
+        match self.camera.generate_ray(x / settings.width as f64, y / settings.height as f64, sampler) {
+            Some(r) => first_hit(&r, &self.scene, sampler),
+            None => Aov::default(),
+        }
+    }
+
+    // Color seen through the film position (x, y)
+    fn sample(&self, x: f64, y: f64, sampler: &mut dyn Sampler, path: &mut PathState) -> Color {
+        let settings = &self.settings;
+        path.stats.samples += 1;
+        let Some(mut r) = self.camera.generate_ray(x / settings.width as f64, y / settings.height as f64, sampler) else {
+            return Color::new(0.0, 0.0, 0.0);
+        };
+        if settings.spectral {
+            sampler.start(Dimension::Wavelength);
+            let lambda = sampler.get_range(LAMBDA_MIN, LAMBDA_MAX);
+            r.set_wavelength(Some(lambda));
+            let radiance = ray_color(&r, &self.scene, settings.max_depth, None, sampler, path).x();
+            // The light path layers hold radiances at lambda too
+            for c in path.layers_mut() {
+                *c = xyz_to_film_rgb(spectral_sample_xyz(c.x(), lambda));
+            }
+            xyz_to_film_rgb(spectral_sample_xyz(radiance, lambda))
+        } else {
+            ray_color(&r, &self.scene, settings.max_depth, None, sampler, path)
+        }
+    }
+}
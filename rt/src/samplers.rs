@@ -0,0 +1,11 @@
+mod independent;
+mod stratified;
+mod halton;
+mod sobol;
+mod kind;
+
+pub use independent::*;
+pub use stratified::*;
+pub use halton::*;
+pub use sobol::*;
+pub use kind::*;
@@ -0,0 +1,79 @@
+use std::sync::OnceLock;
+
+use crate::traits::{Dimensions, Sampler};
+use crate::samplers::permutation_element;
+use crate::utils::{Rng, mix64, pixel_seed};
+
+const HALTON_DIMENSIONS: usize = 512;
+
+// Halton sequence of each pixel: dimension d is the radical inverse in the d-th prime base,
+// with the digits permuted at random for each pixel, which decorrelates the pixels and the high dimensions
+pub struct HaltonSampler {
+    seed: u64,
+    pixel: u64,
+    index: u32,
+    dims: Dimensions,
+}
+
+impl HaltonSampler {
+    pub fn new(seed: u64) -> HaltonSampler {
+        HaltonSampler {
+            seed,
+            pixel: 0,
+            index: 0,
+            dims: Dimensions::new(),
+        }
+    }
+}
+
+impl Sampler for HaltonSampler {
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.pixel = pixel_seed(self.seed, x, y);
+        self.index = index;
+        self.dims.reset(Rng::for_sample(self.seed, x, y, index));
+    }
+
+    fn sample(&mut self, dim: u32) -> f64 {
+        let primes = primes();
+        if dim as usize >= primes.len() {
+            return self.dims.rng.rand_01();
+        }
+        scrambled_radical_inverse(primes[dim as usize], self.index as u64, mix64(self.pixel.wrapping_add(dim as u64)))
+    }
+
+    fn dimensions(&mut self) -> &mut Dimensions {
+        &mut self.dims
+    }
+}
+
+// Digits of `index` in `base`, mirrored around the decimal point, each through a permutation drawn from `key`
+fn scrambled_radical_inverse(base: u64, mut index: u64, key: u64) -> f64 {
+    let inv_base = 1.0 / base as f64;
+    let mut inv_base_n = inv_base;
+    let mut result = 0.0;
+    let mut digit_key = key;
+    // Past the digits of the index, the permuted zeros still count, up to the precision of the result
+    while inv_base_n > 1.0e-10 {
+        digit_key = mix64(digit_key);
+        let digit = permutation_element((index % base) as u32, base as u32, digit_key as u32);
+        result += digit as f64 * inv_base_n;
+        index /= base;
+        inv_base_n *= inv_base;
+    }
+    f64::min(result, 1.0 - f64::EPSILON)
+}
+
+fn primes() -> &'static [u64] {
+    static PRIMES: OnceLock<Vec<u64>> = OnceLock::new();
+    PRIMES.get_or_init(|| {
+        let mut primes = Vec::with_capacity(HALTON_DIMENSIONS);
+        let mut n = 2;
+        while primes.len() < HALTON_DIMENSIONS {
+            if primes.iter().take_while(|&&p| p * p <= n).all(|&p| n % p != 0) {
+                primes.push(n);
+            }
+            n += 1;
+        }
+        primes
+    })
+}
@@ -0,0 +1,31 @@
+use crate::traits::{Dimensions, Sampler};
+use crate::utils::Rng;
+
+// Uniform random numbers for every draw
+pub struct IndependentSampler {
+    seed: u64,
+    dims: Dimensions,
+}
+
+impl IndependentSampler {
+    pub fn new(seed: u64) -> IndependentSampler {
+        IndependentSampler {
+            seed,
+            dims: Dimensions::new(),
+        }
+    }
+}
+
+impl Sampler for IndependentSampler {
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.dims.reset(Rng::for_sample(self.seed, x, y, index));
+    }
+
+    fn sample(&mut self, _dim: u32) -> f64 {
+        self.dims.rng.rand_01()
+    }
+
+    fn dimensions(&mut self) -> &mut Dimensions {
+        &mut self.dims
+    }
+}
@@ -0,0 +1,74 @@
+use crate::traits::{Dimensions, Sampler};
+use crate::utils::{Rng, mix64, pixel_seed};
+
+// Sobol sequence with Owen scrambling (Burley 2020): each pair of dimensions uses the first two Sobol
+// dimensions, with the sample order shuffled and the values scrambled by a different seed for each pair and pixel
+// Any power of two of samples is well stratified in every pair
+pub struct SobolSampler {
+    seed: u64,
+    pixel: u64,
+    index: u32,
+    dims: Dimensions,
+}
+
+impl SobolSampler {
+    pub fn new(seed: u64) -> SobolSampler {
+        SobolSampler {
+            seed,
+            pixel: 0,
+            index: 0,
+            dims: Dimensions::new(),
+        }
+    }
+}
+
+impl Sampler for SobolSampler {
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.pixel = pixel_seed(self.seed, x, y);
+        self.index = index;
+        self.dims.reset(Rng::for_sample(self.seed, x, y, index));
+    }
+
+    fn sample(&mut self, dim: u32) -> f64 {
+        let pair_seed = mix64(self.pixel.wrapping_add((dim / 2) as u64));
+        let index = nested_uniform_scramble(self.index, pair_seed as u32);
+        let (value, value_seed) = if dim.is_multiple_of(2) {
+            (index.reverse_bits(), (pair_seed >> 32) as u32)
+        } else {
+            (sobol_second_dimension(index), mix64(pair_seed) as u32)
+        };
+        nested_uniform_scramble(value, value_seed) as f64 / 4294967296.0
+    }
+
+    fn dimensions(&mut self) -> &mut Dimensions {
+        &mut self.dims
+    }
+}
+
+// Generator matrix of the second Sobol dimension (primitive polynomial x + 1)
+fn sobol_second_dimension(mut index: u32) -> u32 {
+    let mut v: u32 = 1 << 31;
+    let mut result = 0;
+    while index != 0 {
+        if index & 1 != 0 {
+            result ^= v;
+        }
+        index >>= 1;
+        v ^= v >> 1;
+    }
+    result
+}
+
+// Owen scrambling of the bits of x: each bit is flipped depending on the bits above it
+fn nested_uniform_scramble(x: u32, seed: u32) -> u32 {
+    laine_karras_permutation(x.reverse_bits(), seed).reverse_bits()
+}
+
+fn laine_karras_permutation(mut x: u32, seed: u32) -> u32 {
+    x = x.wrapping_add(seed);
+    x ^= x.wrapping_mul(0x6c50b47c);
+    x ^= x.wrapping_mul(0xb82f1e52);
+    x ^= x.wrapping_mul(0xc7afe638);
+    x ^= x.wrapping_mul(0x8d22f6e6);
+    x
+}
@@ -0,0 +1,94 @@
+use crate::traits::{Dimensions, Sampler};
+use crate::utils::{Rng, hash_01, mix64, pixel_seed};
+
+// Jittered strata: each pair of dimensions is a grid of k x k strata when there are k * k samples per pixel
+// (one stratum per sample, in a random order for each pair), otherwise each dimension has one stratum per sample
+pub struct StratifiedSampler {
+    samples_per_pixel: u32,
+    grid: Option<u32>, // k
+    seed: u64,
+    pixel: u64,
+    index: u32,
+    dims: Dimensions,
+}
+
+impl StratifiedSampler {
+    pub fn new(samples_per_pixel: u32, seed: u64) -> StratifiedSampler {
+        let samples_per_pixel = samples_per_pixel.max(1);
+        let k = f64::sqrt(samples_per_pixel as f64).round() as u32;
+        StratifiedSampler {
+            samples_per_pixel,
+            grid: if k * k == samples_per_pixel { Some(k) } else { None },
+            seed,
+            pixel: 0,
+            index: 0,
+            dims: Dimensions::new(),
+        }
+    }
+}
+
+impl Sampler for StratifiedSampler {
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.pixel = pixel_seed(self.seed, x, y);
+        self.index = index;
+        self.dims.reset(Rng::for_sample(self.seed, x, y, index));
+    }
+
+    fn sample(&mut self, dim: u32) -> f64 {
+        let n = self.samples_per_pixel;
+        // Past n samples, the strata are visited again in another order
+        let round = (self.index / n) as u64;
+        let jitter = hash_01(mix64(self.pixel ^ ((dim as u64) << 32) ^ self.index as u64));
+        match self.grid {
+            Some(k) => {
+                let pair = (dim / 2) as u64;
+                let order = mix64(self.pixel.wrapping_add(pair) ^ (round << 48)) as u32;
+                let stratum = permutation_element(self.index % n, n, order);
+                let cell = if dim.is_multiple_of(2) { stratum % k } else { stratum / k };
+                (cell as f64 + jitter) / k as f64
+            }
+            None => {
+                let order = mix64(self.pixel.wrapping_add(dim as u64) ^ (round << 48)) as u32;
+                (permutation_element(self.index % n, n, order) as f64 + jitter) / n as f64
+            }
+        }
+    }
+
+    fn dimensions(&mut self) -> &mut Dimensions {
+        &mut self.dims
+    }
+}
+
+// Element i of a random permutation of [0, l[ chosen by `p`, without storing it (Kensler)
+pub fn permutation_element(mut i: u32, l: u32, p: u32) -> u32 {
+    let mut w = l - 1;
+    w |= w >> 1;
+    w |= w >> 2;
+    w |= w >> 4;
+    w |= w >> 8;
+    w |= w >> 16;
+    loop {
+        i ^= p;
+        i = i.wrapping_mul(0xe170893d);
+        i ^= p >> 16;
+        i ^= (i & w) >> 4;
+        i ^= p >> 8;
+        i = i.wrapping_mul(0x0929eb3f);
+        i ^= p >> 23;
+        i ^= (i & w) >> 1;
+        i = i.wrapping_mul(1 | (p >> 27));
+        i = i.wrapping_mul(0x6935fa69);
+        i ^= (i & w) >> 11;
+        i = i.wrapping_mul(0x74dcb303);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0x9e501cc3);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0xc860a3df);
+        i &= w;
+        i ^= i >> 5;
+        if i < l {
+            break;
+        }
+    }
+    (i.wrapping_add(p)) % l
+}
@@ -0,0 +1,141 @@
+use std::sync::Arc;
+use crate::basics::{Color, Point3, Ray, Vec3, to_spectral};
+use crate::traits::{HitRecord, Hittable, Light, Material, Sampler};
+use crate::volumes::{Atmosphere, HittableList};
+use crate::utils::INFINITY;
+
+// Everything the integrator needs: the objects, and what lights them
+#[derive(Debug)]
+pub struct Scene {
+    pub world: HittableList,
+    pub emitters: HittableList, // Emissive objects of the world
+    pub lights: Vec<Box<dyn Light>>,
+    pub atmosphere: Option<Atmosphere>, // Fog everywhere, outside of the objects as well
+    materials: Vec<Arc<dyn Material>>, // Of the world, in order of first use: their ids
+    emitter_ids: Vec<u32>, // Object ids of the emitters
+}
+
+impl Scene {
+    pub fn new(world: HittableList, lights: Vec<Box<dyn Light>>) -> Scene {
+        let mut materials: Vec<Arc<dyn Material>> = Vec::new();
+        for material in world.materials() {
+            if !materials.iter().any(|m| Arc::ptr_eq(m, &material)) {
+                materials.push(material);
+            }
+        }
+        Scene {
+            emitters: world.lights(),
+            emitter_ids: world.emissive_ids(),
+            world,
+            lights,
+            atmosphere: None,
+            materials,
+        }
+    }
+
+    // Moves an object (animation): the list of emitters follows, the ids and materials stay
+    pub fn replace_object(&mut self, id: u32, object: Arc<dyn Hittable>) {
+        let emissive = object.is_emissive();
+        self.world.replace(id, object);
+        if emissive {
+            self.emitters = self.world.lights();
+        }
+    }
+
+    // Index + 1 of the material among those of the world, 0 for the others (the atmosphere)
+    pub fn material_id(&self, material: &Arc<dyn Material>) -> u32 {
+        self.materials.iter().position(|m| Arc::ptr_eq(m, material)).map_or(0, |index| index as u32 + 1)
+    }
+
+    // Light groups: each emissive object of the world, each light, then the emission of anything else (media)
+    pub fn light_groups(&self) -> Vec<String> {
+        let objects = self.emitter_ids.iter().map(|id| format!("object_{}", id));
+        let lights = (1..=self.lights.len()).map(|k| format!("light_{}", k));
+        objects.chain(lights).chain(["other".to_string()]).collect()
+    }
+
+    // Light group of the emission of an object
+    pub fn object_light_group(&self, object_id: u32) -> usize {
+        match self.emitter_ids.iter().position(|&id| id == object_id) {
+            Some(k) => k,
+            None => self.emitter_ids.len() + self.lights.len(),
+        }
+    }
+
+    // Light group of lights[light]
+    pub fn light_group(&self, light: usize) -> usize {
+        self.emitter_ids.len() + light
+    }
+
+    // Emissive objects and lights, each chosen with the same probability by light sampling
+    pub fn light_count(&self) -> usize {
+        self.emitters.len() + self.lights.len()
+    }
+
+    // Solid-angle pdf of light sampling giving `direction`, over all non-delta lights
+    pub fn light_pdf(&self, origin: Point3, direction: Vec3, sampler: &mut dyn Sampler) -> f64 {
+        let n = self.light_count();
+        if n == 0 {
+            return 0.0;
+        }
+        let emitters_pdf = self.emitters.len() as f64 * self.emitters.pdf_value(origin, direction, sampler);
+        let lights_pdf: f64 = self.lights.iter().map(|light| light.pdf_li(origin, direction)).sum();
+        (emitters_pdf + lights_pdf) / n as f64
+    }
+
+    // Emission of the lights crossed by the ray before t_max
+    // `each` gets the part of every light group, for the light path layers
+    pub fn lights_emitted(&self, r: &Ray, t_max: f64, each: &mut dyn FnMut(usize, Color)) -> Color {
+        let mut emitted = Color::new(0.0, 0.0, 0.0);
+        for (k, light) in self.lights.iter().enumerate() {
+            if let Some((radiance, _)) = light.emitted_along(r, t_max) {
+                let radiance = to_spectral(radiance, r.wavelength());
+                each(self.light_group(k), radiance);
+                emitted += radiance;
+            }
+        }
+        emitted
+    }
+
+    // Radiance arriving along the ray straight from an emitter or a light, through the fog
+    pub fn emission_along(&self, r: &Ray, sampler: &mut dyn Sampler, each: &mut dyn FnMut(usize, Color)) -> Color {
+        let mut rec = HitRecord::new();
+        let mut emitted = Color::new(0.0, 0.0, 0.0);
+        let mut t_max = INFINITY;
+        if self.world.shadow_hit(r, 0.001, INFINITY, &mut rec, sampler) {
+            t_max = rec.t;
+            emitted = self.medium_transmittance(r, rec.t, sampler) * rec.mat.as_ref().unwrap().emission(r, &rec);
+            each(self.object_light_group(rec.object_id), emitted);
+        }
+        for (k, light) in self.lights.iter().enumerate() {
+            if let Some((radiance, t)) = light.emitted_along(r, t_max) {
+                let radiance = self.medium_transmittance(r, t, sampler) * to_spectral(radiance, r.wavelength());
+                each(self.light_group(k), radiance);
+                emitted += radiance;
+            }
+        }
+        emitted
+    }
+
+    // Shadow ray towards a light at `dist` along a unit direction: fraction of its light arriving
+    pub fn transmittance(&self, r: &Ray, dist: f64, sampler: &mut dyn Sampler) -> f64 {
+        let mut rec = HitRecord::new();
+        let t_max = dist * (1.0 - 1.0e-6);
+        if self.world.shadow_hit(r, 0.001, t_max, &mut rec, sampler) {
+            return 0.0;
+        }
+        self.medium_transmittance(r, t_max, sampler)
+    }
+
+    // Fog and media crossed by the ray up to t_max
+    fn medium_transmittance(&self, r: &Ray, t_max: f64, sampler: &mut dyn Sampler) -> f64 {
+        let fog = match &self.atmosphere {
+            Some(atmosphere) => atmosphere.transmittance(t_max * r.direction().length()),
+            None => 1.0,
+        };
+        if fog <= 0.0 {
+            return 0.0;
+        }
+        fog * self.world.transmittance(r, 0.001, t_max, sampler)
+    }
+}
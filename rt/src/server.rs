@@ -0,0 +1,385 @@
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::io::{self, Cursor, Read};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use tiny_http::{Header, Method, Request, Response, ResponseBox, Server};
+
+use crate::basics::Color;
+use crate::description::{SceneDescription, SceneSetup};
+use crate::film::{Film, TileMerger};
+use crate::tiles::{TileOrder, tiles};
+
+// Largest scene description accepted
+const MAX_BODY: u64 = 1 << 20;
+// Snapshots are encoded after a pass, at most every SNAPSHOT_INTERVAL
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(1);
+// Finished jobs are kept for their image, at most MAX_FINISHED_JOBS of them (the latest) for FINISHED_JOB_LIFETIME
+pub const MAX_FINISHED_JOBS: usize = 64;
+const FINISHED_JOB_LIFETIME: Duration = Duration::from_secs(3600);
+// Requests handled at once; a stream holds its handler until its job ends, so streams get all of them but one
+const HANDLERS: usize = 8;
+// Paths recurse at each bounce, up to a max_depth of 1000: about 2 MiB of stack, the default size of a thread,
+// in a debug build; the render workers get much more
+const RENDER_STACK_SIZE: usize = 64 << 20;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum JobState {
+    Queued,
+    Running,
+    Done,
+    Cancelled,
+    Failed,
+}
+
+impl JobState {
+    fn name(self) -> &'static str {
+        match self {
+            JobState::Queued => "queued",
+            JobState::Running => "running",
+            JobState::Done => "done",
+            JobState::Cancelled => "cancelled",
+            JobState::Failed => "failed",
+        }
+    }
+
+    fn is_finished(self) -> bool {
+        matches!(self, JobState::Done | JobState::Cancelled | JobState::Failed)
+    }
+}
+
+struct JobStatus {
+    state: JobState,
+    error: Option<String>,
+    passes_done: i32,
+    started: Option<Instant>,
+    elapsed: Duration, // Once finished
+    finished: Option<Instant>,
+    snapshot: Option<Arc<Vec<u8>>>, // Latest .png
+    snapshot_version: u64,
+}
+
+// A render submitted to the server; its scene is built on submission, so invalid ones are refused right away
+struct Job {
+    id: u64,
+    setup: SceneSetup,
+    passes: i32,
+    status: Mutex<JobStatus>,
+    changed: Condvar, // New snapshot or state
+}
+
+impl Job {
+    fn update(&self, f: impl FnOnce(&mut JobStatus)) {
+        f(&mut self.status.lock().unwrap());
+        self.changed.notify_all();
+    }
+
+    fn to_json(&self) -> Value {
+        let status = self.status.lock().unwrap();
+        let elapsed = match (status.state, status.started) {
+            (JobState::Running, Some(started)) => started.elapsed(),
+            _ => status.elapsed,
+        };
+        // From the average duration of the passes so far
+        let eta = (status.state == JobState::Running && status.passes_done > 0)
+            .then(|| elapsed.as_secs_f64() / status.passes_done as f64 * (self.passes - status.passes_done) as f64);
+        json!({
+            "id": self.id,
+            "state": status.state.name(),
+            "error": status.error,
+            "passes_done": status.passes_done,
+            "passes": self.passes,
+            "elapsed_seconds": elapsed.as_secs_f64(),
+            "eta_seconds": eta,
+            "width": self.setup.renderer.settings.width,
+            "height": self.setup.renderer.settings.height,
+        })
+    }
+}
+
+struct Jobs {
+    next_id: u64,
+    jobs: BTreeMap<u64, Arc<Job>>,
+}
+
+impl Jobs {
+    // Forgets the finished jobs past their lifetime, and the oldest ones beyond MAX_FINISHED_JOBS
+    fn prune(&mut self) {
+        let mut finished: Vec<(Instant, u64)> =
+            self.jobs.values().filter_map(|job| job.status.lock().unwrap().finished.map(|t| (t, job.id))).collect();
+        finished.sort();
+        let excess = finished.len().saturating_sub(MAX_FINISHED_JOBS);
+        for (k, (t, id)) in finished.into_iter().enumerate() {
+            if k < excess || t.elapsed() >= FINISHED_JOB_LIFETIME {
+                self.jobs.remove(&id);
+            }
+        }
+    }
+}
+
+// Render server: jobs are posted as scene descriptions, rendered one at a time on the workers
+// POST /jobs, GET /jobs, GET /jobs/<id>, GET /jobs/<id>/image.png, GET /jobs/<id>/stream, DELETE /jobs/<id>
+pub fn serve(address: &str) -> io::Result<()> {
+    let server = Server::http(address).map_err(io::Error::other)?;
+    eprintln!("Listening on http://{}", address);
+    let render_server = RenderServer::start();
+    thread::scope(|scope| {
+        for _ in 0..HANDLERS {
+            scope.spawn(|| {
+                while let Ok(mut request) = server.recv() {
+                    let response = render_server.handle(&mut request);
+                    // The client may have gone away
+                    let _ = request.respond(response);
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+// The jobs, and the thread rendering them
+pub struct RenderServer {
+    jobs: Mutex<Jobs>,
+    queue: Sender<Arc<Job>>,
+    streams: Arc<AtomicUsize>, // Open
+}
+
+impl RenderServer {
+    pub fn start() -> RenderServer {
+        let workers = ThreadPoolBuilder::new().stack_size(RENDER_STACK_SIZE).build().expect("creating the workers");
+        let (queue, waiting) = mpsc::channel();
+        thread::spawn(move || run_jobs(waiting, workers));
+        RenderServer {
+            jobs: Mutex::new(Jobs { next_id: 1, jobs: BTreeMap::new() }),
+            queue,
+            streams: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    // Response to a request; a stream is read as it is sent
+    pub fn handle(&self, request: &mut Request) -> ResponseBox {
+        let path: Vec<String> = request.url().trim_matches('/').split('/').map(str::to_string).collect();
+        let path: Vec<&str> = path.iter().map(String::as_str).collect();
+        self.jobs.lock().unwrap().prune();
+        let job = |id: &str| id.parse().ok().and_then(|id: u64| self.jobs.lock().unwrap().jobs.get(&id).cloned());
+
+        match (request.method(), path.as_slice()) {
+            (Method::Post, ["jobs"]) => self.submit(request),
+            (Method::Get, ["jobs"]) => {
+                let list: Vec<Value> = self.jobs.lock().unwrap().jobs.values().map(|job| job.to_json()).collect();
+                json_response(200, &Value::Array(list))
+            }
+            (Method::Get, ["jobs", id]) => match job(id) {
+                Some(job) => json_response(200, &job.to_json()),
+                None => not_found(),
+            },
+            // Cancels a job, or forgets a finished one
+            (Method::Delete, ["jobs", id]) => match job(id) {
+                Some(job) => {
+                    let finished = job.status.lock().unwrap().state.is_finished();
+                    if finished {
+                        self.jobs.lock().unwrap().jobs.remove(&job.id);
+                    }
+                    job.update(|s| {
+                        if s.state == JobState::Queued {
+                            s.state = JobState::Cancelled;
+                            s.finished = Some(Instant::now());
+                        }
+                    });
+                    job.setup.renderer.cancel();
+                    json_response(200, &job.to_json())
+                }
+                None => not_found(),
+            },
+            (Method::Get, ["jobs", id, "image.png"]) => match job(id).and_then(|job| job.status.lock().unwrap().snapshot.clone()) {
+                Some(png) => Response::from_data(png.to_vec()).with_header(header("Content-Type", "image/png")).boxed(),
+                None => not_found(),
+            },
+            (Method::Get, ["jobs", id, "stream"]) => match job(id) {
+                Some(_) if self.streams.fetch_add(1, Ordering::Relaxed) >= HANDLERS - 1 => {
+                    self.streams.fetch_sub(1, Ordering::Relaxed);
+                    json_response(503, &json!({ "error": "too many streams" }))
+                }
+                Some(job) => Response::new(
+                    200.into(),
+                    vec![header("Content-Type", &format!("multipart/x-mixed-replace; boundary={}", BOUNDARY))],
+                    SnapshotStream { job, version: 0, part: Cursor::new(Vec::new()), ended: false, streams: self.streams.clone() },
+                    None,
+                    None,
+                )
+                .boxed(),
+                None => not_found(),
+            },
+            (_, ["jobs", ..]) => json_response(405, &json!({ "error": "method not allowed" })),
+            _ => not_found(),
+        }
+    }
+
+    fn submit(&self, request: &mut Request) -> ResponseBox {
+        let mut body = String::new();
+        if request.as_reader().take(MAX_BODY).read_to_string(&mut body).is_err() {
+            return json_response(400, &json!({ "error": "the body must be a scene description in UTF-8" }));
+        }
+        // Building may panic on values the checks missed
+        let setup = SceneDescription::from_json(&body).and_then(|description| {
+            panic::catch_unwind(AssertUnwindSafe(|| description.build())).unwrap_or_else(|e| Err(panic_message(e)))
+        });
+        let setup = match setup {
+            Ok(setup) => setup,
+            Err(error) => return json_response(400, &json!({ "error": error })),
+        };
+
+        let passes = setup.renderer.settings.passes();
+        let mut jobs = self.jobs.lock().unwrap();
+        let job = Arc::new(Job {
+            id: jobs.next_id,
+            passes,
+            setup,
+            status: Mutex::new(JobStatus {
+                state: JobState::Queued,
+                error: None,
+                passes_done: 0,
+                started: None,
+                elapsed: Duration::ZERO,
+                finished: None,
+                snapshot: None,
+                snapshot_version: 0,
+            }),
+            changed: Condvar::new(),
+        });
+        jobs.next_id += 1;
+        jobs.jobs.insert(job.id, job.clone());
+        self.queue.send(job.clone()).expect("the render thread is gone");
+        json_response(201, &job.to_json())
+    }
+}
+
+fn run_jobs(waiting: Receiver<Arc<Job>>, workers: ThreadPool) {
+    for job in waiting {
+        if job.status.lock().unwrap().state != JobState::Queued {
+            continue; // Cancelled while queued
+        }
+        job.update(|s| {
+            s.state = JobState::Running;
+            s.started = Some(Instant::now());
+        });
+        // A panic in the render (a bad scene) fails the job, not the server
+        let result = panic::catch_unwind(AssertUnwindSafe(|| workers.install(|| render(&job))));
+        job.update(|s| {
+            s.elapsed = s.started.map_or(Duration::ZERO, |t| t.elapsed());
+            s.finished = Some(Instant::now());
+            s.state = match result {
+                Ok(true) => JobState::Done,
+                Ok(false) => JobState::Cancelled,
+                Err(e) => {
+                    s.error = Some(panic_message(e));
+                    JobState::Failed
+                }
+            };
+        });
+    }
+}
+
+// False if cancelled
+fn render(job: &Job) -> bool {
+    let setup = &job.setup;
+    let renderer = &setup.renderer;
+    let settings = &renderer.settings;
+    let tiles = tiles(settings.width, settings.height, setup.tile_size, TileOrder::Spiral);
+    let merger = Mutex::new(TileMerger::new(Film::new(settings.width, settings.height, setup.filter.clone())));
+    let mut last_snapshot: Option<Instant> = None;
+    let passes = renderer.render_passes(&merger, &tiles, 0..job.passes, |pass, film| {
+        let last = pass + 1 == job.passes;
+        let snapshot = (last || last_snapshot.is_none_or(|t| t.elapsed() >= SNAPSHOT_INTERVAL)).then(|| {
+            last_snapshot = Some(Instant::now());
+            let mut png = Vec::new();
+            film.write_png(&mut png, Color::new(1.0, 1.0, 1.0), setup.gamma).expect("encoding the snapshot");
+            Arc::new(png)
+        });
+        job.update(|s| {
+            s.passes_done = pass + 1;
+            if let Some(png) = snapshot {
+                s.snapshot = Some(png);
+                s.snapshot_version += 1;
+            }
+        });
+        true
+    });
+    passes == job.passes
+}
+
+const BOUNDARY: &str = "snapshot";
+
+// Body of a stream: each new snapshot as a part of a multipart response, until the job is finished
+struct SnapshotStream {
+    job: Arc<Job>,
+    version: u64, // Of the last snapshot sent
+    part: Cursor<Vec<u8>>,
+    ended: bool,
+    streams: Arc<AtomicUsize>, // Of the server, counting this one
+}
+
+impl Drop for SnapshotStream {
+    fn drop(&mut self) {
+        self.streams.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Read for SnapshotStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let n = self.part.read(buf)?;
+            if n > 0 {
+                return Ok(n);
+            }
+            // Waits for the next snapshot
+            let status = self.job.status.lock().unwrap();
+            let status = self
+                .job
+                .changed
+                .wait_while(status, |s| s.snapshot_version == self.version && !s.state.is_finished())
+                .unwrap();
+            if status.snapshot_version == self.version {
+                if self.ended {
+                    return Ok(0);
+                }
+                self.ended = true;
+                self.part = Cursor::new(format!("--{}--\r\n", BOUNDARY).into_bytes());
+                continue;
+            }
+            self.version = status.snapshot_version;
+            let png = status.snapshot.clone().unwrap();
+            let mut part = format!("--{}\r\nContent-Type: image/png\r\nContent-Length: {}\r\n\r\n", BOUNDARY, png.len()).into_bytes();
+            part.extend_from_slice(&png);
+            part.extend_from_slice(b"\r\n");
+            self.part = Cursor::new(part);
+        }
+    }
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).unwrap()
+}
+
+fn json_response(code: u16, value: &Value) -> ResponseBox {
+    Response::from_data(value.to_string())
+        .with_status_code(code)
+        .with_header(header("Content-Type", "application/json"))
+        .boxed()
+}
+
+fn not_found() -> ResponseBox {
+    json_response(404, &json!({ "error": "not found" }))
+}
+
+fn panic_message(e: Box<dyn std::any::Any + Send>) -> String {
+    match e.downcast::<String>() {
+        Ok(message) => *message,
+        Err(e) => e.downcast_ref::<&str>().map_or("the render panicked".to_string(), |m| m.to_string()),
+    }
+}
@@ -0,0 +1,9 @@
+mod solid;
+mod checker;
+mod image;
+mod perlin;
+
+pub use solid::*;
+pub use checker::*;
+pub use image::*;
+pub use perlin::*;
\ No newline at end of file
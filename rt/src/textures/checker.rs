@@ -0,0 +1,29 @@
+use std::sync::Arc;
+use crate::basics::{Color, Point3};
+use crate::traits::Texture;
+
+// Alternating squares of two textures, in (u, v) space
+#[derive(Debug)]
+pub struct Checker {
+    scale: f64, // Number of squares per unit of u and v
+    even: Arc<dyn Texture>,
+    odd: Arc<dyn Texture>,
+}
+
+impl Checker {
+    pub fn new(scale: f64, even: Arc<dyn Texture>, odd: Arc<dyn Texture>) -> Checker {
+        Checker { scale, even, odd }
+    }
+}
+
+impl Texture for Checker {
+    fn value(&self, u: f64, v: f64, p: Point3) -> Color {
+        let i = (self.scale * u).floor() as i64;
+        let j = (self.scale * v).floor() as i64;
+        if (i + j) % 2 == 0 {
+            self.even.value(u, v, p)
+        } else {
+            self.odd.value(u, v, p)
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use std::fs;
+use std::io;
+use crate::basics::{Color, Point3};
+use crate::traits::Texture;
+use crate::utils::clamp;
+
+// Picture read from a .ppm file (P3 or P6), as rendered by this program
+// Values are linear: the file's gamma is removed when loading
+#[derive(Debug)]
+pub struct ImageTexture {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl ImageTexture {
+    pub fn load(path: &str, gamma: f64) -> io::Result<ImageTexture> {
+        let data = fs::read(path)?;
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path, msg));
+
+        // Header: magic, width, height, max value, separated by whitespace and comments
+        let mut fields = Vec::new();
+        let mut pos = 0;
+        while fields.len() < 4 {
+            while pos < data.len() && (data[pos].is_ascii_whitespace() || data[pos] == b'#') {
+                if data[pos] == b'#' {
+                    while pos < data.len() && data[pos] != b'\n' {
+                        pos += 1;
+                    }
+                } else {
+                    pos += 1;
+                }
+            }
+            let start = pos;
+            while pos < data.len() && !data[pos].is_ascii_whitespace() {
+                pos += 1;
+            }
+            if start == pos {
+                return Err(invalid("truncated header"));
+            }
+            fields.push(String::from_utf8_lossy(&data[start..pos]).to_string());
+        }
+        let number = |s: &str| s.parse::<usize>().map_err(|_| invalid("bad header value"));
+        let width = number(&fields[1])?;
+        let height = number(&fields[2])?;
+        let max = number(&fields[3])?.max(1) as f64;
+
+        let values: Vec<f64> = match fields[0].as_str() {
+            "P6" => data[(pos + 1).min(data.len())..].iter().map(|&b| b as f64).collect(),
+            "P3" => String::from_utf8_lossy(&data[pos..])
+                .split_ascii_whitespace()
+                .map(|s| s.parse::<f64>().map_err(|_| invalid("bad pixel value")))
+                .collect::<io::Result<_>>()?,
+            _ => return Err(invalid("not a P3 or P6 file")),
+        };
+        if values.len() < 3 * width * height {
+            return Err(invalid("missing pixels"));
+        }
+
+        let pixels = values
+            .chunks(3)
+            .take(width * height)
+            .map(|c| Color::new((c[0] / max).powf(gamma), (c[1] / max).powf(gamma), (c[2] / max).powf(gamma)))
+            .collect();
+        Ok(ImageTexture { width, height, pixels })
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _p: Point3) -> Color {
+        if self.pixels.is_empty() {
+            return Color::new(0.0, 1.0, 1.0);
+        }
+
+        // The first row of the file is the top of the picture (v = 1)
+        let u = clamp(u, 0.0, 1.0);
+        let v = 1.0 - clamp(v, 0.0, 1.0);
+        let i = ((u * self.width as f64) as usize).min(self.width - 1);
+        let j = ((v * self.height as f64) as usize).min(self.height - 1);
+        self.pixels[j * self.width + i]
+    }
+}
@@ -0,0 +1,132 @@
+use crate::basics::{Color, Point3, Vec3, dot, unit_vec};
+use crate::traits::Texture;
+use crate::utils::Rng;
+
+const POINT_COUNT: usize = 256;
+
+// Gradient noise, smooth and repeatable in space
+#[derive(Debug)]
+pub struct Perlin {
+    rand_vec: Vec<Vec3>,
+    perm_x: Vec<usize>,
+    perm_y: Vec<usize>,
+    perm_z: Vec<usize>,
+}
+
+impl Perlin {
+    // The same seed gives the same noise
+    pub fn new(seed: u64) -> Perlin {
+        let mut rng = Rng::new(seed);
+        Perlin {
+            rand_vec: (0..POINT_COUNT).map(|_| unit_vec(random_vec(&mut rng))).collect(),
+            perm_x: generate_perm(&mut rng),
+            perm_y: generate_perm(&mut rng),
+            perm_z: generate_perm(&mut rng),
+        }
+    }
+
+    // In [-1, 1]
+    pub fn noise(&self, p: Point3) -> f64 {
+        let u = p.x() - p.x().floor();
+        let v = p.y() - p.y().floor();
+        let w = p.z() - p.z().floor();
+        let i = p.x().floor() as i64;
+        let j = p.y().floor() as i64;
+        let k = p.z().floor() as i64;
+
+        let mut c = [[[Vec3::default(); 2]; 2]; 2];
+        for (di, plane) in c.iter_mut().enumerate() {
+            for (dj, row) in plane.iter_mut().enumerate() {
+                for (dk, corner) in row.iter_mut().enumerate() {
+                    *corner = self.rand_vec[self.perm_x[((i + di as i64) & 255) as usize]
+                        ^ self.perm_y[((j + dj as i64) & 255) as usize]
+                        ^ self.perm_z[((k + dk as i64) & 255) as usize]];
+                }
+            }
+        }
+        perlin_interp(&c, u, v, w)
+    }
+
+    // Sum of `depth` octaves of noise, in [0, 2[
+    pub fn turbulence(&self, p: Point3, depth: usize) -> f64 {
+        let mut accum = 0.0;
+        let mut temp_p = p;
+        let mut weight = 1.0;
+        for _ in 0..depth {
+            accum += weight * self.noise(temp_p).abs();
+            weight *= 0.5;
+            temp_p *= 2.0;
+        }
+        accum
+    }
+}
+
+impl Default for Perlin {
+    fn default() -> Perlin {
+        Perlin::new(0)
+    }
+}
+
+fn random_vec(rng: &mut Rng) -> Vec3 {
+    Vec3::new(rng.rand_range(-1.0, 1.0), rng.rand_range(-1.0, 1.0), rng.rand_range(-1.0, 1.0))
+}
+
+fn generate_perm(rng: &mut Rng) -> Vec<usize> {
+    let mut p: Vec<usize> = (0..POINT_COUNT).collect();
+    for i in (1..POINT_COUNT).rev() {
+        let target = rng.rand_index(i + 1);
+        p.swap(i, target);
+    }
+    p
+}
+
+// Trilinear interpolation of the gradients, with a Hermite smoothing
+fn perlin_interp(c: &[[[Vec3; 2]; 2]; 2], u: f64, v: f64, w: f64) -> f64 {
+    let uu = u * u * (3.0 - 2.0 * u);
+    let vv = v * v * (3.0 - 2.0 * v);
+    let ww = w * w * (3.0 - 2.0 * w);
+    let mut accum = 0.0;
+    for (i, plane) in c.iter().enumerate() {
+        for (j, row) in plane.iter().enumerate() {
+            for (k, &corner) in row.iter().enumerate() {
+                let (fi, fj, fk) = (i as f64, j as f64, k as f64);
+                let weight = Vec3::new(u - fi, v - fj, w - fk);
+                accum += (fi * uu + (1.0 - fi) * (1.0 - uu))
+                    * (fj * vv + (1.0 - fj) * (1.0 - vv))
+                    * (fk * ww + (1.0 - fk) * (1.0 - ww))
+                    * dot(corner, weight);
+            }
+        }
+    }
+    accum
+}
+
+// Marble-like solid texture (the hit point is used, not u, v)
+#[derive(Debug)]
+pub struct NoiseTexture {
+    noise: Perlin,
+    scale: f64,
+    color: Color,
+}
+
+impl NoiseTexture {
+    pub fn new(scale: f64, color: Color) -> NoiseTexture {
+        NoiseTexture {
+            noise: Perlin::default(),
+            scale,
+            color,
+        }
+    }
+
+    // Another pattern of noise
+    pub fn with_seed(mut self, seed: u64) -> NoiseTexture {
+        self.noise = Perlin::new(seed);
+        self
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _u: f64, _v: f64, p: Point3) -> Color {
+        0.5 * (1.0 + f64::sin(self.scale * p.z() + 10.0 * self.noise.turbulence(p, 7))) * self.color
+    }
+}
@@ -0,0 +1,20 @@
+use crate::basics::{Color, Point3};
+use crate::traits::Texture;
+
+// Same color everywhere
+#[derive(Debug)]
+pub struct SolidColor {
+    color: Color,
+}
+
+impl SolidColor {
+    pub fn new(color: Color) -> SolidColor {
+        SolidColor { color }
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _u: f64, _v: f64, _p: Point3) -> Color {
+        self.color
+    }
+}
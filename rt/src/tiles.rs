@@ -0,0 +1,78 @@
+use crate::film::TileRect;
+
+// Order in which the tiles are handed to the workers, and merged in the film
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TileOrder {
+    Scanline, // Rows of tiles from the top of the image
+    Spiral,   // From the center of the image outwards, where the subject usually is
+    Hilbert,  // Along a Hilbert curve: consecutive tiles are neighbors
+}
+
+impl TileOrder {
+    pub fn parse(name: &str) -> Option<TileOrder> {
+        match name {
+            "scanline" => Some(TileOrder::Scanline),
+            "spiral" => Some(TileOrder::Spiral),
+            "hilbert" => Some(TileOrder::Hilbert),
+            _ => None,
+        }
+    }
+}
+
+// Tiles of at most size x size pixels covering the image, in the given order
+pub fn tiles(width: usize, height: usize, size: usize, order: TileOrder) -> Vec<TileRect> {
+    let nx = width.div_ceil(size);
+    let ny = height.div_ceil(size);
+    let mut grid: Vec<(usize, usize)> = (0..ny).flat_map(|ty| (0..nx).map(move |tx| (tx, ty))).collect();
+
+    match order {
+        // The film's rows go up
+        TileOrder::Scanline => grid.sort_by_key(|&(tx, ty)| (ny - 1 - ty, tx)),
+        // Square rings around the center, each walked by angle
+        TileOrder::Spiral => {
+            let (cx, cy) = ((nx - 1) as f64 / 2.0, (ny - 1) as f64 / 2.0);
+            let key = |&(tx, ty): &(usize, usize)| {
+                let (dx, dy) = (tx as f64 - cx, ty as f64 - cy);
+                (f64::max(dx.abs(), dy.abs()), f64::atan2(dy, dx))
+            };
+            grid.sort_by(|a, b| {
+                let (ka, kb) = (key(a), key(b));
+                ka.0.total_cmp(&kb.0).then(ka.1.total_cmp(&kb.1))
+            });
+        }
+        TileOrder::Hilbert => {
+            let n = usize::max(nx, ny).next_power_of_two();
+            grid.sort_by_key(|&(tx, ty)| hilbert_index(n, tx, ty));
+        }
+    }
+
+    grid.into_iter()
+        .map(|(tx, ty)| TileRect {
+            x0: tx * size,
+            y0: ty * size,
+            x1: usize::min((tx + 1) * size, width),
+            y1: usize::min((ty + 1) * size, height),
+        })
+        .collect()
+}
+
+// Distance along the Hilbert curve filling an n x n grid (n a power of two) of the cell (x, y)
+fn hilbert_index(n: usize, mut x: usize, mut y: usize) -> usize {
+    let mut d = 0;
+    let mut s = n / 2;
+    while s > 0 {
+        let rx = usize::from(x & s > 0);
+        let ry = usize::from(y & s > 0);
+        d += s * s * ((3 * rx) ^ ry);
+        // Rotate the quadrant
+        if ry == 0 {
+            if rx == 1 {
+                x = s - 1 - (x & (s - 1));
+                y = s - 1 - (y & (s - 1));
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s /= 2;
+    }
+    d
+}
@@ -1,5 +1,17 @@
 mod hittable;
 mod material;
+mod light;
+mod texture;
+mod density;
+mod sampler;
+mod filter;
+mod camera;
 
 pub use hittable::*;
-pub use material::*;
\ No newline at end of file
+pub use material::*;
+pub use light::*;
+pub use texture::*;
+pub use density::*;
+pub use sampler::*;
+pub use filter::*;
+pub use camera::*;
\ No newline at end of file
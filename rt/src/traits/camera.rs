@@ -0,0 +1,16 @@
+use std::fmt::Debug;
+use crate::basics::Ray;
+use crate::traits::Sampler;
+
+// Projection of the film on the scene
+pub trait Camera: Send + Sync + Debug {
+    // Ray through the film position (u, v) in [0, 1] x [0, 1], v going up
+    // None where the projection sees nothing (outside the circle of a fisheye): the sample is black
+    fn generate_ray(&self, u: f64, v: f64, sampler: &mut dyn Sampler) -> Option<Ray>;
+
+    // Parts of the film seen by separate cameras (the eyes of a stereo pair), as [u0, v0, u1, v1]:
+    // samples are not filtered across their edges
+    fn views(&self) -> Vec<[f64; 4]> {
+        vec![[0.0, 0.0, 1.0, 1.0]]
+    }
+}
@@ -0,0 +1,10 @@
+use std::fmt::Debug;
+use crate::basics::Point3;
+
+// Density of a heterogeneous medium, varying in space
+pub trait DensityField: Send + Sync + Debug {
+    fn density(&self, p: Point3) -> f64;
+
+    // Upper bound of density(), the majorant used by delta tracking
+    fn max_density(&self) -> f64;
+}
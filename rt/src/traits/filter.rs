@@ -0,0 +1,9 @@
+use std::fmt::Debug;
+
+// Reconstruction filter: weight of a sample at offset (x, y) from a pixel center, in pixels
+pub trait Filter: Send + Sync + Debug {
+    // Samples farther than the radius (on x or y) do not contribute
+    fn radius(&self) -> f64;
+
+    fn evaluate(&self, x: f64, y: f64) -> f64;
+}
@@ -1,15 +1,19 @@
+use std::fmt::Debug;
 use std::sync::Arc;
 use crate::basics::*;
 
-use crate::traits::Material;
+use crate::traits::{Material, Sampler};
 
 #[derive(Clone, Default)]
 pub struct HitRecord {
     pub p: Point3,
     pub normal: Vec3,
     pub t: f64,
+    pub u: f64, // Surface coordinates, for textures
+    pub v: f64,
 	pub front: bool,
     pub mat: Option<Arc<dyn Material>>,
+    pub object_id: u32, // Index + 1 of the object in the world, 0 if none
 }
 
 impl HitRecord {
@@ -27,6 +31,37 @@ impl HitRecord {
     }
 }
 
-pub trait Hittable: Send + Sync {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool;
+// Only participating media draw samples in hit()
+pub trait Hittable: Send + Sync + Debug {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord, sampler: &mut dyn Sampler) -> bool;
+
+    // Light sampling: solid-angle pdf of reaching the object from `origin` along `direction`
+    fn pdf_value(&self, _origin: Point3, _direction: Vec3, _sampler: &mut dyn Sampler) -> f64 {
+        0.0
+    }
+
+    // Light sampling: random direction from `origin` towards a point of the surface
+    fn random(&self, _origin: Point3, _sampler: &mut dyn Sampler) -> Vec3 {
+        Vec3::new(1.0, 0.0, 0.0)
+    }
+
+    // Materials of the object, for their ids
+    fn materials(&self) -> Vec<Arc<dyn Material>> {
+        Vec::new()
+    }
+
+    // True if the object has an emissive material and should be sampled as a light
+    fn is_emissive(&self) -> bool {
+        false
+    }
+
+    // Shadow rays: only surfaces block them, participating media are crossed
+    fn shadow_hit(&self, ray: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord, sampler: &mut dyn Sampler) -> bool {
+        self.hit(ray, t_min, t_max, rec, sampler)
+    }
+
+    // Shadow rays: fraction of light crossing the media of the object between t_min and t_max
+    fn transmittance(&self, _ray: &Ray, _t_min: f64, _t_max: f64, _sampler: &mut dyn Sampler) -> f64 {
+        1.0
+    }
 }
\ No newline at end of file
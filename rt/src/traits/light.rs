@@ -0,0 +1,30 @@
+use std::fmt::Debug;
+use crate::basics::{Color, Point3, Ray, Vec3};
+use crate::traits::Sampler;
+
+// Incident light at a point, from a light without geometry
+pub struct LightSample {
+    pub wi: Vec3,  // Unit direction towards the light
+    pub li: Color, // Incident radiance (or irradiance for delta lights)
+    pub dist: f64, // Distance to the light, INFINITY for directional lights
+}
+
+// Lights are not part of the world: they do not block rays, shadow rays are tested against the world
+pub trait Light: Send + Sync + Debug {
+    fn sample_li(&self, p: Point3, sampler: &mut dyn Sampler) -> Option<LightSample>;
+
+    // Point, spot and sharp directional lights cannot be reached by a scattered ray
+    fn is_delta(&self) -> bool {
+        false
+    }
+
+    // Radiance carried along a ray that reaches the light before t_max, and the ray parameter there
+    fn emitted_along(&self, _r: &Ray, _t_max: f64) -> Option<(Color, f64)> {
+        None
+    }
+
+    // Solid-angle pdf of sample_li() returning `direction` from `origin`, 0.0 for delta lights
+    fn pdf_li(&self, _origin: Point3, _direction: Vec3) -> f64 {
+        0.0
+    }
+}
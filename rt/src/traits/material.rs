@@ -1,17 +1,46 @@
-use crate::basics::{Color, Ray};
-use crate::traits::HitRecord;
+use std::fmt::Debug;
+use crate::basics::{Color, Ray, rgb_to_spectrum};
+use crate::traits::{HitRecord, Sampler};
 
 // Send + Sync necessary to work with Arc & rayon crate
-pub trait Material: Send + Sync {
+pub trait Material: Send + Sync + Debug {
     fn scatter(
         &self,
         r_in: &Ray,
         rec: &HitRecord,
         attenuation: &mut Color,
         scattered: &mut Ray,
+        sampler: &mut dyn Sampler,
     ) -> bool;
 
-    fn emitted(&self) -> Color {
+    // Light leaving the surface towards the origin of r_in
+    fn emitted(&self, _r_in: &Ray, _rec: &HitRecord) -> Color {
         Color::new(0.0, 0.0, 0.0)
     }
+
+    // Spectral response of the emission at `lambda`; the RGB emission is upsampled by default
+    fn emitted_spectral(&self, r_in: &Ray, rec: &HitRecord, lambda: f64) -> f64 {
+        rgb_to_spectrum(self.emitted(r_in, rec), lambda)
+    }
+
+    // Emission carried by r_in: RGB, or the spectral value in every component when the path has a wavelength
+    fn emission(&self, r_in: &Ray, rec: &HitRecord) -> Color {
+        match r_in.wavelength() {
+            Some(lambda) => {
+                let s = self.emitted_spectral(r_in, rec, lambda);
+                Color::new(s, s, s)
+            }
+            None => self.emitted(r_in, rec),
+        }
+    }
+
+    // Pdf of `scattered` (solid angle); 0.0 for specular materials, which skip light sampling
+    fn scattering_pdf(&self, _r_in: &Ray, _rec: &HitRecord, _scattered: &Ray) -> f64 {
+        0.0
+    }
+
+    // Emissive surfaces, that light sampling can reach
+    fn is_emissive(&self) -> bool {
+        false
+    }
 }
@@ -0,0 +1,126 @@
+use crate::utils::Rng;
+
+// What a sample value is used for; each purpose has its own dimensions, and each bounce its own set
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Dimension {
+    Pixel,      // Position in the pixel (2D)
+    Lens,       // Position on the lens (2D)
+    Time,       // Shutter time
+    Wavelength, // Spectral mode
+    Medium,     // Scattering distance in the media (the rest of a delta tracking walk is random)
+    Bsdf,       // Scattered direction (2D), then the choice between reflection and refraction
+    Light,      // Choice of the light, then the point on it (2D)
+}
+
+const CAMERA_DIMENSIONS: u32 = 6;
+const BOUNCE_DIMENSIONS: u32 = 8;
+
+impl Dimension {
+    // First dimension and number of dimensions; 2D draws start on even dimensions
+    fn range(self, bounce: u32) -> (u32, u32) {
+        let first = CAMERA_DIMENSIONS + bounce * BOUNCE_DIMENSIONS;
+        match self {
+            Dimension::Pixel => (0, 2),
+            Dimension::Lens => (2, 2),
+            Dimension::Time => (4, 1),
+            Dimension::Wavelength => (5, 1),
+            Dimension::Medium => (first, 2),
+            Dimension::Bsdf => (first + 2, 3),
+            Dimension::Light => (first + 5, 3),
+        }
+    }
+}
+
+// Dimensions given to the current draws; past them, or for independent sampling, draws come from `rng`
+pub struct Dimensions {
+    bounce: u32,
+    next: u32,
+    remaining: u32,
+    pub rng: Rng,
+}
+
+impl Dimensions {
+    pub fn new() -> Dimensions {
+        Dimensions {
+            bounce: 0,
+            next: 0,
+            remaining: 0,
+            rng: Rng::new(0),
+        }
+    }
+
+    // New pixel sample: first bounce, and the random numbers of this sample
+    pub fn reset(&mut self, rng: Rng) {
+        self.bounce = 0;
+        self.next = 0;
+        self.remaining = 0;
+        self.rng = rng;
+    }
+
+    pub fn start(&mut self, purpose: Dimension) {
+        (self.next, self.remaining) = purpose.range(self.bounce);
+    }
+
+    pub fn next_bounce(&mut self) {
+        self.bounce += 1;
+        self.remaining = 0;
+    }
+
+    // Dimension of the next draw, if any is left
+    pub fn take(&mut self) -> Option<u32> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.next += 1;
+        Some(self.next - 1)
+    }
+}
+
+impl Default for Dimensions {
+    fn default() -> Dimensions {
+        Dimensions::new()
+    }
+}
+
+// Source of the sample values of a pixel sample, in [0, 1[
+// A sampler is used by one thread at a time: each worker renders its pixels with its own sampler
+pub trait Sampler: Send {
+    // Pixel (x, y), sample `index` among the samples of the pixel
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32);
+
+    // Value of the current pixel sample in `dim`
+    fn sample(&mut self, dim: u32) -> f64;
+
+    fn dimensions(&mut self) -> &mut Dimensions;
+
+    // Next draws are used for `purpose`, at the current bounce
+    fn start(&mut self, purpose: Dimension) {
+        self.dimensions().start(purpose);
+    }
+
+    fn next_bounce(&mut self) {
+        self.dimensions().next_bounce();
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        match self.dimensions().take() {
+            Some(dim) => self.sample(dim),
+            None => self.dimensions().rng.rand_01(),
+        }
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        (self.get_1d(), self.get_1d())
+    }
+
+    // Return a real in [min, max[
+    fn get_range(&mut self, min: f64, max: f64) -> f64 {
+        min + (max - min) * self.get_1d()
+    }
+
+    // Return an index in [0, n[
+    fn get_index(&mut self, n: usize) -> usize {
+        ((self.get_1d() * n as f64) as usize).min(n - 1)
+    }
+}
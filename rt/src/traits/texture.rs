@@ -0,0 +1,7 @@
+use std::fmt::Debug;
+use crate::basics::{Color, Point3};
+
+// Color varying on a surface, from its (u, v) coordinates or the hit point
+pub trait Texture: Send + Sync + Debug {
+    fn value(&self, u: f64, v: f64, p: Point3) -> Color;
+}
@@ -1,9 +1,7 @@
-use rand::Rng;
-
 // Constants
 
 pub use std::f64::consts::PI;
-pub use std::f64::INFINITY;
+pub const INFINITY: f64 = f64::INFINITY;
 
 // Utility functions
 
@@ -11,14 +9,81 @@ pub fn degrees_to_radians(degrees: f64) -> f64 {
     degrees * PI / 180.0
 }
 
-pub fn rand_01() -> f64 {
-    // Return a random real in [0.0, 1.0[
-    rand::rng().random_range(0.0..1.0)
+// Random numbers (PCG32): seeded for each sample of each pixel, so that a render only depends on the seed
+#[derive(Clone)]
+pub struct Rng {
+    state: u64,
+    inc: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng::with_stream(seed, 0)
+    }
+
+    // Independent sequences for the same seed
+    pub fn with_stream(seed: u64, stream: u64) -> Rng {
+        let mut rng = Rng {
+            state: 0,
+            inc: (stream << 1) | 1,
+        };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
+    }
+
+    // Sequence of the sample `index` of the pixel (x, y) for a scene seed: the same whatever the thread
+    pub fn for_sample(seed: u64, x: u32, y: u32, index: u32) -> Rng {
+        let pixel = pixel_seed(seed, x, y);
+        Rng::with_stream(pixel, mix64(pixel.wrapping_add(index as u64)))
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old.wrapping_mul(6364136223846793005).wrapping_add(self.inc);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        let rot = (old >> 59) as u32;
+        xorshifted.rotate_right(rot)
+    }
+
+    pub fn rand_01(&mut self) -> f64 {
+        // Return a random real in [0.0, 1.0[
+        self.next_u32() as f64 / 4294967296.0
+    }
+
+    pub fn rand_range(&mut self, min: f64, max: f64) -> f64 {
+        // Return a random real in [min, max[
+        min + (max - min) * self.rand_01()
+    }
+
+    // Return a random index in [0, n[
+    pub fn rand_index(&mut self, n: usize) -> usize {
+        ((self.rand_01() * n as f64) as usize).min(n - 1)
+    }
+}
+
+// Bit mixer of SplitMix64, to derive unrelated seeds from close values
+pub fn mix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+// Real in [0.0, 1.0[ derived from a key
+pub fn hash_01(key: u64) -> f64 {
+    (mix64(key) >> 32) as f64 / 4294967296.0
 }
 
-pub fn rand_range(min: f64, max: f64) -> f64 {
-    // Return a random real in [min, max[
-    min + (max - min) * rand_01()
+// Hash of a byte string (FNV-1a), stable across runs and platforms
+pub fn hash_bytes(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |h, &b| (h ^ b as u64).wrapping_mul(0x100000001b3))
+}
+
+// Seed of a pixel for a scene seed
+pub fn pixel_seed(seed: u64, x: u32, y: u32) -> u64 {
+    mix64(seed ^ mix64(((x as u64) << 32) | y as u64))
 }
 
 pub fn clamp(x: f64, min: f64, max: f64) -> f64 {
@@ -35,4 +100,14 @@ pub fn clamp(x: f64, min: f64, max: f64) -> f64 {
 pub fn near_zero(val: f64) -> bool {
     const EPS: f64 = 1.0e-12;
     f64::abs(val) < EPS
-}
\ No newline at end of file
+}
+
+// Power heuristic (beta = 2) weight of a strategy with pdf `f` against another with pdf `g`
+pub fn power_heuristic(f: f64, g: f64) -> f64 {
+    let f2 = f * f;
+    let g2 = g * g;
+    if f2 + g2 == 0.0 {
+        return 0.0;
+    }
+    f2 / (f2 + g2)
+}
@@ -0,0 +1,385 @@
+use std::num::NonZeroU32;
+use std::rc::Rc;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+
+use softbuffer::{Context, Surface};
+use winit::application::ApplicationHandler;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::{ElementState, MouseButton, MouseScrollDelta, WindowEvent};
+use winit::event_loop::{ActiveEventLoop, EventLoop, EventLoopProxy};
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+use winit::window::{Window, WindowId};
+
+use crate::basics::{Color, Point3, Vec3, cross, dot, unit_vec};
+use crate::camera::{CameraParameters, Convergence, Projection, StereoLayout};
+use crate::film::{Film, TileMerger};
+use crate::renderer::{RenderSettings, Renderer};
+use crate::scene::Scene;
+use crate::tiles::{TileOrder, tiles};
+use crate::traits::Filter;
+
+const TILE_SIZE: usize = 32;
+// Camera moves: radians per pixel dragged, distance factor per wheel step, degrees per key press
+const ORBIT_SPEED: f64 = 0.005;
+const DOLLY_FACTOR: f64 = 0.9;
+const FOV_STEP: f64 = 5.0;
+
+// Shared by the window and the render thread
+struct ViewState {
+    camera: CameraParameters,
+    generation: u64, // Increased by each camera move, the render restarts
+    renderer: Option<Arc<Renderer>>, // Render in progress, cancelled by a move
+    image: Vec<u32>, // Latest pass, 0RGB, top row first
+    passes_done: i32,
+    quit: bool,
+}
+
+struct Shared {
+    state: Mutex<ViewState>,
+    changed: Condvar,
+}
+
+impl Shared {
+    // The current render is dropped for a new one from `camera`
+    fn move_camera(&self, camera: CameraParameters) {
+        let mut state = self.state.lock().unwrap();
+        state.camera = camera;
+        state.generation += 1;
+        state.passes_done = 0;
+        if let Some(renderer) = &state.renderer {
+            renderer.cancel();
+        }
+        self.changed.notify_all();
+    }
+}
+
+// Interactive preview: the image refines pass after pass, and restarts when the camera moves
+// Left drag orbits around `at`, right drag (or shift + left drag) pans, the wheel dollies, +/- change the field of view,
+// R goes back to the initial camera, P prints the camera, Escape quits; the camera is printed when the window closes
+pub fn view(settings: RenderSettings, scene: Arc<Scene>, camera: CameraParameters, filter: Arc<dyn Filter>, color_filter: Color, gamma: f64) -> Result<(), String> {
+    let event_loop = EventLoop::new().map_err(|e| format!("Cannot open the viewer: {}", e))?;
+    let shared = Arc::new(Shared {
+        state: Mutex::new(ViewState {
+            camera,
+            generation: 0,
+            renderer: None,
+            image: vec![0; settings.width * settings.height],
+            passes_done: 0,
+            quit: false,
+        }),
+        changed: Condvar::new(),
+    });
+    let proxy = event_loop.create_proxy();
+    let render_thread = {
+        let shared = shared.clone();
+        let settings = settings.clone();
+        thread::spawn(move || render_loop(&shared, settings, scene, filter, color_filter, gamma, proxy))
+    };
+
+    let mut app = App {
+        shared: shared.clone(),
+        initial: camera,
+        size: (settings.width, settings.height),
+        passes: settings.passes(),
+        window: None,
+        surface: None,
+        cursor: None,
+        button: None,
+        modifiers: ModifiersState::empty(),
+    };
+    let result = event_loop.run_app(&mut app).map_err(|e| format!("The viewer failed: {}", e));
+
+    let mut state = shared.state.lock().unwrap();
+    state.quit = true;
+    if let Some(renderer) = &state.renderer {
+        renderer.cancel();
+    }
+    print_camera(&state.camera);
+    drop(state);
+    shared.changed.notify_all();
+    render_thread.join().expect("the render thread panicked");
+    result
+}
+
+// Renders the passes of the current camera, until it moves
+#[allow(clippy::too_many_arguments)]
+fn render_loop(shared: &Shared, settings: RenderSettings, scene: Arc<Scene>, filter: Arc<dyn Filter>, color_filter: Color, gamma: f64, proxy: EventLoopProxy<()>) {
+    let aspect_ratio = settings.width as f64 / settings.height as f64;
+    let tiles = tiles(settings.width, settings.height, TILE_SIZE, TileOrder::Spiral);
+    let mut rendered = None; // Generation of the last finished render
+    loop {
+        let (camera, generation) = {
+            let state = shared.changed.wait_while(shared.state.lock().unwrap(), |s| rendered == Some(s.generation) && !s.quit).unwrap();
+            if state.quit {
+                return;
+            }
+            (state.camera, state.generation)
+        };
+        let renderer = Arc::new(Renderer::new(settings.clone(), scene.clone(), camera.camera(aspect_ratio)));
+        shared.state.lock().unwrap().renderer = Some(renderer.clone());
+        let merger = Mutex::new(TileMerger::new(Film::new(settings.width, settings.height, filter.clone())));
+
+        let mut closed = false;
+        renderer.render_passes(&merger, &tiles, 0..settings.passes(), |pass, film| {
+            let rgb = film.to_rgb8(color_filter, gamma);
+            let mut state = shared.state.lock().unwrap();
+            // The camera moved after the render started
+            if state.generation != generation {
+                return false;
+            }
+            for (pixel, c) in state.image.iter_mut().zip(rgb.chunks_exact(3)) {
+                *pixel = u32::from(c[0]) << 16 | u32::from(c[1]) << 8 | u32::from(c[2]);
+            }
+            state.passes_done = pass + 1;
+            drop(state);
+            // The window is closed
+            closed = proxy.send_event(()).is_err();
+            !closed
+        });
+        if closed {
+            return;
+        }
+        rendered = Some(generation);
+    }
+}
+
+struct App {
+    shared: Arc<Shared>,
+    initial: CameraParameters,
+    size: (usize, usize), // Of the image
+    passes: i32,
+    window: Option<Rc<Window>>,
+    surface: Option<Surface<Rc<Window>, Rc<Window>>>,
+    cursor: Option<PhysicalPosition<f64>>,
+    button: Option<MouseButton>, // Held down
+    modifiers: ModifiersState,
+}
+
+impl App {
+    fn camera(&self) -> CameraParameters {
+        self.shared.state.lock().unwrap().camera
+    }
+
+    fn update_title(&self) {
+        let Some(window) = &self.window else { return };
+        let (camera, passes_done) = {
+            let state = self.shared.state.lock().unwrap();
+            (state.camera, state.passes_done)
+        };
+        let projection = match camera.projection {
+            Projection::Perspective => format!("fov {:.1}", camera.vfov),
+            Projection::Orthographic { height } => format!("height {:.3}", height),
+            Projection::Fisheye { fov } => format!("fisheye {:.1}", fov),
+            Projection::Equirectangular => "equirectangular".to_string(),
+            Projection::Cylindrical { hfov } => format!("cylindrical {:.1}", hfov),
+        };
+        window.set_title(&format!(
+            "rt | pass {}/{} | from {} | at {} | vup {} | {}",
+            passes_done,
+            self.passes,
+            format_vec(camera.from),
+            format_vec(camera.at),
+            format_vec(camera.vup),
+            projection
+        ));
+    }
+
+    // The image stretched to the window
+    fn redraw(&mut self) {
+        let (Some(window), Some(surface)) = (&self.window, &mut self.surface) else { return };
+        let size = window.inner_size();
+        let (Some(width), Some(height)) = (NonZeroU32::new(size.width), NonZeroU32::new(size.height)) else { return };
+        surface.resize(width, height).expect("resizing the window buffer");
+        let mut buffer = surface.buffer_mut().expect("getting the window buffer");
+        let (w, h) = (size.width as usize, size.height as usize);
+        let (image_width, image_height) = self.size;
+        let state = self.shared.state.lock().unwrap();
+        for y in 0..h {
+            let row = y * image_height / h * image_width;
+            for x in 0..w {
+                buffer[y * w + x] = state.image[row + x * image_width / w];
+            }
+        }
+        drop(state);
+        buffer.present().expect("showing the window buffer");
+    }
+
+    fn drag(&mut self, dx: f64, dy: f64, button: MouseButton) {
+        let mut camera = self.camera();
+        let offset = camera.from - camera.at;
+        let w = unit_vec(offset);
+        let u = unit_vec(cross(camera.vup, w));
+        let v = cross(w, u);
+        if button == MouseButton::Left && !self.modifiers.shift_key() {
+            // Around vup, then up and down without going over the poles
+            let up = unit_vec(camera.vup);
+            let offset = rotate_around(offset, up, -dx * ORBIT_SPEED);
+            let angle = f64::acos(dot(unit_vec(offset), up).clamp(-1.0, 1.0));
+            let pitch = (-dy * ORBIT_SPEED).clamp(0.01 - angle, std::f64::consts::PI - 0.01 - angle);
+            let axis = unit_vec(cross(up, offset));
+            camera.from = camera.at + rotate_around(offset, axis, pitch);
+        } else {
+            // The point under the cursor stays under it, on the plane of `at`
+            let view_height = match camera.projection {
+                Projection::Orthographic { height } => height,
+                _ => 2.0 * offset.length() * f64::tan(camera.vfov.to_radians() / 2.0),
+            };
+            let pixel = view_height / self.window_height();
+            let shift = (-dx * u + dy * v) * pixel;
+            camera.from += shift;
+            camera.at += shift;
+        }
+        self.shared.move_camera(camera);
+    }
+
+    fn dolly(&mut self, steps: f64) {
+        let mut camera = self.camera();
+        let offset = (camera.from - camera.at) * DOLLY_FACTOR.powf(steps);
+        if offset.length() > 1e-3 {
+            camera.from = camera.at + offset;
+            self.shared.move_camera(camera);
+        }
+    }
+
+    fn window_height(&self) -> f64 {
+        self.window.as_ref().map_or(self.size.1 as f64, |window| window.inner_size().height.max(1) as f64)
+    }
+}
+
+impl ApplicationHandler for App {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.window.is_some() {
+            return;
+        }
+        let attributes = Window::default_attributes().with_title("rt").with_inner_size(PhysicalSize::new(self.size.0 as u32, self.size.1 as u32));
+        let window = Rc::new(event_loop.create_window(attributes).expect("creating the window"));
+        let context = Context::new(window.clone()).expect("creating the display context");
+        self.surface = Some(Surface::new(&context, window.clone()).expect("creating the window surface"));
+        self.window = Some(window);
+        self.update_title();
+    }
+
+    // A pass is done
+    fn user_event(&mut self, _event_loop: &ActiveEventLoop, _event: ()) {
+        self.update_title();
+        if let Some(window) = &self.window {
+            window.request_redraw();
+        }
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
+        match event {
+            WindowEvent::CloseRequested => event_loop.exit(),
+            WindowEvent::RedrawRequested => self.redraw(),
+            WindowEvent::ModifiersChanged(modifiers) => self.modifiers = modifiers.state(),
+            WindowEvent::MouseInput { state, button, .. } => {
+                self.button = (state == ElementState::Pressed).then_some(button);
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                if let (Some(button), Some(last)) = (self.button, self.cursor) {
+                    self.drag(position.x - last.x, position.y - last.y, button);
+                    self.update_title();
+                }
+                self.cursor = Some(position);
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                let steps = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => y as f64,
+                    MouseScrollDelta::PixelDelta(position) => position.y / 50.0,
+                };
+                self.dolly(steps);
+                self.update_title();
+            }
+            WindowEvent::KeyboardInput { event, .. } if event.state == ElementState::Pressed => {
+                let mut camera = self.camera();
+                match event.logical_key.as_ref() {
+                    Key::Named(NamedKey::Escape) => event_loop.exit(),
+                    Key::Character("+") | Key::Character("=") => {
+                        zoom(&mut camera, -1.0);
+                        self.shared.move_camera(camera);
+                    }
+                    Key::Character("-") => {
+                        zoom(&mut camera, 1.0);
+                        self.shared.move_camera(camera);
+                    }
+                    Key::Character("r") => self.shared.move_camera(self.initial),
+                    Key::Character("p") => print_camera(&camera),
+                    _ => {}
+                }
+                self.update_title();
+            }
+            _ => {}
+        }
+    }
+}
+
+// Field of view one step wider (1.0) or narrower (-1.0); the orthographic height changes by the dolly factor
+fn zoom(camera: &mut CameraParameters, direction: f64) {
+    let step = |fov: f64, max: f64| (fov + direction * FOV_STEP).clamp(FOV_STEP, max);
+    match &mut camera.projection {
+        Projection::Perspective => camera.vfov = step(camera.vfov, 180.0 - FOV_STEP),
+        Projection::Orthographic { height } => *height /= DOLLY_FACTOR.powf(direction),
+        Projection::Fisheye { fov } => *fov = step(*fov, 360.0),
+        Projection::Equirectangular => {}
+        Projection::Cylindrical { hfov } => *hfov = step(*hfov, 360.0),
+    }
+}
+
+// Rodrigues' rotation of v by `angle` radians around the unit vector `axis`
+fn rotate_around(v: Vec3, axis: Vec3, angle: f64) -> Vec3 {
+    let (sin, cos) = angle.sin_cos();
+    v * cos + cross(axis, v) * sin + axis * (dot(axis, v) * (1.0 - cos))
+}
+
+fn format_vec(v: Vec3) -> String {
+    format!("({:.3}, {:.3}, {:.3})", v.x(), v.y(), v.z())
+}
+
+// To be pasted in main.rs, or in a scene description
+fn print_camera(camera: &CameraParameters) {
+    let rust = |v: Point3| format!("{:.3}, {:.3}, {:.3}", v.x(), v.y(), v.z());
+    let json = |v: Point3| format!("[{:.3}, {:.3}, {:.3}]", v.x(), v.y(), v.z());
+    // Builder calls and JSON fields beyond the placement
+    let (mut with, mut fields) = match camera.projection {
+        Projection::Perspective => (String::new(), format!("\"vfov\": {:.1}", camera.vfov)),
+        p => {
+            let projection = match p {
+                Projection::Orthographic { height } => format!("\"type\": \"orthographic\", \"height\": {:.3}", height),
+                Projection::Fisheye { fov } => format!("\"type\": \"fisheye\", \"fov\": {:.1}", fov),
+                Projection::Cylindrical { hfov } => format!("\"type\": \"cylindrical\", \"hfov\": {:.1}", hfov),
+                _ => "\"type\": \"equirectangular\"".to_string(),
+            };
+            (format!(".with_projection(Projection::{:?})", p), format!("\"projection\": {{ {} }}", projection))
+        }
+    };
+    if let Some(stereo) = camera.stereo {
+        with += &format!(
+            ".with_stereo(Stereo {{ mode: Convergence::{:?}, layout: StereoLayout::{:?}, ..Stereo::new({}, {}) }})",
+            stereo.mode, stereo.layout, stereo.interocular, stereo.convergence
+        );
+        let mode = if stereo.mode == Convergence::ToeIn { "toe_in" } else { "off_axis" };
+        let layout = if stereo.layout == StereoLayout::OverUnder { "over_under" } else { "side_by_side" };
+        fields += &format!(
+            ", \"stereo\": {{ \"interocular\": {}, \"convergence\": {}, \"mode\": \"{}\", \"layout\": \"{}\" }}",
+            stereo.interocular, stereo.convergence, mode, layout
+        );
+    }
+    eprintln!(
+        "CameraParameters::new(Point3::new({}), Point3::new({}), Vec3::new({}), {:.1}){}",
+        rust(camera.from),
+        rust(camera.at),
+        rust(camera.vup),
+        camera.vfov,
+        with
+    );
+    eprintln!(
+        "\"camera\": {{ \"from\": {}, \"to\": {}, \"up\": {}, {}, \"aperture\": {}, \"focus_distance\": {} }}",
+        json(camera.from),
+        json(camera.at),
+        json(camera.vup),
+        fields,
+        camera.aperture,
+        camera.focus_dist
+    );
+}
@@ -6,6 +6,10 @@ mod subplane_d;
 mod subplane_s;
 mod cylinder;
 mod cylinder_t;
+mod constant_medium;
+mod atmosphere;
+mod heterogeneous_medium;
+mod transformed;
 
 pub use hittable_list::*;
 pub use sphere::*;
@@ -15,3 +19,31 @@ pub use subplane_d::*;
 pub use subplane_s::*;
 pub use cylinder::*;
 pub use cylinder_t::*;
+pub use constant_medium::*;
+pub use atmosphere::*;
+pub use heterogeneous_medium::*;
+pub use transformed::*;
+
+use crate::basics::Ray;
+use crate::traits::{HitRecord, Hittable, Sampler};
+use crate::utils::INFINITY;
+
+// Ray parameters where the ray is inside the closed boundary of a medium, clamped to [t_min, t_max]
+pub(crate) fn inside_boundary(boundary: &dyn Hittable, ray: &Ray, t_min: f64, t_max: f64, sampler: &mut dyn Sampler) -> Option<(f64, f64)> {
+    // Where the ray enters and leaves the boundary, even behind its origin
+    let mut rec1 = HitRecord::new();
+    let mut rec2 = HitRecord::new();
+    if !boundary.hit(ray, -INFINITY, INFINITY, &mut rec1, sampler) {
+        return None;
+    }
+    if !boundary.hit(ray, rec1.t + 0.0001, INFINITY, &mut rec2, sampler) {
+        return None;
+    }
+
+    let t_enter = f64::max(rec1.t, t_min);
+    let t_exit = f64::min(rec2.t, t_max);
+    if t_enter >= t_exit {
+        return None;
+    }
+    Some((t_enter, t_exit))
+}
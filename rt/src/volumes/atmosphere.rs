@@ -0,0 +1,56 @@
+use std::sync::Arc;
+use crate::basics::{Color, Ray};
+use crate::materials::Isotropic;
+use crate::traits::{Material, Sampler};
+
+// Homogeneous fog filling the whole scene, with absorption and scattering coefficients (per unit of distance)
+// Rays leaving the scene cross `extent` units of fog (INFINITY: nothing comes from the sky)
+#[derive(Debug)]
+pub struct Atmosphere {
+    absorption: f64,
+    scattering: f64,
+    extent: f64,
+    phase_function: Arc<dyn Material>,
+}
+
+impl Atmosphere {
+    pub fn new(absorption: f64, scattering: f64, extent: f64) -> Atmosphere {
+        let extinction = absorption + scattering;
+        let albedo = if extinction > 0.0 { scattering / extinction } else { 0.0 };
+        Atmosphere {
+            absorption,
+            scattering,
+            extent,
+            phase_function: Arc::new(Isotropic::new(Color::new(albedo, albedo, albedo))),
+        }
+    }
+
+    fn extinction(&self) -> f64 {
+        self.absorption + self.scattering
+    }
+
+    // Ray parameter of a collision with the fog before t_max, if any
+    // Absorption is accounted for by the albedo of the phase function
+    pub fn sample_event(&self, r: &Ray, t_max: f64, sampler: &mut dyn Sampler) -> Option<f64> {
+        if self.extinction() <= 0.0 {
+            return None;
+        }
+        let ray_length = r.direction().length();
+        let max_distance = if t_max.is_finite() { t_max * ray_length } else { self.extent };
+        let distance = -f64::ln(1.0 - sampler.get_1d()) / self.extinction();
+        if distance >= max_distance {
+            return None;
+        }
+        Some(distance / ray_length)
+    }
+
+    // Fraction of light crossing `distance` units of fog; an infinite distance (leaving the scene) crosses `extent`
+    pub fn transmittance(&self, distance: f64) -> f64 {
+        let distance = if distance.is_finite() { distance } else { self.extent };
+        f64::exp(-self.extinction() * distance)
+    }
+
+    pub fn phase_function(&self) -> Arc<dyn Material> {
+        self.phase_function.clone()
+    }
+}
@@ -0,0 +1,67 @@
+use std::sync::Arc;
+use crate::traits::{HitRecord, Hittable, Material, Sampler};
+use crate::basics::{Color, Ray, Vec3};
+use crate::materials::Isotropic;
+use crate::volumes::inside_boundary;
+
+// Fog or smoke filling a closed boundary (sphere, cube, cylinder)
+// A ray crossing it scatters at a random distance, following Beer-Lambert's law
+#[derive(Debug)]
+pub struct ConstantMedium {
+    boundary: Box<dyn Hittable>,
+    neg_inv_density: f64,
+    phase_function: Arc<dyn Material>,
+}
+
+impl ConstantMedium {
+    pub fn new(boundary: Box<dyn Hittable>, density: f64, albedo: Color) -> ConstantMedium {
+        assert!(density > 0.0 && density.is_finite(), "the density of a medium must be positive and finite");
+        ConstantMedium {
+            boundary,
+            neg_inv_density: -1.0 / density,
+            phase_function: Arc::new(Isotropic::new(albedo)),
+        }
+    }
+}
+
+impl Hittable for ConstantMedium {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord, sampler: &mut dyn Sampler) -> bool {
+        let Some((t_enter, t_exit)) = inside_boundary(self.boundary.as_ref(), ray, t_min, t_max, sampler) else {
+            return false;
+        };
+
+        let ray_length = ray.world_length();
+        let distance_inside = (t_exit - t_enter) * ray_length;
+        let hit_distance = self.neg_inv_density * f64::ln(1.0 - sampler.get_1d());
+        if hit_distance > distance_inside {
+            return false;
+        }
+
+        rec.t = t_enter + hit_distance / ray_length;
+        rec.p = ray.at(rec.t);
+        // No surface: arbitrary normal, the phase function does not use it
+        rec.normal = Vec3::new(1.0, 0.0, 0.0);
+        rec.front = true;
+        rec.mat = Some(self.phase_function.clone());
+        true
+    }
+
+    fn shadow_hit(&self, _ray: &Ray, _t_min: f64, _t_max: f64, _rec: &mut HitRecord, _sampler: &mut dyn Sampler) -> bool {
+        false
+    }
+
+    // Beer-Lambert's law
+    fn transmittance(&self, ray: &Ray, t_min: f64, t_max: f64, sampler: &mut dyn Sampler) -> f64 {
+        match inside_boundary(self.boundary.as_ref(), ray, t_min, t_max, sampler) {
+            Some((t_enter, t_exit)) => {
+                let distance_inside = (t_exit - t_enter) * ray.world_length();
+                f64::exp(distance_inside / self.neg_inv_density)
+            }
+            None => 1.0,
+        }
+    }
+
+    fn materials(&self) -> Vec<Arc<dyn Material>> {
+        vec![self.phase_function.clone()]
+    }
+}
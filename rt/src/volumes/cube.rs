@@ -1,9 +1,11 @@
 use std::f64::consts::PI;
 use std::sync::Arc;
 
-use crate::traits::{HitRecord, Hittable, Material};
-use crate::basics::{Point3, Ray, Vec3, rotate, dot};
+use crate::traits::{HitRecord, Hittable, Material, Sampler};
+use crate::basics::{Point3, Ray, Vec3, rotate, rotate_inv, dot};
+use crate::utils::INFINITY;
 
+#[derive(Debug)]
 pub struct Cube {
     pub center: Point3,
     pub size: f64,
@@ -55,10 +57,26 @@ impl Cube {
 
         normals_vec[result_index]
     }
+
+    // Normals (in the cube's own space) of the faces seen from a point
+    fn visible_faces(&self, origin: Point3) -> Vec<Vec3> {
+        let rel = rotate(origin - self.center, self.rotation);
+        [
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(-1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, -1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(0.0, 0.0, -1.0),
+        ]
+        .into_iter()
+        .filter(|&n| dot(rel, n) > self.size)
+        .collect()
+    }
 }
 
 impl Hittable for Cube {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord, _sampler: &mut dyn Sampler) -> bool {
         // Rotate ray origin around cube center by cube's rotation
         let oc = ray.origin() - self.center;
 
@@ -86,13 +104,65 @@ impl Hittable for Cube {
 
         // eprintln!("orig : x: {}, y: {}, z: {}", ray.origin().x(), ray.origin().y(), ray.origin().z());
         // eprintln!("clone : x: {}, y: {}, z: {}", clone_ray.origin().x(), clone_ray.origin().y(), clone_ray.origin().z());
-        if tc <= tf && tf > 0. && tc >= t_min && tc <= t_max {
-            rec.t = tc;
+        if tc > tf {
+            return false;
+        }
+        // Entry point, or exit point for rays starting inside the cube
+        let t = if tc >= t_min { tc } else { tf };
+        if t >= t_min && t <= t_max {
+            rec.t = t;
             rec.p = ray.at(rec.t);
-            rec.set_face_normal(ray, rotate(self.outward_normal(clone_ray.at(tc)), -self.rotation));
+            let local_normal = self.outward_normal(clone_ray.at(t));
+            rec.set_face_normal(ray, rotate_inv(local_normal, self.rotation));
+
+            // Coordinates on the face, along the same axes as random()
+            let rel = clone_ray.at(t) - self.center;
+            let a = Vec3::new(local_normal.z(), local_normal.x(), local_normal.y());
+            let b = Vec3::new(local_normal.y(), local_normal.z(), local_normal.x());
+            rec.u = 0.5 + dot(rel, a) / (2.0 * self.size);
+            rec.v = 0.5 + dot(rel, b) / (2.0 * self.size);
             rec.mat = Some(self.mat.clone());
             return true;
         }
         false
     }
+
+    // Uniform sampling of the area of the visible faces, converted to solid angle
+    fn pdf_value(&self, origin: Point3, direction: Vec3, sampler: &mut dyn Sampler) -> f64 {
+        let mut rec = HitRecord::new();
+        if !self.hit(&Ray::new(origin, direction), 0.001, INFINITY, &mut rec, sampler) {
+            return 0.0;
+        }
+
+        let visible = self.visible_faces(origin).len();
+        if visible == 0 {
+            return 0.0;
+        }
+        let area = visible as f64 * 4.0 * self.size * self.size;
+        let dist_squared = rec.t * rec.t * direction.length_squared();
+        let cosine = f64::abs(dot(direction, rec.normal) / direction.length());
+        dist_squared / (cosine * area)
+    }
+
+    fn random(&self, origin: Point3, sampler: &mut dyn Sampler) -> Vec3 {
+        let faces = self.visible_faces(origin);
+        if faces.is_empty() {
+            return self.center - origin;
+        }
+        let n = faces[sampler.get_index(faces.len())];
+
+        // Two axes spanning the face
+        let a = Vec3::new(n.z(), n.x(), n.y());
+        let b = Vec3::new(n.y(), n.z(), n.x());
+        let local = self.size * n + sampler.get_range(-self.size, self.size) * a + sampler.get_range(-self.size, self.size) * b;
+        self.center + rotate_inv(local, self.rotation) - origin
+    }
+
+    fn materials(&self) -> Vec<Arc<dyn Material>> {
+        vec![self.mat.clone()]
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.mat.is_emissive()
+    }
 }
\ No newline at end of file
@@ -1,9 +1,10 @@
 use std::sync::Arc;
 use crate::basics::{Point3, Vec3, dot, unit_vec};
-use crate::traits::{Hittable, Material};
+use crate::traits::{Hittable, Material, Sampler};
 use crate::volumes::{CylinderTube, Disk, HittableList};
 
 // Composite Volume
+#[derive(Debug)]
 pub struct Cylinder {
     parts: HittableList
 }
@@ -28,7 +29,24 @@ impl Cylinder {
 }
 
 impl Hittable for Cylinder {
-    fn hit(&self, ray: &crate::basics::Ray, t_min: f64, t_max: f64, rec: &mut crate::traits::HitRecord) -> bool {
-        self.parts.hit(ray, t_min, t_max, rec)
+    fn hit(&self, ray: &crate::basics::Ray, t_min: f64, t_max: f64, rec: &mut crate::traits::HitRecord, sampler: &mut dyn Sampler) -> bool {
+        self.parts.hit(ray, t_min, t_max, rec, sampler)
+    }
+
+    // Tube and disks are sampled as an uniform mixture
+    fn pdf_value(&self, origin: Point3, direction: Vec3, sampler: &mut dyn Sampler) -> f64 {
+        self.parts.pdf_value(origin, direction, sampler)
+    }
+
+    fn random(&self, origin: Point3, sampler: &mut dyn Sampler) -> Vec3 {
+        self.parts.random(origin, sampler)
+    }
+
+    fn materials(&self) -> Vec<Arc<dyn Material>> {
+        self.parts.materials()
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.parts.is_emissive()
     }
 }
\ No newline at end of file
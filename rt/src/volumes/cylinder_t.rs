@@ -1,8 +1,10 @@
 use std::sync::Arc;
 
-use crate::traits::{HitRecord, Hittable, Material};
-use crate::basics::{Point3, Ray, Vec3, dot, unit_vec};
+use crate::traits::{HitRecord, Hittable, Material, Sampler};
+use crate::basics::{Point3, Ray, Vec3, cross, dot, unit_vec};
+use crate::utils::{INFINITY, PI};
 
+#[derive(Debug)]
 pub struct CylinderTube {
     pub base: Point3,
     pub orientation: Vec3,
@@ -23,8 +25,22 @@ impl CylinderTube {
     }
 }
 
+impl CylinderTube {
+    // Two axes perpendicular to the orientation
+    fn base_uv(&self) -> (Vec3, Vec3) {
+        let temp = if self.orientation.x().abs() > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let u = unit_vec(cross(self.orientation, temp));
+        let v = cross(self.orientation, u);
+        (u, v)
+    }
+}
+
 impl Hittable for CylinderTube {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord, _sampler: &mut dyn Sampler) -> bool {
         let x = ray.origin() - self.base;
         let a = ray.direction().length_squared() - (dot(ray.direction(), self.orientation)).powf(2.0);
         let half_b = dot(ray.direction(), x) - (dot(ray.direction(), self.orientation) * dot(x, self.orientation));
@@ -56,7 +72,45 @@ impl Hittable for CylinderTube {
         rec.p = ray.at(rec.t);
         let outward_normal = unit_vec(rec.p - self.base - self.orientation * m);
         rec.set_face_normal(ray, outward_normal);
+        let (u, v) = self.base_uv();
+        let phi = f64::atan2(dot(outward_normal, v), dot(outward_normal, u));
+        rec.u = (phi + PI) / (2.0 * PI);
+        rec.v = m / self.length;
         rec.mat = Some(self.mat.clone());
         true
     }
+
+    // Uniform sampling of the area, converted to solid angle
+    // A direction can cross the tube twice: both points could have been sampled
+    fn pdf_value(&self, origin: Point3, direction: Vec3, sampler: &mut dyn Sampler) -> f64 {
+        let area = 2.0 * PI * self.radius * self.length;
+        let ray = Ray::new(origin, direction);
+        let mut pdf = 0.0;
+        let mut t_min = 0.001;
+        let mut rec = HitRecord::new();
+        while self.hit(&ray, t_min, INFINITY, &mut rec, sampler) {
+            let dist_squared = rec.t * rec.t * direction.length_squared();
+            let cosine = f64::abs(dot(direction, rec.normal) / direction.length());
+            pdf += dist_squared / (cosine * area);
+            t_min = rec.t;
+        }
+        pdf
+    }
+
+    fn random(&self, origin: Point3, sampler: &mut dyn Sampler) -> Vec3 {
+        let (u, v) = self.base_uv();
+
+        let h = self.length * sampler.get_1d();
+        let phi = 2.0 * PI * sampler.get_1d();
+        let p = self.base + h * self.orientation + self.radius * (f64::cos(phi) * u + f64::sin(phi) * v);
+        p - origin
+    }
+
+    fn materials(&self) -> Vec<Arc<dyn Material>> {
+        vec![self.mat.clone()]
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.mat.is_emissive()
+    }
 }
\ No newline at end of file
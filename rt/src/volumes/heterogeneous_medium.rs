@@ -0,0 +1,107 @@
+use std::sync::Arc;
+use crate::traits::{DensityField, HitRecord, Hittable, Material, Texture, Sampler};
+use crate::basics::{Color, Ray, Vec3};
+use crate::materials::HenyeyGreenstein;
+use crate::volumes::inside_boundary;
+
+// Clouds, smoke plumes or fire inside a closed boundary, with a density varying in space
+// Collisions are found by delta tracking against the majorant, which is unbiased for any density
+#[derive(Debug)]
+pub struct HeterogeneousMedium {
+    boundary: Box<dyn Hittable>,
+    density: Arc<dyn DensityField>,
+    scale: f64, // Extinction coefficient for a density of 1.0
+    phase_function: Arc<dyn Material>,
+}
+
+impl HeterogeneousMedium {
+    // `g` is the anisotropy of the Henyey-Greenstein phase function
+    pub fn new(boundary: Box<dyn Hittable>, density: Arc<dyn DensityField>, scale: f64, albedo: Color, g: f64) -> HeterogeneousMedium {
+        HeterogeneousMedium {
+            boundary,
+            density,
+            scale,
+            phase_function: Arc::new(HenyeyGreenstein::new(albedo, g)),
+        }
+    }
+
+    // Fire: the absorbed part of the collisions emits the texture's color (evaluated at the hit point)
+    pub fn emissive(boundary: Box<dyn Hittable>, density: Arc<dyn DensityField>, scale: f64, albedo: Color, g: f64, emit: Arc<dyn Texture>) -> HeterogeneousMedium {
+        HeterogeneousMedium {
+            boundary,
+            density,
+            scale,
+            phase_function: Arc::new(HenyeyGreenstein::new(albedo, g).with_emission(emit)),
+        }
+    }
+
+    fn majorant(&self) -> f64 {
+        self.scale * self.density.max_density()
+    }
+}
+
+impl Hittable for HeterogeneousMedium {
+    // Delta tracking: tentative collisions with the majorant, real with probability density / majorant
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord, sampler: &mut dyn Sampler) -> bool {
+        let majorant = self.majorant();
+        if majorant <= 0.0 {
+            return false;
+        }
+        let Some((t_enter, t_exit)) = inside_boundary(self.boundary.as_ref(), ray, t_min, t_max, sampler) else {
+            return false;
+        };
+
+        let ray_length = ray.world_length();
+        let mut t = t_enter;
+        loop {
+            t -= f64::ln(1.0 - sampler.get_1d()) / (majorant * ray_length);
+            if t >= t_exit {
+                return false;
+            }
+            if sampler.get_1d() * majorant < self.scale * self.density.density(ray.at(t)) {
+                break;
+            }
+        }
+
+        rec.t = t;
+        rec.p = ray.at(t);
+        // No surface: arbitrary normal, the phase function does not use it
+        rec.normal = Vec3::new(1.0, 0.0, 0.0);
+        rec.front = true;
+        rec.mat = Some(self.phase_function.clone());
+        true
+    }
+
+    fn shadow_hit(&self, _ray: &Ray, _t_min: f64, _t_max: f64, _rec: &mut HitRecord, _sampler: &mut dyn Sampler) -> bool {
+        false
+    }
+
+    // Ratio tracking: every tentative collision keeps the probability of being a null one
+    fn transmittance(&self, ray: &Ray, t_min: f64, t_max: f64, sampler: &mut dyn Sampler) -> f64 {
+        let majorant = self.majorant();
+        if majorant <= 0.0 {
+            return 1.0;
+        }
+        let Some((t_enter, t_exit)) = inside_boundary(self.boundary.as_ref(), ray, t_min, t_max, sampler) else {
+            return 1.0;
+        };
+
+        let ray_length = ray.world_length();
+        let mut transmittance = 1.0;
+        let mut t = t_enter;
+        loop {
+            t -= f64::ln(1.0 - sampler.get_1d()) / (majorant * ray_length);
+            if t >= t_exit {
+                return transmittance;
+            }
+            transmittance *= 1.0 - self.scale * self.density.density(ray.at(t)) / majorant;
+            if transmittance <= 0.0 {
+                return 0.0;
+            }
+        }
+    }
+
+    fn materials(&self) -> Vec<Arc<dyn Material>> {
+        vec![self.phase_function.clone()]
+    }
+}
@@ -1,9 +1,11 @@
-use crate::traits::{HitRecord, Hittable};
-use crate::basics::Ray;
+use std::sync::Arc;
+use crate::traits::{HitRecord, Hittable, Material, Sampler};
+use crate::basics::{Point3, Ray, Vec3};
 
-#[derive(Default)]
+// Objects are shared so that the emissive ones can also be listed as lights
+#[derive(Default, Debug)]
 pub struct HittableList {
-    objects: Vec<Box<dyn Hittable>>,
+    objects: Vec<Arc<dyn Hittable>>,
 }
 
 impl HittableList {
@@ -12,24 +14,114 @@ impl HittableList {
     }
 
     pub fn add(&mut self, object: Box<dyn Hittable>) {
-        self.objects.push(object);
+        self.objects.push(Arc::from(object));
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    // Object of id `id` (index + 1)
+    pub fn get(&self, id: u32) -> Option<Arc<dyn Hittable>> {
+        self.objects.get((id as usize).checked_sub(1)?).cloned()
+    }
+
+    // Replaces the object of id `id`, which must exist
+    pub fn replace(&mut self, id: u32, object: Arc<dyn Hittable>) {
+        self.objects[id as usize - 1] = object;
+    }
+
+    // Ids of the emissive objects, in the order of lights()
+    pub fn emissive_ids(&self) -> Vec<u32> {
+        (0..self.objects.len()).filter(|&k| self.objects[k].is_emissive()).map(|k| k as u32 + 1).collect()
+    }
+
+    // Every emissive object of the list, to be sampled directly
+    pub fn lights(&self) -> HittableList {
+        HittableList {
+            objects: self
+                .objects
+                .iter()
+                .filter(|object| object.is_emissive())
+                .cloned()
+                .collect(),
+        }
     }
 }
 
 impl Hittable for HittableList {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord, sampler: &mut dyn Sampler) -> bool {
         let mut temp_rec = HitRecord::new();
         let mut hit_anything = false;
         let mut closest_so_far = t_max;
 
-        for object in &self.objects {
-            if object.hit(ray, t_min, closest_so_far, &mut temp_rec) {
+        // The ids of the objects are their place in the list: the outermost list, the world, sets them last
+        for (index, object) in self.objects.iter().enumerate() {
+            if object.hit(ray, t_min, closest_so_far, &mut temp_rec, sampler) {
                 hit_anything = true;
                 closest_so_far = temp_rec.t;
                 *rec = temp_rec.clone();
+                rec.object_id = index as u32 + 1;
             }
         }
 
         hit_anything
     }
-}
\ No newline at end of file
+
+    fn materials(&self) -> Vec<Arc<dyn Material>> {
+        self.objects.iter().flat_map(|object| object.materials()).collect()
+    }
+
+    // Uniform mixture of the objects' pdfs
+    fn pdf_value(&self, origin: Point3, direction: Vec3, sampler: &mut dyn Sampler) -> f64 {
+        if self.objects.is_empty() {
+            return 0.0;
+        }
+        let weight = 1.0 / self.objects.len() as f64;
+        self.objects
+            .iter()
+            .map(|object| weight * object.pdf_value(origin, direction, sampler))
+            .sum()
+    }
+
+    fn random(&self, origin: Point3, sampler: &mut dyn Sampler) -> Vec3 {
+        if self.is_empty() {
+            return Vec3::new(1.0, 0.0, 0.0);
+        }
+        let n = self.objects.len();
+        let index = sampler.get_index(n);
+        self.objects[index].random(origin, sampler)
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.objects.iter().any(|object| object.is_emissive())
+    }
+
+    fn shadow_hit(&self, ray: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord, sampler: &mut dyn Sampler) -> bool {
+        let mut temp_rec = HitRecord::new();
+        let mut hit_anything = false;
+        let mut closest_so_far = t_max;
+
+        for (index, object) in self.objects.iter().enumerate() {
+            if object.shadow_hit(ray, t_min, closest_so_far, &mut temp_rec, sampler) {
+                hit_anything = true;
+                closest_so_far = temp_rec.t;
+                *rec = temp_rec.clone();
+                rec.object_id = index as u32 + 1;
+            }
+        }
+
+        hit_anything
+    }
+
+    fn transmittance(&self, ray: &Ray, t_min: f64, t_max: f64, sampler: &mut dyn Sampler) -> f64 {
+        self.objects
+            .iter()
+            .map(|object| object.transmittance(ray, t_min, t_max, sampler))
+            .product()
+    }
+}
@@ -1,12 +1,15 @@
 use std::sync::Arc;
-use crate::traits::{HitRecord, Hittable, Material};
-use crate::basics::{dot, Ray, Vec3, unit_vec};
+use crate::traits::{HitRecord, Hittable, Material, Sampler};
+use crate::basics::{cross, dot, Ray, Vec3, unit_vec};
 use crate::utils::near_zero;
 
 
+#[derive(Debug)]
 pub struct Plane {
     norm: Vec3,
     dist: f64,
+    base_u: Vec3,
+    base_v: Vec3,
     mat: Arc<dyn Material>,
 }
 
@@ -15,16 +18,28 @@ impl Plane {
     pub fn new(norm: Vec3, dist: f64, mat: Arc<dyn Material>) -> Plane {
         let unit_norm = unit_vec(norm);
         let unit_dist = dist / norm.length();
+
+        // Creating a base u, v on the plane
+        let temp = if unit_norm.x().abs() > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let base_u = unit_vec(cross(unit_norm, temp));
+        let base_v = cross(unit_norm, base_u);
+
         Plane {
             norm: unit_norm,
             dist: unit_dist,
+            base_u,
+            base_v,
             mat,
         }
     }
 }
 
 impl Hittable for Plane {
-    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord, _sampler: &mut dyn Sampler) -> bool {
         let num = dot(self.norm, r.origin()) + self.dist;
 
         let denom = dot(self.norm,r.direction());
@@ -40,7 +55,14 @@ impl Hittable for Plane {
         rec.t = root;
         rec.p = r.at(rec.t);
         rec.set_face_normal(r, self.norm);
+        // Unbounded: coordinates in world units, from the origin's projection
+        rec.u = dot(rec.p, self.base_u);
+        rec.v = dot(rec.p, self.base_v);
         rec.mat = Some(self.mat.clone());
         true
     }
-}
\ No newline at end of file
+
+    fn materials(&self) -> Vec<Arc<dyn Material>> {
+        vec![self.mat.clone()]
+    }
+}
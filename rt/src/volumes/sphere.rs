@@ -1,7 +1,9 @@
 use std::sync::Arc;
-use crate::traits::{HitRecord, Hittable, Material};
-use crate::basics::{dot, Point3, Ray};
+use crate::traits::{HitRecord, Hittable, Material, Sampler};
+use crate::basics::{dot, sample_uniform_cone, sample_uniform_sphere, uniform_cone_pdf, uniform_sphere_pdf, Point3, Ray, Vec3};
+use crate::utils::{INFINITY, PI};
 
+#[derive(Debug)]
 pub struct Sphere {
     center: Point3,
     radius: f64,
@@ -19,8 +21,15 @@ impl Sphere {
     }
 }
 
+// Longitude and latitude of a point of the unit sphere, in [0, 1]
+fn sphere_uv(p: Point3) -> (f64, f64) {
+    let theta = f64::acos(-p.y());
+    let phi = f64::atan2(-p.z(), p.x()) + PI;
+    (phi / (2.0 * PI), theta / PI)
+}
+
 impl Hittable for Sphere {
-    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord, _sampler: &mut dyn Sampler) -> bool {
         let oc = r.origin() - self.center;
         let a = r.direction().length_squared();
         let half_b = dot(oc, r.direction());
@@ -45,7 +54,44 @@ impl Hittable for Sphere {
         rec.p = r.at(rec.t);
         let outward_normal = (rec.p - self.center) / self.radius;
         rec.set_face_normal(r, outward_normal);
+        (rec.u, rec.v) = sphere_uv(outward_normal);
         rec.mat = Some(self.mat.clone());
         true
     }
+
+    // Uniform sampling of the cone subtended by the sphere
+    fn pdf_value(&self, origin: Point3, direction: Vec3, sampler: &mut dyn Sampler) -> f64 {
+        let mut rec = HitRecord::new();
+        if !self.hit(&Ray::new(origin, direction), 0.001, INFINITY, &mut rec, sampler) {
+            return 0.0;
+        }
+
+        let dist_squared = (self.center - origin).length_squared();
+        if dist_squared <= self.radius * self.radius {
+            // From inside, every direction reaches the surface
+            return uniform_sphere_pdf();
+        }
+        let cos_theta_max = f64::sqrt(1.0 - self.radius * self.radius / dist_squared);
+        uniform_cone_pdf(cos_theta_max)
+    }
+
+    fn random(&self, origin: Point3, sampler: &mut dyn Sampler) -> Vec3 {
+        let direction = self.center - origin;
+        let dist_squared = direction.length_squared();
+        if dist_squared <= self.radius * self.radius {
+            return sample_uniform_sphere(sampler.get_2d()).0;
+        }
+
+        // Direction inside the cone, around the direction of the center
+        let cos_theta_max = f64::sqrt(1.0 - self.radius * self.radius / dist_squared);
+        sample_uniform_cone(direction, cos_theta_max, sampler.get_2d()).0
+    }
+
+    fn materials(&self) -> Vec<Arc<dyn Material>> {
+        vec![self.mat.clone()]
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.mat.is_emissive()
+    }
 }
\ No newline at end of file
@@ -1,13 +1,16 @@
 use std::sync::Arc;
-use crate::traits::{HitRecord, Hittable, Material};
-use crate::basics::{Point3, Ray, Vec3, dot, unit_vec};
-use crate::utils::near_zero;
+use crate::traits::{HitRecord, Hittable, Material, Sampler};
+use crate::basics::{Point3, Ray, Vec3, cross, dot, sample_concentric_disk, unit_vec};
+use crate::utils::{INFINITY, PI, near_zero};
 
+#[derive(Debug)]
 pub struct Disk {
     norm: Vec3,
     dist: f64,
     center: Point3,          // Proj of the point given
     radius: f64,
+	base_u: Vec3,
+	base_v: Vec3,
     mat: Arc<dyn Material>,
 }
 
@@ -19,18 +22,29 @@ impl Disk {
 		let signed_dist_center = dot(unit_norm, point) + unit_dist;
         let center = point - signed_dist_center * unit_norm;
 
+		// Creating a base u, v on the plane
+		let temp = if unit_norm.x().abs() > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let base_u = unit_vec(cross(unit_norm, temp));
+        let base_v = cross(unit_norm, base_u);
+
         Disk {
             norm: unit_norm,
             dist: unit_dist,
             center,
             radius,
+			base_u,
+			base_v,
             mat,
         }
     }
 }
 
 impl Hittable for Disk {
-    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord, _sampler: &mut dyn Sampler) -> bool {
         let num = dot(self.norm, r.origin()) + self.dist;
 
         let denom = dot(self.norm, r.direction());
@@ -52,7 +66,36 @@ impl Hittable for Disk {
         rec.t = root;
         rec.p = p;
         rec.set_face_normal(r, self.norm);
+        rec.u = 0.5 + dot(v, self.base_u) / (2.0 * self.radius);
+        rec.v = 0.5 + dot(v, self.base_v) / (2.0 * self.radius);
         rec.mat = Some(self.mat.clone());
         true
     }
+
+    // Uniform sampling of the area, converted to solid angle
+    fn pdf_value(&self, origin: Point3, direction: Vec3, sampler: &mut dyn Sampler) -> f64 {
+        let mut rec = HitRecord::new();
+        if !self.hit(&Ray::new(origin, direction), 0.001, INFINITY, &mut rec, sampler) {
+            return 0.0;
+        }
+
+        let area = PI * self.radius * self.radius;
+        let dist_squared = rec.t * rec.t * direction.length_squared();
+        let cosine = f64::abs(dot(direction, rec.normal) / direction.length());
+        dist_squared / (cosine * area)
+    }
+
+    fn random(&self, origin: Point3, sampler: &mut dyn Sampler) -> Vec3 {
+        let ((x, y), _) = sample_concentric_disk(sampler.get_2d());
+        let p = self.center + self.radius * (x * self.base_u + y * self.base_v);
+        p - origin
+    }
+
+    fn materials(&self) -> Vec<Arc<dyn Material>> {
+        vec![self.mat.clone()]
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.mat.is_emissive()
+    }
 }
\ No newline at end of file
@@ -1,8 +1,9 @@
 use std::sync::Arc;
-use crate::traits::{HitRecord, Hittable, Material};
+use crate::traits::{HitRecord, Hittable, Material, Sampler};
 use crate::basics::{Point3, Ray, Vec3, cross, dot, unit_vec};
-use crate::utils::{degrees_to_radians, near_zero};
+use crate::utils::{INFINITY, degrees_to_radians, near_zero};
 
+#[derive(Debug)]
 pub struct Square {
     norm: Vec3,
     dist: f64,
@@ -53,7 +54,7 @@ impl Square {
 }
 
 impl Hittable for Square {
-    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord, _sampler: &mut dyn Sampler) -> bool {
         let num = dot(self.norm, r.origin()) + self.dist;
 
         let denom = dot(self.norm, r.direction());
@@ -79,7 +80,36 @@ impl Hittable for Square {
         rec.t = root;
         rec.p = p;
         rec.set_face_normal(r, self.norm);
+        rec.u = 0.5 + x / (2.0 * self.size);
+        rec.v = 0.5 + y / (2.0 * self.size);
         rec.mat = Some(self.mat.clone());
         true
     }
+
+    // Uniform sampling of the area, converted to solid angle
+    fn pdf_value(&self, origin: Point3, direction: Vec3, sampler: &mut dyn Sampler) -> f64 {
+        let mut rec = HitRecord::new();
+        if !self.hit(&Ray::new(origin, direction), 0.001, INFINITY, &mut rec, sampler) {
+            return 0.0;
+        }
+
+        let area = 4.0 * self.size * self.size;
+        let dist_squared = rec.t * rec.t * direction.length_squared();
+        let cosine = f64::abs(dot(direction, rec.normal) / direction.length());
+        dist_squared / (cosine * area)
+    }
+
+    fn random(&self, origin: Point3, sampler: &mut dyn Sampler) -> Vec3 {
+        let x = sampler.get_range(-self.size, self.size);
+        let y = sampler.get_range(-self.size, self.size);
+        self.proj + x * self.base_u + y * self.base_v - origin
+    }
+
+    fn materials(&self) -> Vec<Arc<dyn Material>> {
+        vec![self.mat.clone()]
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.mat.is_emissive()
+    }
 }
\ No newline at end of file
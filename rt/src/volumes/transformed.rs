@@ -0,0 +1,106 @@
+use std::sync::Arc;
+use crate::traits::{HitRecord, Hittable, Material, Sampler};
+use crate::basics::{Point3, Ray, Vec3, rotate, rotate_inv};
+use crate::utils::PI;
+
+// Placement of an object: scaled and rotated around `pivot`, then moved by `translation`
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Transform {
+    pub pivot: Point3,
+    pub translation: Vec3,
+    pub rotation: Vec3, // Degrees around x, y and z, as Cube
+    pub scale: f64,
+}
+
+impl Transform {
+    pub fn new(pivot: Point3) -> Transform {
+        Transform { pivot, translation: Vec3::new(0.0, 0.0, 0.0), rotation: Vec3::new(0.0, 0.0, 0.0), scale: 1.0 }
+    }
+
+    // Rays are divided by the scale: it must not be 0
+    pub fn check(&self) -> Result<(), String> {
+        let values = [self.pivot, self.translation, self.rotation].into_iter().flat_map(|v| [v.x(), v.y(), v.z()]);
+        if !values.chain([self.scale]).all(f64::is_finite) || self.scale == 0.0 {
+            return Err(format!("the transform {:?} needs finite values and a scale other than 0", self));
+        }
+        Ok(())
+    }
+}
+
+// An object with a transform: rays are taken to the object's own space
+// Rotations and uniform scales keep the angles, so the solid angle pdfs of light sampling are those of the object
+#[derive(Debug)]
+pub struct Transformed {
+    object: Arc<dyn Hittable>,
+    pivot: Point3,
+    translation: Vec3,
+    rotation: Vec3, // Radians
+    scale: f64,
+}
+
+impl Transformed {
+    pub fn new(object: Arc<dyn Hittable>, transform: Transform) -> Transformed {
+        transform.check().expect("placing the object");
+        Transformed {
+            object,
+            pivot: transform.pivot,
+            translation: transform.translation,
+            rotation: transform.rotation * (PI / 180.0),
+            scale: transform.scale,
+        }
+    }
+
+    // The same parameter t reaches the same point in both spaces
+    fn to_object(&self, r: &Ray) -> Ray {
+        let origin = self.pivot + rotate_inv(r.origin() - self.translation - self.pivot, self.rotation) / self.scale;
+        let mut local = Ray::new(origin, rotate_inv(r.direction(), self.rotation) / self.scale);
+        local.set_wavelength(r.wavelength());
+        local.set_world_length(Some(r.world_length()));
+        local
+    }
+
+    fn to_world(&self, r: &Ray, rec: &mut HitRecord) {
+        rec.p = r.at(rec.t);
+        rec.normal = rotate(rec.normal, self.rotation);
+    }
+}
+
+impl Hittable for Transformed {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord, sampler: &mut dyn Sampler) -> bool {
+        if !self.object.hit(&self.to_object(r), t_min, t_max, rec, sampler) {
+            return false;
+        }
+        self.to_world(r, rec);
+        true
+    }
+
+    fn shadow_hit(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord, sampler: &mut dyn Sampler) -> bool {
+        if !self.object.shadow_hit(&self.to_object(r), t_min, t_max, rec, sampler) {
+            return false;
+        }
+        self.to_world(r, rec);
+        true
+    }
+
+    fn transmittance(&self, r: &Ray, t_min: f64, t_max: f64, sampler: &mut dyn Sampler) -> f64 {
+        self.object.transmittance(&self.to_object(r), t_min, t_max, sampler)
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3, sampler: &mut dyn Sampler) -> f64 {
+        let local = self.to_object(&Ray::new(origin, direction));
+        self.object.pdf_value(local.origin(), local.direction(), sampler)
+    }
+
+    fn random(&self, origin: Point3, sampler: &mut dyn Sampler) -> Vec3 {
+        let local = self.to_object(&Ray::new(origin, Vec3::new(1.0, 0.0, 0.0)));
+        rotate(self.object.random(local.origin(), sampler), self.rotation)
+    }
+
+    fn materials(&self) -> Vec<Arc<dyn Material>> {
+        self.object.materials()
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.object.is_emissive()
+    }
+}
@@ -0,0 +1,53 @@
+// Colored glass absorbs the light along every segment travelled inside (Beer-Lambert)
+use std::sync::Arc;
+
+use rt::basics::{Color, Point3, Ray, Vec3};
+use rt::integrator::{PathState, PathStats, ray_color};
+use rt::materials::{Dielectric, DiffuseLight};
+use rt::samplers::SamplerKind;
+use rt::scene::Scene;
+use rt::volumes::{Cube, HittableList, Sphere};
+
+// Transmittance after 1 unit inside
+const TRANSMITTANCE: [f64; 3] = [0.5, 0.8, 0.2];
+
+// Glass that does not bend the light, in a cube of edge `2 * half_size` at the origin, with `inner` inside,
+// and an emitter of radiance 1 behind it
+fn color(half_size: f64, inner: Option<Sphere>, direction: Vec3) -> Color {
+    let [r, g, b] = TRANSMITTANCE;
+    let glass = Dielectric::new(1.0, 0.0).with_absorption(Color::new(r, g, b), 1.0);
+    let mut world = HittableList::new();
+    world.add(Box::new(Cube::new(Point3::new(0.0, 0.0, 0.0), half_size, Vec3::new(0.0, 0.0, 0.0), Arc::new(glass))));
+    if let Some(inner) = inner {
+        world.add(Box::new(inner));
+    }
+    world.add(Box::new(Sphere::new(Point3::new(20.0, 0.0, 0.0), 5.0, Arc::new(DiffuseLight::new(Color::new(1.0, 1.0, 1.0))))));
+    let scene = Scene::new(world, Vec::new());
+
+    let mut sampler = SamplerKind::Independent.create(1, 0);
+    let mut stats = PathStats::default();
+    let ray = Ray::new(Point3::new(-10.0, 0.0, 0.0), direction);
+    ray_color(&ray, &scene, 16, None, sampler.as_mut(), &mut PathState::new(&mut stats, None))
+}
+
+fn expected(distance: f64) -> Color {
+    let [r, g, b] = TRANSMITTANCE;
+    Color::new(r.powf(distance), g.powf(distance), b.powf(distance))
+}
+
+#[test]
+fn slab_transmits_exp_of_minus_sigma_d() {
+    for (half_size, length) in [(0.5, 1.0), (1.5, 1.0), (1.5, 3.0)] {
+        let color = color(half_size, None, Vec3::new(length, 0.0, 0.0));
+        let thickness = 2.0 * half_size;
+        assert!((color - expected(thickness)).length() < 1e-9, "{} through {} units of glass", color, thickness);
+    }
+}
+
+#[test]
+fn light_inside_the_glass_is_absorbed_on_the_way_out() {
+    // An emitter at the center: its light crosses half the cube, less its radius
+    let inner = Sphere::new(Point3::new(0.0, 0.0, 0.0), 0.25, Arc::new(DiffuseLight::new(Color::new(1.0, 1.0, 1.0))));
+    let color = color(1.0, Some(inner), Vec3::new(1.0, 0.0, 0.0));
+    assert!((color - expected(0.75)).length() < 1e-9, "{}", color);
+}
@@ -0,0 +1,68 @@
+// Adaptive sampling: where the pixels stop
+mod common;
+
+use rt::basics::Color;
+use rt::film::SampleStats;
+use rt::renderer::RenderSettings;
+
+use common::*;
+
+// A diffuse floor under a small light, and the black sky above the horizon
+const SCENE: &str = r#"{
+    "camera": { "from": [0.0, 1.0, -4.0], "to": [0.0, 1.0, 0.0], "vfov": 60.0 },
+    "materials": {
+        "floor": { "type": "lambertian", "albedo": [0.7, 0.7, 0.7] },
+        "light": { "type": "light", "color": [50.0, 50.0, 50.0] }
+    },
+    "objects": [
+        { "type": "plane", "normal": [0.0, 1.0, 0.0], "distance": 0.0, "material": "floor" },
+        { "type": "sphere", "center": [0.0, 3.0, 4.0], "radius": 0.2, "material": "light" }
+    ]
+}"#;
+
+#[test]
+fn converged_needs_the_minimum_and_a_small_error() {
+    let mut description = small_description(SCENE);
+    description.image.min_samples_per_pixel = 16;
+    description.image.noise_threshold = 0.01;
+    let settings = description.build().unwrap().renderer.settings;
+
+    let mut black = SampleStats::default();
+    let mut noisy = SampleStats::default();
+    for n in 1..=64u32 {
+        black.add(Color::new(0.0, 0.0, 0.0));
+        noisy.add(if n % 2 == 0 { Color::new(1.0, 1.0, 1.0) } else { Color::new(0.0, 0.0, 0.0) });
+        assert_eq!(settings.converged(black), n >= 16 && n.is_power_of_two(), "black pixel after {} samples", n);
+        assert!(!settings.converged(noisy), "noisy pixel after {} samples", n);
+    }
+    // Disabled
+    let settings = RenderSettings { noise_threshold: 0.0, ..settings };
+    assert!(!settings.converged(black));
+}
+
+#[test]
+fn black_pixels_stop_at_the_minimum_and_noisy_ones_go_on() {
+    let mut description = small_description(SCENE);
+    description.image.samples_per_pixel = 64;
+    description.image.samples_per_pass = 4;
+    description.image.min_samples_per_pixel = 16;
+    // Out of reach of the noise of the floor
+    description.image.noise_threshold = 1e-4;
+    let setup = description.build().unwrap();
+    let film = render(&setup, film(&setup), whole_image(), 0..setup.renderer.settings.passes());
+
+    let (mut black, mut noisy) = (0, 0);
+    for j in 0..HEIGHT {
+        for i in 0..WIDTH {
+            let stats = film.stats(i, j);
+            if stats.mean() == 0.0 {
+                assert_eq!(stats.count(), 16, "black pixel ({}, {})", i, j);
+                black += 1;
+            } else if stats.error() > 0.0 {
+                assert_eq!(stats.count(), 64, "noisy pixel ({}, {})", i, j);
+                noisy += 1;
+            }
+        }
+    }
+    assert!(black > 0 && noisy > 0, "{} black and {} noisy pixels", black, noisy);
+}
@@ -0,0 +1,123 @@
+// Keyframe interpolation, and the scene put at the frames of an animation
+use std::sync::Arc;
+
+use rt::animation::{Animation, Animator, Interpolation, Key, ObjectAnimation, Track};
+use rt::basics::{Color, Point3, Ray, Vec3};
+use rt::materials::Lambertian;
+use rt::samplers::SamplerKind;
+use rt::scene::Scene;
+use rt::traits::Hittable;
+use rt::utils::INFINITY;
+use rt::volumes::{ConstantMedium, HittableList, Sphere, Transform, Transformed};
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-12
+}
+
+#[test]
+fn linear_keys_are_interpolated_and_held() {
+    let track = Track::new(vec![Key::new(10.0, 4.0, Interpolation::Linear), Key::new(0.0, 2.0, Interpolation::Linear)]);
+    assert!(close(track.at(2.5), 2.5));
+    assert!(close(track.at(-1.0), 2.0));
+    assert!(close(track.at(10.0), 4.0));
+    assert!(close(track.at(20.0), 4.0));
+}
+
+#[test]
+fn catmull_rom_goes_through_the_keys() {
+    // Keys on a line, unevenly spaced: the tangents are those of the line
+    let keys = [(0.0, 0.0), (1.0, 2.0), (4.0, 8.0), (5.0, 10.0)];
+    let track = Track::new(keys.iter().map(|&(frame, value)| Key::new(frame, value, Interpolation::CatmullRom)).collect());
+    for (frame, value) in keys {
+        assert!(close(track.at(frame), value));
+    }
+    for frame in [0.5, 2.0, 3.5, 4.25] {
+        assert!(close(track.at(frame), 2.0 * frame), "{} at frame {}", track.at(frame), frame);
+    }
+
+    // A step: the middle segment gets tangents of 0.5 per frame, less than its slope,
+    // so it is symmetric around its middle and below the line in its first half
+    let track = Track::new(vec![
+        Key::new(0.0, 0.0, Interpolation::CatmullRom),
+        Key::new(1.0, 0.0, Interpolation::CatmullRom),
+        Key::new(2.0, 1.0, Interpolation::CatmullRom),
+        Key::new(3.0, 1.0, Interpolation::CatmullRom),
+    ]);
+    assert!(close(track.at(1.5), 0.5));
+    assert!(track.at(1.25) < 0.25 && track.at(1.25) > 0.0);
+}
+
+#[test]
+fn bezier_follows_the_handles() {
+    // Control points 0, 1, 1, 0 over frames 0 to 3: 3 / 4 at the middle
+    let track = Track::new(vec![
+        Key::new(0.0, 0.0, Interpolation::Bezier).with_handles(0.0, 1.0),
+        Key::new(3.0, 0.0, Interpolation::Bezier).with_handles(1.0, 0.0),
+    ]);
+    assert!(close(track.at(1.5), 0.75));
+    assert!(close(track.at(1.0), 3.0 * (2.0 / 3.0) * (1.0 / 3.0)));
+
+    // Without handles, the tangents of Catmull–Rom
+    let keys = |interpolation| Track::new(vec![Key::new(0.0, 0.0, interpolation), Key::new(1.0, 3.0, interpolation), Key::new(3.0, 1.0, interpolation)]);
+    let (bezier, catmull_rom) = (keys(Interpolation::Bezier), keys(Interpolation::CatmullRom));
+    for frame in [0.25, 0.5, 1.5, 2.75] {
+        assert!(close(bezier.at(frame), catmull_rom.at(frame)));
+    }
+}
+
+#[test]
+#[should_panic(expected = "same frame")]
+fn keys_at_the_same_frame_are_refused() {
+    Track::new(vec![Key::new(1.0, 0.0, Interpolation::CatmullRom), Key::new(1.0, 1.0, Interpolation::CatmullRom)]);
+}
+
+#[test]
+fn set_frame_reports_changes() {
+    let mut world = HittableList::new();
+    world.add(Box::new(Sphere::new(Point3::new(0.0, 0.0, 3.0), 1.0, Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))))));
+    let mut scene = Scene::new(world, Vec::new());
+    let translation = Track::new(vec![
+        Key::new(0.0, Vec3::new(0.0, 0.0, 0.0), Interpolation::Linear),
+        Key::new(10.0, Vec3::new(1.0, 0.0, 0.0), Interpolation::Linear),
+    ]);
+    let scale = Track::new(vec![Key::new(20.0, 1.0, Interpolation::Linear), Key::new(30.0, -1.0, Interpolation::Linear)]);
+    let object = ObjectAnimation { translation: Some(translation), scale: Some(scale), ..ObjectAnimation::new(1, Point3::new(0.0, 0.0, 3.0)) };
+    let mut animator = Animator::new(Animation { objects: vec![object], ..Animation::default() }, &scene).unwrap();
+
+    assert_eq!(animator.set_frame(&mut scene, 0.0), Ok(true));
+    assert_eq!(animator.set_frame(&mut scene, 0.0), Ok(false));
+    assert_eq!(animator.set_frame(&mut scene, 5.0), Ok(true));
+    // Held after the last translation key, before the first scale key
+    assert_eq!(animator.set_frame(&mut scene, 10.0), Ok(true));
+    assert_eq!(animator.set_frame(&mut scene, 15.0), Ok(false));
+    // A scale of 0 on the way to the mirror image
+    assert!(animator.set_frame(&mut scene, 25.0).is_err());
+
+    assert!(Animator::new(Animation { objects: vec![ObjectAnimation::new(2, Point3::default())], ..Animation::default() }, &scene).is_err());
+}
+
+#[test]
+fn scaled_media_measure_world_distances() {
+    let density = 0.5;
+    let medium = || ConstantMedium::new(Box::new(Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0, Arc::new(Lambertian::new(Color::default())))), density, Color::default());
+    let transform = Transform { scale: 2.0, ..Transform::new(Point3::new(0.0, 0.0, 0.0)) };
+    let scaled = Transformed::new(Arc::new(medium()), transform);
+    let mut sampler = SamplerKind::Independent.create(1, 0);
+
+    // Across the center: 4 world units of medium, whatever the length of the direction
+    for length in [1.0, 3.0] {
+        let ray = Ray::new(Point3::new(-10.0, 0.0, 0.0), Vec3::new(length, 0.0, 0.0));
+        let transmittance = scaled.transmittance(&ray, 0.0001, INFINITY, sampler.as_mut());
+        assert!(close(transmittance, f64::exp(-density * 4.0)), "{} for a direction of length {}", transmittance, length);
+    }
+    let ray = Ray::new(Point3::new(-10.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+    assert!(close(medium().transmittance(&ray, 0.0001, INFINITY, sampler.as_mut()), f64::exp(-density * 2.0)));
+}
+
+#[test]
+fn transforms_with_a_scale_of_0_are_refused() {
+    let transform = Transform { scale: 0.0, ..Transform::new(Point3::new(0.0, 0.0, 0.0)) };
+    assert!(transform.check().is_err());
+    assert!(Transform { scale: f64::NAN, ..transform }.check().is_err());
+    assert!(Transform { scale: -2.0, ..transform }.check().is_ok());
+}
@@ -0,0 +1,108 @@
+// AOVs of the camera rays on known geometry
+mod common;
+
+use rt::basics::{Color, Point3, Vec3, dot, unit_vec};
+use rt::description::SceneSetup;
+use rt::film::Aov;
+use rt::integrator::first_hit;
+use rt::samplers::SamplerKind;
+
+use common::*;
+
+// One diffuse sphere ahead of the camera, on a black background
+const SPHERE: &str = r#"{
+    "camera": { "from": [0.0, 0.0, -1.0], "to": [0.0, 0.0, 1.0], "vfov": 90.0 },
+    "materials": { "blue": { "type": "lambertian", "albedo": [0.2, 0.6, 0.8] } },
+    "objects": [{ "type": "sphere", "center": [0.0, 0.0, 3.0], "radius": 1.5, "material": "blue" }]
+}"#;
+
+// A mirror sphere, inside a diffuse one around the whole scene
+const MIRROR: &str = r#"{
+    "camera": { "from": [0.0, 0.0, -1.0], "to": [0.0, 0.0, 1.0], "vfov": 90.0 },
+    "materials": {
+        "mirror": { "type": "metal", "albedo": [0.5, 0.9, 0.9], "fuzz": 0.0 },
+        "walls": { "type": "lambertian", "albedo": [0.8, 0.4, 0.6] }
+    },
+    "objects": [
+        { "type": "sphere", "center": [0.0, 0.0, 3.0], "radius": 1.5, "material": "mirror" },
+        { "type": "sphere", "center": [0.0, 0.0, 0.0], "radius": 50.0, "material": "walls" }
+    ]
+}"#;
+
+// Of the sphere in both scenes
+const RADIUS: f64 = 1.5;
+
+fn center() -> Point3 {
+    Point3::new(0.0, 0.0, 3.0)
+}
+
+fn close(a: Color, b: Color) -> bool {
+    (a - b).length() < 1e-9
+}
+
+// The camera rays through a grid over the film: origin, direction and AOVs
+fn camera_rays(setup: &SceneSetup) -> Vec<(Point3, Vec3, Aov)> {
+    let renderer = &setup.renderer;
+    let mut sampler = SamplerKind::Independent.create(1, 0);
+    let mut rays = Vec::new();
+    for j in 0..=20 {
+        for i in 0..=20 {
+            let (u, v) = (i as f64 / 20.0, j as f64 / 20.0);
+            let r = renderer.camera.generate_ray(u, v, sampler.as_mut()).unwrap();
+            rays.push((r.origin(), r.direction(), first_hit(&r, &renderer.scene, sampler.as_mut())));
+        }
+    }
+    rays
+}
+
+#[test]
+fn sphere_aovs_are_its_geometry_and_albedo() {
+    let setup = small_description(SPHERE).build().unwrap();
+    let rays = camera_rays(&setup);
+    assert!(rays.iter().any(|(_, _, aov)| aov.hit) && rays.iter().any(|(_, _, aov)| !aov.hit));
+    for (origin, direction, aov) in rays {
+        if !aov.hit {
+            assert_eq!((aov.object_id, aov.material_id, aov.depth), (0, 0, 0.0));
+            continue;
+        }
+        assert!(((aov.position - center()).length() - RADIUS).abs() < 1e-9, "{} is not on the sphere", aov.position);
+        // Facing the camera: the outer normal, towards the ray's origin
+        assert!(close(aov.normal, (aov.position - center()) / RADIUS), "normal {} at {}", aov.normal, aov.position);
+        assert!(dot(aov.normal, direction) < 0.0);
+        // t |d|, the distance from the camera, whatever the length of the direction
+        assert!((aov.depth - (aov.position - origin).length()).abs() < 1e-9);
+        assert!(close(aov.position, origin + aov.depth * unit_vec(direction)));
+        assert!(close(aov.albedo, Color::new(0.2, 0.6, 0.8)));
+        assert_eq!((aov.object_id, aov.material_id), (1, 1));
+    }
+}
+
+#[test]
+fn albedo_is_that_of_the_first_diffuse_surface() {
+    let setup = small_description(MIRROR).build().unwrap();
+    let walls = Color::new(0.8, 0.4, 0.6);
+    let rays = camera_rays(&setup);
+    assert!(rays.iter().any(|(_, _, aov)| aov.object_id == 1) && rays.iter().any(|(_, _, aov)| aov.object_id == 2));
+    for (_, _, aov) in rays {
+        assert!(aov.hit);
+        if aov.object_id == 1 {
+            // Through the mirror, tinted by it; the other AOVs are those of the mirror
+            assert!(close(aov.albedo, Color::new(0.5, 0.9, 0.9) * walls), "albedo {} through the mirror", aov.albedo);
+            assert!(((aov.position - center()).length() - RADIUS).abs() < 1e-9);
+        } else {
+            assert!(close(aov.albedo, walls));
+        }
+    }
+}
+
+#[test]
+fn ids_are_the_same_in_every_run() {
+    let ids = || {
+        let setup = small_description(MIRROR).build().unwrap();
+        let film = render(&setup, film(&setup), whole_image(), 0..PASSES);
+        (0..WIDTH * HEIGHT).map(|k| film.aov(k % WIDTH, k / WIDTH).unwrap()).map(|a| (a.object_id, a.material_id)).collect::<Vec<_>>()
+    };
+    let first = ids();
+    assert!(first.contains(&(1, 1)) && first.contains(&(2, 2)), "both spheres are seen");
+    assert_eq!(ids(), first);
+}
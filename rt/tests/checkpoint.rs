@@ -0,0 +1,91 @@
+// Resuming from a checkpoint gives the film of an uninterrupted render
+mod common;
+
+use rt::checkpoint::{Checkpoint, scene_hash};
+use rt::description::{FilterDescription, MaterialDescription, SceneDescription, SceneSetup};
+use rt::tiles::TileOrder;
+
+use common::*;
+
+#[test]
+fn resumed_render_matches_uninterrupted_render() {
+    let setup = setup();
+    let straight = render(&setup, film(&setup), whole_image(), 0..PASSES);
+
+    let interrupted = render(&setup, film(&setup), whole_image(), 0..2);
+    let checkpoint = Checkpoint { scene_hash: 7, region: whole_image(), first_pass: 0, last_pass: PASSES as u32, next_pass: 2 };
+    let file = TempFile::new("resume.ck");
+    checkpoint.save(file.path(), &interrupted).unwrap();
+
+    let mut resumed = film(&setup);
+    let loaded = Checkpoint::load(file.path(), &mut resumed).unwrap();
+    assert_eq!((loaded.scene_hash, loaded.region, loaded.next_pass), (7, whole_image(), 2));
+    let resumed = render(&setup, resumed, whole_image(), loaded.next_pass as i32..PASSES);
+    assert!(data(&resumed) == data(&straight), "the resumed render differs");
+}
+
+#[test]
+fn film_data_round_trip_is_lossless() {
+    let setup = setup();
+    let original = render(&setup, film(&setup), whole_image(), 0..PASSES);
+    let bytes = data(&original);
+    let mut read = film(&setup);
+    read.read_data(&mut bytes.as_slice()).unwrap();
+
+    assert!(data(&read) == bytes);
+    for j in 0..HEIGHT {
+        for i in 0..WIDTH {
+            assert_eq!(read.pixel(i, j), original.pixel(i, j));
+            assert_eq!(read.samples(i, j), original.samples(i, j));
+            assert_eq!(read.stats(i, j).error().to_bits(), original.stats(i, j).error().to_bits());
+            for layer in 0..original.light_paths().len() {
+                assert_eq!(read.light_path(i, j, layer), original.light_path(i, j, layer));
+            }
+            let (a, b) = (read.aov(i, j).unwrap(), original.aov(i, j).unwrap());
+            assert_eq!((a.albedo, a.normal, a.position), (b.albedo, b.normal, b.position));
+            assert_eq!((a.depth.to_bits(), a.object_id, a.material_id, a.hit), (b.depth.to_bits(), b.object_id, b.material_id, b.hit));
+        }
+    }
+}
+
+// What is changed in the description, and how
+type Change = (&'static str, fn(&mut SceneDescription));
+
+fn hash_of(setup: &SceneSetup, tile_order: TileOrder) -> u64 {
+    let renderer = &setup.renderer;
+    scene_hash(&renderer.scene, renderer.camera.as_ref(), setup.filter.as_ref(), &renderer.settings, setup.tile_size, tile_order)
+}
+
+#[test]
+fn resume_is_refused_for_another_scene_or_settings() {
+    let setup = setup();
+    let hash = hash_of(&setup, TileOrder::Spiral);
+    assert_eq!(hash_of(&common::setup(), TileOrder::Spiral), hash, "building the same scene again changed its hash");
+    assert_ne!(hash_of(&setup, TileOrder::Hilbert), hash);
+
+    let partial = render(&setup, film(&setup), whole_image(), 0..2);
+    let checkpoint = Checkpoint { scene_hash: hash, region: whole_image(), first_pass: 0, last_pass: PASSES as u32, next_pass: 2 };
+    let file = TempFile::new("refused.ck");
+    checkpoint.save(file.path(), &partial).unwrap();
+    assert!(Checkpoint::resume(file.path(), hash, &mut film(&setup)).is_ok());
+
+    let changes: [Change; 7] = [
+        ("a material", |d| {
+            d.materials.insert("red".to_string(), MaterialDescription::Lambertian { albedo: [0.7, 0.3, 0.4] });
+        }),
+        ("an object", |d| d.objects.truncate(3)),
+        ("the camera", |d| d.camera.vfov = Some(80.0)),
+        ("the filter", |d| d.image.filter = FilterDescription::Tent { radius: 1.0 }),
+        ("the samples per pixel", |d| d.image.samples_per_pixel = 4 * PASSES + 1),
+        ("the depth", |d| d.image.max_depth += 1),
+        ("the seed", |d| d.image.seed += 1),
+    ];
+    for (change, apply) in changes {
+        let mut description = description();
+        apply(&mut description);
+        let changed = description.build().unwrap();
+        let changed_hash = hash_of(&changed, TileOrder::Spiral);
+        assert_ne!(changed_hash, hash, "changing {} kept the hash", change);
+        assert!(Checkpoint::resume(file.path(), changed_hash, &mut film(&changed)).is_err(), "resumed after changing {}", change);
+    }
+}
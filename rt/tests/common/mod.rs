@@ -0,0 +1,84 @@
+// Small renders of scenes/example.json, shared by the tests
+#![allow(dead_code)]
+
+use std::env;
+use std::fs;
+use std::ops::Range;
+use std::sync::Mutex;
+
+use rt::description::{SceneDescription, SceneSetup};
+use rt::film::{Film, TileMerger, TileRect};
+use rt::integrator::PATH_KINDS;
+use rt::tiles::{TileOrder, tiles};
+
+// File in the temporary directory, unique to the test process, removed when dropped
+pub struct TempFile(String);
+
+impl TempFile {
+    pub fn new(name: &str) -> TempFile {
+        TempFile(env::temp_dir().join(format!("rt-{}-{}", std::process::id(), name)).to_string_lossy().into_owned())
+    }
+
+    pub fn path(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+pub const WIDTH: usize = 32;
+pub const HEIGHT: usize = 18;
+pub const PASSES: i32 = 4;
+
+// scenes/example.json, made small and quick to render
+pub fn description() -> SceneDescription {
+    small_description(include_str!("../../scenes/example.json"))
+}
+
+pub fn small_description(json: &str) -> SceneDescription {
+    let mut description = SceneDescription::from_json(json).unwrap();
+    description.image.width = WIDTH;
+    description.image.height = HEIGHT;
+    description.image.samples_per_pixel = 4 * PASSES;
+    description.image.samples_per_pass = 4;
+    description.image.tile_size = 8;
+    description
+}
+
+pub fn setup() -> SceneSetup {
+    description().build().unwrap()
+}
+
+// Empty film of the setup, with the AOVs and the light path layers
+pub fn film(setup: &SceneSetup) -> Film {
+    let layers = PATH_KINDS.iter().map(|kind| kind.to_string()).chain(setup.renderer.scene.light_groups());
+    Film::new(WIDTH, HEIGHT, setup.filter.clone()).with_aovs().with_light_paths(layers.collect())
+}
+
+// Tiles of the whole image, cut to `region` as for a partial render
+pub fn tiles_of(setup: &SceneSetup, region: TileRect) -> Vec<TileRect> {
+    tiles(WIDTH, HEIGHT, setup.tile_size, TileOrder::Spiral).iter().filter_map(|tile| tile.intersect(&region)).collect()
+}
+
+pub fn whole_image() -> TileRect {
+    TileRect { x0: 0, y0: 0, x1: WIDTH, y1: HEIGHT }
+}
+
+// Adds the passes of the region to `film`
+pub fn render(setup: &SceneSetup, film: Film, region: TileRect, passes: Range<i32>) -> Film {
+    let merger = Mutex::new(TileMerger::new(film));
+    let tiles = tiles_of(setup, region);
+    setup.renderer.render_passes(&merger, &tiles, passes, |_, _| true);
+    merger.into_inner().unwrap().film
+}
+
+// Everything the film accumulated, as saved in checkpoints
+pub fn data(film: &Film) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    film.write_data(&mut bytes).unwrap();
+    bytes
+}
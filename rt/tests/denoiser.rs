@@ -0,0 +1,68 @@
+// Denoising a noisy image of two flat regions: the noise goes, the edge between them stays
+use std::sync::Arc;
+
+use rt::basics::{Color, Vec3};
+use rt::denoiser::Denoiser;
+use rt::film::{Aov, Film, TileRect};
+use rt::filters::BoxFilter;
+use rt::samplers::SamplerKind;
+
+const WIDTH: usize = 32;
+const HEIGHT: usize = 16;
+const SAMPLES: u32 = 4;
+// Gray levels of the left and right halves, close enough for the noise to hide the edge in the colors
+const LEFT: f64 = 0.4;
+const RIGHT: f64 = 0.6;
+
+// Each sample of the halves off by up to 0.3; the AOVs of the halves differ in their normals, or in nothing
+fn noisy_film(normal_edge: bool) -> Film {
+    let mut film = Film::new(WIDTH, HEIGHT, Arc::new(BoxFilter::new(0.5))).with_aovs();
+    let mut tile = film.tile(TileRect { x0: 0, y0: 0, x1: WIDTH, y1: HEIGHT });
+    let mut sampler = SamplerKind::Independent.create(SAMPLES, 0);
+    for j in 0..HEIGHT {
+        for i in 0..WIDTH {
+            let left = i < WIDTH / 2;
+            let gray = if left { LEFT } else { RIGHT };
+            let normal = if left || !normal_edge { Vec3::new(0.0, 0.0, -1.0) } else { Vec3::new(-1.0, 0.0, 0.0) };
+            for s in 0..SAMPLES {
+                sampler.start_pixel_sample(i as u32, j as u32, s);
+                let (du, dv) = sampler.get_2d();
+                let value = gray + 0.6 * (sampler.get_1d() - 0.5);
+                tile.add_sample(i as f64 + du, j as f64 + dv, Color::new(value, value, value), &[]);
+                let aov = Aov { albedo: Color::new(0.5, 0.5, 0.5), normal, depth: 1.0, hit: true, ..Aov::default() };
+                tile.add_aov(i, j, s, &aov);
+            }
+        }
+    }
+    film.merge(tile);
+    film
+}
+
+// Mean and variance of the gray levels of the columns [i0, i1[
+fn columns(film: &Film, i0: usize, i1: usize) -> (f64, f64) {
+    let values: Vec<f64> = (0..HEIGHT).flat_map(|j| (i0..i1).map(move |i| (i, j))).map(|(i, j)| film.pixel(i, j).x()).collect();
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    (mean, values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / values.len() as f64)
+}
+
+#[test]
+fn noise_goes_and_normal_edges_stay() {
+    let noisy = noisy_film(true);
+    let denoised = Denoiser::new(1.0).denoise(&noisy);
+    for (i0, i1, gray) in [(0, WIDTH / 2, LEFT), (WIDTH / 2, WIDTH, RIGHT)] {
+        let (_, noisy_variance) = columns(&noisy, i0, i1);
+        let (mean, variance) = columns(&denoised, i0, i1);
+        assert!(variance < 0.1 * noisy_variance, "variance {} after denoising, {} before", variance, noisy_variance);
+        assert!((mean - gray).abs() < 0.02, "mean {} instead of {}", mean, gray);
+    }
+    // The columns on each side of the edge keep their levels
+    let (left, _) = columns(&denoised, WIDTH / 2 - 1, WIDTH / 2);
+    let (right, _) = columns(&denoised, WIDTH / 2, WIDTH / 2 + 1);
+    assert!((left - LEFT).abs() < 0.02 && (right - RIGHT).abs() < 0.02, "{} and {} along the edge", left, right);
+
+    // Without the normals to tell them apart, the halves are blended along the edge
+    let blended = Denoiser::new(1.0).denoise(&noisy_film(false));
+    let (left, _) = columns(&blended, WIDTH / 2 - 1, WIDTH / 2);
+    let (right, _) = columns(&blended, WIDTH / 2, WIDTH / 2 + 1);
+    assert!(right - left < 0.75 * (RIGHT - LEFT), "{} and {} along the edge, without the normals", left, right);
+}
@@ -0,0 +1,82 @@
+// The image does not depend on the number of threads, nor on when the workers finish their tiles
+mod common;
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Mutex;
+
+use rayon::ThreadPoolBuilder;
+use rt::basics::{Point3, Ray, Vec3};
+use rt::description::SceneSetup;
+use rt::film::TileMerger;
+use rt::renderer::Renderer;
+use rt::traits::{Camera, Sampler};
+
+use common::*;
+
+fn films_at_thread_counts(setup: &SceneSetup) -> Vec<Vec<u8>> {
+    [1, 3, 8]
+        .iter()
+        .map(|&threads| {
+            let pool = ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+            pool.install(|| data(&render(setup, film(setup), whole_image(), 0..PASSES)))
+        })
+        .collect()
+}
+
+#[test]
+fn same_film_at_any_thread_count() {
+    let films = films_at_thread_counts(&setup());
+    assert!(films[1] == films[0], "3 threads differ from 1");
+    assert!(films[2] == films[0], "8 threads differ from 1");
+}
+
+#[test]
+fn same_film_at_any_thread_count_with_adaptive_sampling() {
+    // Tiles of a pass start before the previous pass ends: each still sees the statistics of its own pixels
+    let mut description = description();
+    description.image.min_samples_per_pixel = 4;
+    description.image.noise_threshold = 0.2;
+    let films = films_at_thread_counts(&description.build().unwrap());
+    assert!(films[1] == films[0], "3 threads differ from 1");
+    assert!(films[2] == films[0], "8 threads differ from 1");
+}
+
+#[test]
+fn stopped_render_keeps_the_passes_done() {
+    let setup = setup();
+    let pool = ThreadPoolBuilder::new().num_threads(8).build().unwrap();
+    let merger = Mutex::new(TileMerger::new(film(&setup)));
+    let mut done = Vec::new();
+    let next_pass = pool.install(|| {
+        setup.renderer.render_passes(&merger, &tiles_of(&setup, whole_image()), 0..PASSES, |pass, _| {
+            done.push(pass);
+            pass < 1
+        })
+    });
+    assert_eq!((next_pass, done), (2, vec![0, 1]));
+    let two_passes = render(&setup, film(&setup), whole_image(), 0..2);
+    assert!(data(&merger.into_inner().unwrap().film) == data(&two_passes), "the tiles of the passes after the stop were merged");
+}
+
+// Panics in the middle of the image
+#[derive(Debug)]
+struct Broken;
+
+impl Camera for Broken {
+    fn generate_ray(&self, u: f64, v: f64, _sampler: &mut dyn Sampler) -> Option<Ray> {
+        assert!(u < 0.5 || v < 0.5, "broken camera");
+        Some(Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(u, v, 1.0)))
+    }
+}
+
+#[test]
+fn panic_in_a_tile_does_not_hang_the_other_workers() {
+    let setup = setup();
+    let renderer = Renderer::new(setup.renderer.settings.clone(), setup.renderer.scene.clone(), Box::new(Broken));
+    let pool = ThreadPoolBuilder::new().num_threads(8).build().unwrap();
+    let merger = Mutex::new(TileMerger::new(film(&setup)));
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        pool.install(|| renderer.render_passes(&merger, &tiles_of(&setup, whole_image()), 0..PASSES, |_, _| true))
+    }));
+    assert!(result.is_err());
+}
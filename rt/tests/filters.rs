@@ -0,0 +1,90 @@
+// Reconstruction filters: their support, and the film normalizing their weights
+use std::sync::Arc;
+
+use rt::basics::Color;
+use rt::film::{Film, TileRect};
+use rt::filters::{BoxFilter, GaussianFilter, LanczosFilter, MitchellFilter, TentFilter};
+use rt::samplers::SamplerKind;
+use rt::traits::Filter;
+
+fn filters() -> Vec<Arc<dyn Filter>> {
+    vec![
+        Arc::new(BoxFilter::new(0.5)),
+        Arc::new(BoxFilter::new(1.5)),
+        Arc::new(TentFilter::new(1.0)),
+        Arc::new(GaussianFilter::new(1.5, 0.5)),
+        Arc::new(MitchellFilter::new(2.0, 1.0 / 3.0, 1.0 / 3.0)),
+        Arc::new(LanczosFilter::new(3.0)),
+    ]
+}
+
+// Integral over [-radius, radius]^2, midpoint rule
+fn integral(filter: &dyn Filter) -> f64 {
+    let n = 400;
+    let h = 2.0 * filter.radius() / n as f64;
+    let mut sum = 0.0;
+    for j in 0..n {
+        for i in 0..n {
+            let (x, y) = (-filter.radius() + (i as f64 + 0.5) * h, -filter.radius() + (j as f64 + 0.5) * h);
+            sum += filter.evaluate(x, y);
+        }
+    }
+    sum * h * h
+}
+
+#[test]
+fn filters_vanish_outside_their_radius() {
+    for filter in filters() {
+        let r = filter.radius();
+        assert!(filter.evaluate(0.0, 0.0) > 0.0, "{:?} at its center", filter);
+        for k in 0..=100 {
+            let t = -2.0 * r + 4.0 * r * k as f64 / 100.0;
+            for outside in [r, r + 0.01, 1.5 * r, -r - 0.01, -1.5 * r] {
+                assert_eq!(filter.evaluate(outside, t), 0.0, "{:?} at ({}, {})", filter, outside, t);
+                assert_eq!(filter.evaluate(t, outside), 0.0, "{:?} at ({}, {})", filter, t, outside);
+            }
+        }
+    }
+}
+
+#[test]
+fn filters_integrate_to_their_known_areas() {
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-3 * b;
+    assert!(close(integral(&BoxFilter::new(1.5)), 9.0));
+    // The tent is radius high on each axis, not 1
+    assert!(close(integral(&TentFilter::new(2.0)), 16.0));
+    // The Mitchell–Netravali cubics integrate to 1 over [-2, 2], whatever B and C
+    for (b, c) in [(1.0 / 3.0, 1.0 / 3.0), (0.0, 0.5), (1.0, 0.0)] {
+        assert!(close(integral(&MitchellFilter::new(3.0, b, c)), 1.5 * 1.5));
+    }
+    for filter in filters() {
+        assert!(integral(filter.as_ref()) > 0.0, "{:?}", filter);
+    }
+}
+
+#[test]
+fn film_normalizes_the_weights() {
+    // Samples of a constant color everywhere: each pixel gets that color, negative lobes included
+    let (width, height) = (12, 8);
+    let color = Color::new(0.25, 0.5, 0.75);
+    for filter in filters() {
+        let mut film = Film::new(width, height, filter.clone());
+        let mut tile = film.tile(TileRect { x0: 0, y0: 0, x1: width, y1: height });
+        let mut sampler = SamplerKind::Independent.create(64, 0);
+        for j in 0..height {
+            for i in 0..width {
+                for s in 0..64 {
+                    sampler.start_pixel_sample(i as u32, j as u32, s);
+                    let (du, dv) = sampler.get_2d();
+                    tile.add_sample(i as f64 + du, j as f64 + dv, color, &[]);
+                }
+            }
+        }
+        film.merge(tile);
+        for j in 0..height {
+            for i in 0..width {
+                assert!((film.pixel(i, j) - color).length() < 1e-9, "{:?}: pixel ({}, {}) is {}", filter, i, j, film.pixel(i, j));
+            }
+        }
+    }
+}
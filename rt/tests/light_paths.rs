@@ -0,0 +1,46 @@
+// The light path layers split the color of each pixel: by kind of path, and by light group
+mod common;
+
+use std::ops::Range;
+
+use rt::basics::Color;
+use rt::film::Film;
+use rt::integrator::PATH_KINDS;
+
+use common::*;
+
+// Sum of the layers of pixel (i, j), clamped to black as Film::pixel (spectral samples may be out of gamut)
+fn layer_sum(film: &Film, i: usize, j: usize, layers: Range<usize>) -> Color {
+    let sum = layers.fold(Color::default(), |sum, layer| sum + film.light_path(i, j, layer));
+    Color::new(f64::max(0.0, sum.x()), f64::max(0.0, sum.y()), f64::max(0.0, sum.z()))
+}
+
+fn check_layers(json: &str, spectral: bool) {
+    let mut description = small_description(json);
+    description.image.spectral = spectral;
+    let setup = description.build().unwrap();
+    let film = render(&setup, film(&setup), whole_image(), 0..PASSES);
+    let (kinds, layers) = (PATH_KINDS.len(), film.light_paths().len());
+    assert_eq!(layers, kinds + setup.renderer.scene.light_groups().len());
+
+    let mut lit = 0;
+    for j in 0..HEIGHT {
+        for i in 0..WIDTH {
+            let pixel = film.pixel(i, j);
+            let tolerance = 1e-9 * f64::max(1.0, pixel.length());
+            for (split, sum) in [("kinds", layer_sum(&film, i, j, 0..kinds)), ("light groups", layer_sum(&film, i, j, kinds..layers))] {
+                assert!((sum - pixel).length() < tolerance, "the {} of pixel ({}, {}) sum to {} instead of {}", split, i, j, sum, pixel);
+            }
+            lit += (pixel.length() > 0.0) as usize;
+        }
+    }
+    assert!(lit > WIDTH * HEIGHT / 2, "the scene is too dark to test anything");
+}
+
+#[test]
+fn layers_sum_to_the_pixel() {
+    for spectral in [false, true] {
+        check_layers(include_str!("../scenes/example.json"), spectral);
+        check_layers(include_str!("../scenes/lights.json"), spectral);
+    }
+}
@@ -0,0 +1,58 @@
+// Light sampling with multiple importance sampling (NEE + MIS) against BSDF sampling alone, at equal samples per pixel
+mod common;
+
+use std::sync::Arc;
+
+use rt::description::SceneSetup;
+use rt::volumes::HittableList;
+
+use common::*;
+
+// A small bright sphere above a diffuse floor: BSDF sampling rarely finds it
+const SCENE: &str = r#"{
+    "camera": { "from": [0.0, 3.0, -4.0], "to": [0.0, 0.0, 0.0], "vfov": 60.0 },
+    "materials": {
+        "floor": { "type": "lambertian", "albedo": [0.7, 0.7, 0.7] },
+        "light": { "type": "light", "color": [50.0, 50.0, 50.0] }
+    },
+    "objects": [
+        { "type": "plane", "normal": [0.0, 1.0, 0.0], "distance": 0.0, "material": "floor" },
+        { "type": "sphere", "center": [0.0, 2.0, 0.0], "radius": 0.2, "material": "light" }
+    ]
+}"#;
+
+// Mean luminance of the pixels of the floor, with its variance, and the average variance of these pixels
+fn floor_statistics(setup: &SceneSetup) -> (f64, f64, f64) {
+    let film = render(setup, film(setup), whole_image(), 0..PASSES);
+    let (mut mean, mut variance, mut n) = (0.0, 0.0, 0.0);
+    for j in 0..HEIGHT {
+        for i in 0..WIDTH {
+            let stats = film.stats(i, j);
+            // Not the pixels seeing the light itself
+            if stats.mean() < 5.0 {
+                mean += stats.mean();
+                variance += stats.mean_variance();
+                n += 1.0;
+            }
+        }
+    }
+    (mean / n, variance / (n * n), variance / n)
+}
+
+#[test]
+fn light_sampling_lowers_the_variance() {
+    let mut description = small_description(SCENE);
+    description.image.samples_per_pixel = 64 * PASSES;
+    description.image.samples_per_pass = 64;
+    let with_light_sampling = description.build().unwrap();
+    // Without emitters to sample, the light is only found by the BSDF samples, with the full weight
+    let mut bsdf_only = description.build().unwrap();
+    Arc::get_mut(&mut bsdf_only.renderer.scene).unwrap().emitters = HittableList::new();
+
+    let (mis_mean, mis_mean_variance, mis_variance) = floor_statistics(&with_light_sampling);
+    let (bsdf_mean, bsdf_mean_variance, bsdf_variance) = floor_statistics(&bsdf_only);
+    // Both converge to the same image: the means agree within 4 standard deviations
+    let tolerance = 4.0 * f64::sqrt(mis_mean_variance + bsdf_mean_variance);
+    assert!((mis_mean - bsdf_mean).abs() < tolerance, "mean luminance {} with light sampling, {} without", mis_mean, bsdf_mean);
+    assert!(mis_variance < 0.01 * bsdf_variance, "variance {} with light sampling, {} without", mis_variance, bsdf_variance);
+}
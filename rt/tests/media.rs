@@ -0,0 +1,23 @@
+// Fog and smoke: Beer-Lambert's law over the distances crossed
+use std::sync::Arc;
+
+use rt::basics::{Color, Point3};
+use rt::materials::Lambertian;
+use rt::utils::INFINITY;
+use rt::volumes::{Atmosphere, ConstantMedium, Sphere};
+
+#[test]
+fn atmosphere_extent_only_bounds_escaping_rays() {
+    let atmosphere = Atmosphere::new(0.1, 0.2, 5.0);
+    assert!((atmosphere.transmittance(2.0) - f64::exp(-0.3 * 2.0)).abs() < 1e-12);
+    // Farther than the extent, inside the scene
+    assert!((atmosphere.transmittance(8.0) - f64::exp(-0.3 * 8.0)).abs() < 1e-12);
+    assert!((atmosphere.transmittance(INFINITY) - f64::exp(-0.3 * 5.0)).abs() < 1e-12);
+}
+
+#[test]
+#[should_panic(expected = "positive and finite")]
+fn media_need_a_positive_density() {
+    let boundary = Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0, Arc::new(Lambertian::new(Color::default())));
+    ConstantMedium::new(Box::new(boundary), 0.0, Color::new(1.0, 1.0, 1.0));
+}
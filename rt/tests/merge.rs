@@ -0,0 +1,71 @@
+// Renders split by region or by passes, merged, give the film of a single render up to rounding
+mod common;
+
+use rt::basics::Color;
+use rt::checkpoint::{Checkpoint, merge_checkpoints};
+use rt::description::SceneSetup;
+use rt::film::{Film, TileRect};
+
+use common::*;
+
+// Relative difference allowed: the sums are added in another order, and the statistics merged instead of accumulated
+const TOLERANCE: f64 = 1e-9;
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() <= TOLERANCE * f64::max(1.0, b.abs())
+}
+
+fn close_colors(a: Color, b: Color) -> bool {
+    close(a.x(), b.x()) && close(a.y(), b.y()) && close(a.z(), b.z())
+}
+
+// Renders each (region, passes) part to a checkpoint, then merges them
+fn split_render(setup: &SceneSetup, name: &str, parts: &[(TileRect, i32, i32)]) -> Film {
+    let files: Vec<TempFile> = (0..parts.len()).map(|k| TempFile::new(&format!("{}-{}.ck", name, k))).collect();
+    let paths: Vec<String> = files.iter().map(|file| file.path().to_string()).collect();
+    for (path, &(region, first_pass, last_pass)) in paths.iter().zip(parts) {
+        let part = render(setup, film(setup), region, first_pass..last_pass);
+        let checkpoint = Checkpoint { scene_hash: 7, region, first_pass: first_pass as u32, last_pass: last_pass as u32, next_pass: last_pass as u32 };
+        checkpoint.save(path, &part).unwrap();
+    }
+    let mut merged = film(setup);
+    assert_eq!(merge_checkpoints(&paths, 7, &mut merged).unwrap().len(), parts.len());
+    merged
+}
+
+fn assert_matches(merged: &Film, single: &Film) {
+    for j in 0..HEIGHT {
+        for i in 0..WIDTH {
+            assert!(close_colors(merged.pixel(i, j), single.pixel(i, j)), "pixel ({}, {})", i, j);
+            assert_eq!(merged.samples(i, j), single.samples(i, j));
+            assert!(close(merged.stats(i, j).mean(), single.stats(i, j).mean()));
+            assert!(close(merged.stats(i, j).error(), single.stats(i, j).error()));
+            for layer in 0..single.light_paths().len() {
+                assert!(close_colors(merged.light_path(i, j, layer), single.light_path(i, j, layer)), "layer {} of ({}, {})", layer, i, j);
+            }
+            let (a, b) = (merged.aov(i, j).unwrap(), single.aov(i, j).unwrap());
+            assert!(close_colors(a.albedo, b.albedo) && close_colors(a.normal, b.normal) && close_colors(a.position, b.position));
+            assert!(close(a.depth, b.depth));
+            assert_eq!((a.object_id, a.material_id, a.hit), (b.object_id, b.material_id, b.hit));
+        }
+    }
+}
+
+#[test]
+fn region_split_matches_single_render() {
+    let setup = setup();
+    let single = render(&setup, film(&setup), whole_image(), 0..PASSES);
+    // Across tiles, which the regions cut
+    let left = TileRect { x0: 0, y0: 0, x1: 12, y1: HEIGHT };
+    let right = TileRect { x0: 12, y0: 0, x1: WIDTH, y1: HEIGHT };
+    let merged = split_render(&setup, "regions", &[(left, 0, PASSES), (right, 0, PASSES)]);
+    assert_matches(&merged, &single);
+}
+
+#[test]
+fn pass_split_matches_single_render() {
+    let setup = setup();
+    let single = render(&setup, film(&setup), whole_image(), 0..PASSES);
+    let merged = split_render(&setup, "passes", &[(whole_image(), 0, 2), (whole_image(), 2, PASSES)]);
+    assert_matches(&merged, &single);
+}
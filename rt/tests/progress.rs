@@ -0,0 +1,57 @@
+// The JSON events of the progress, one object per line
+use serde_json::Value;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rt::integrator::PathStats;
+use rt::progress::{Progress, ProgressFormat};
+
+// Output kept for the test to read
+#[derive(Clone, Default)]
+struct Output(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn json_events_are_one_object_per_line() {
+    let output = Output::default();
+    // Resumed after 2 of the passes 0..10
+    let progress = Progress::new(ProgressFormat::Json, 0, 2, 10).with_output(output.clone());
+    let stats = PathStats { samples: 100, rays: 250, shadow_rays: 50, max_depth_cut: 5 };
+    progress.message("Resuming after 2 passes");
+    progress.pass_done(2, &stats);
+    progress.pass_done(3, &stats);
+    progress.summary(4, &stats, Duration::from_millis(1500));
+
+    let text = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+    let events: Vec<Value> = text.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    let kinds: Vec<&str> = events.iter().map(|event| event["event"].as_str().unwrap()).collect();
+    assert_eq!(kinds, ["message", "progress", "progress", "summary"]);
+
+    assert_eq!(events[0]["message"], "Resuming after 2 passes");
+    let progress = &events[2];
+    assert_eq!((progress["pass"].as_i64(), progress["passes"].as_i64()), (Some(4), Some(10)));
+    assert_eq!(progress["percent"].as_f64(), Some(40.0));
+    for field in ["elapsed_seconds", "eta_seconds", "rays_per_second", "samples_per_second"] {
+        assert!(progress[field].as_f64().is_some_and(|x| x >= 0.0), "{} is {}", field, progress[field]);
+    }
+
+    let summary = &events[3];
+    assert_eq!(summary["passes"].as_i64(), Some(2), "the passes of this run");
+    assert_eq!((summary["primary_rays"].as_u64(), summary["secondary_rays"].as_u64(), summary["shadow_rays"].as_u64()), (Some(100), Some(150), Some(50)));
+    assert_eq!(summary["max_depth_cut"].as_u64(), Some(5));
+    assert_eq!((summary["scene_build_seconds"].as_f64(), summary["bvh"].as_bool()), (Some(1.5), Some(false)));
+    for field in ["average_path_depth", "render_seconds", "rays_per_second"] {
+        assert!(summary[field].as_f64().is_some(), "{} is {}", field, summary[field]);
+    }
+}
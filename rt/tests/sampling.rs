@@ -0,0 +1,117 @@
+// Warps of uniform samples: the samples stay in their domain, and their pdfs integrate functions over it
+// With the pdf p, E[f / p] is the integral of f: E[1 / p] the measure of the domain (Monte Carlo)
+use rt::basics::*;
+use rt::samplers::SamplerKind;
+use rt::utils::PI;
+
+const SAMPLES: u32 = 100_000;
+
+// Independent samples of [0, 1[^2
+fn uniform() -> impl Iterator<Item = (f64, f64)> {
+    let mut sampler = SamplerKind::Independent.create(SAMPLES, 0);
+    (0..SAMPLES).map(move |s| {
+        sampler.start_pixel_sample(0, 0, s);
+        sampler.get_2d()
+    })
+}
+
+// Estimates of the integrals of the functions over the domain, from the samples of `warp` and their pdfs,
+// with their standard errors
+fn estimate<T>(warp: impl Fn((f64, f64)) -> (T, f64), functions: &[&dyn Fn(&T) -> f64]) -> Vec<(f64, f64)> {
+    let mut sums = vec![(0.0, 0.0); functions.len()];
+    for u in uniform() {
+        let (x, pdf) = warp(u);
+        assert!(pdf > 0.0 && pdf.is_finite(), "pdf {} at {:?}", pdf, u);
+        for ((sum, sum2), f) in sums.iter_mut().zip(functions) {
+            let value = f(&x) / pdf;
+            *sum += value;
+            *sum2 += value * value;
+        }
+    }
+    let n = SAMPLES as f64;
+    sums.iter().map(|(sum, sum2)| (sum / n, f64::sqrt(f64::max(0.0, sum2 / n - (sum / n) * (sum / n)) / n))).collect()
+}
+
+// Within 4 standard errors
+fn assert_close(estimates: &[(f64, f64)], expected: &[f64]) {
+    for (&(estimate, error), expected) in estimates.iter().zip(expected) {
+        assert!((estimate - expected).abs() <= 4.0 * error + 1e-9, "{} ± {} instead of {}", estimate, error, expected);
+    }
+}
+
+fn unit(v: &Vec3) -> bool {
+    (v.length() - 1.0).abs() < 1e-9
+}
+
+#[test]
+fn uniform_sphere() {
+    let estimates = estimate(sample_uniform_sphere, &[&|v| {
+        assert!(unit(v));
+        1.0
+    }, &|v| v.z() * v.z()]);
+    assert_close(&estimates, &[4.0 * PI, 4.0 * PI / 3.0]);
+}
+
+#[test]
+fn uniform_hemisphere() {
+    let normal = unit_vec(Vec3::new(1.0, 2.0, -0.5));
+    let estimates = estimate(|u| sample_uniform_hemisphere(normal, u), &[&|v| {
+        assert!(unit(v) && dot(*v, normal) >= 0.0);
+        1.0
+    }, &|v| dot(*v, normal)]);
+    assert_close(&estimates, &[2.0 * PI, PI]);
+}
+
+#[test]
+fn cosine_hemisphere() {
+    let normal = unit_vec(Vec3::new(-0.3, 0.0, 1.0));
+    let estimates = estimate(|u| sample_cosine_hemisphere(normal, u), &[&|v| {
+        assert!(unit(v) && dot(*v, normal) >= 0.0);
+        if dot(*v, normal) > 0.1 { 1.0 } else { 0.0 }
+    }, &|v| dot(*v, normal)]);
+    // 1 / p is not bounded at the horizon, and its variance is infinite: the measure of a cap instead
+    assert_close(&estimates, &[2.0 * PI * 0.9, PI]);
+    for u in uniform().take(1000) {
+        let (v, pdf) = sample_cosine_hemisphere(normal, u);
+        assert!((pdf - cosine_hemisphere_pdf(dot(v, normal))).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn concentric_disk() {
+    let estimates = estimate(sample_concentric_disk, &[&|&(x, y)| {
+        assert!(x * x + y * y <= 1.0 + 1e-12);
+        1.0
+    }, &|&(x, _)| x * x]);
+    assert_close(&estimates, &[PI, PI / 4.0]);
+}
+
+#[test]
+fn uniform_cone() {
+    let axis = unit_vec(Vec3::new(0.0, -1.0, 1.0));
+    for cos_theta_max in [0.99, 0.5, -0.5] {
+        let estimates = estimate(|u| sample_uniform_cone(axis, cos_theta_max, u), &[&|v| {
+            assert!(unit(v) && dot(*v, axis) >= cos_theta_max - 1e-9);
+            1.0
+        }, &|v| dot(*v, axis)]);
+        let expected = [2.0 * PI * (1.0 - cos_theta_max), PI * (1.0 - cos_theta_max * cos_theta_max)];
+        assert_close(&estimates, &expected);
+    }
+}
+
+#[test]
+fn triangle() {
+    let (p0, p1, p2) = (Point3::new(0.0, 0.0, 1.0), Point3::new(3.0, 0.0, 1.0), Point3::new(0.0, 2.0, 2.0));
+    let normal = unit_vec(cross(p1 - p0, p2 - p0));
+    let area = 0.5 * cross(p1 - p0, p2 - p0).length();
+    let estimates = estimate(|u| sample_triangle(p0, p1, p2, u), &[&|p: &Point3| {
+        // In the plane, on the inner side of each edge
+        assert!(dot(*p - p0, normal).abs() < 1e-9);
+        for (a, b) in [(p0, p1), (p1, p2), (p2, p0)] {
+            assert!(dot(cross(b - a, *p - a), normal) >= -1e-9);
+        }
+        1.0
+    }, &|p: &Point3| p.x()]);
+    // The mean of x is that of the centroid
+    assert_close(&estimates, &[area, area * (p0.x() + p1.x() + p2.x()) / 3.0]);
+}
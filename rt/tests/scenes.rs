@@ -0,0 +1,38 @@
+// The scene descriptions of scenes/ build
+use std::fs;
+
+use rt::description::SceneDescription;
+
+#[test]
+fn scene_descriptions_build() {
+    let mut count = 0;
+    for entry in fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/scenes")).unwrap() {
+        let path = entry.unwrap().path();
+        let description = SceneDescription::from_json(&fs::read_to_string(&path).unwrap()).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+        let setup = description.build().unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+        assert_eq!(setup.renderer.scene.lights.len(), description.lights.len());
+        assert_eq!(setup.renderer.scene.atmosphere.is_some(), description.atmosphere.is_some());
+        count += 1;
+    }
+    assert!(count >= 3);
+}
+
+#[test]
+fn stereo_descriptions_are_checked() {
+    let scene = |stereo: &str| format!(r#"{{ "camera": {{ "from": [0, 0, 0], "to": [0, 0, 1], "vfov": 90, "stereo": {} }}, "materials": {{}}, "objects": [] }}"#, stereo);
+    let mut description = SceneDescription::from_json(&scene(r#"{ "interocular": 0.064, "convergence": 3.0, "mode": "toe_in", "layout": "over_under" }"#)).unwrap();
+    description.image.height = 225;
+    assert!(description.build().is_err(), "the eyes would share a row of pixels");
+    description.image.height = 224;
+    assert!(description.build().is_ok());
+    assert!(SceneDescription::from_json(&scene(r#"{ "interocular": 0.064, "convergence": 3.0, "mode": "parallel" }"#)).is_err());
+    assert!(SceneDescription::from_json(&scene(r#"{ "interocular": 0.064, "convergence": 3.0, "layout": "anaglyph" }"#)).is_err());
+
+    let mut description = SceneDescription::from_json(&scene(r#"{ "interocular": 0.064, "convergence": 3.0 }"#)).unwrap();
+    description.camera.stereo.as_mut().unwrap().interocular = f64::NAN;
+    assert!(description.build().is_err());
+    let stereo = description.camera.stereo.as_mut().unwrap();
+    stereo.interocular = 0.064;
+    stereo.convergence = f64::INFINITY;
+    assert!(description.build().is_err());
+}
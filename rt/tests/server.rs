@@ -0,0 +1,146 @@
+// Requests to the render server, handled in process
+use serde_json::Value;
+use std::io::Read;
+use std::thread;
+use std::time::{Duration, Instant};
+use tiny_http::{Method, Request, TestRequest};
+
+use rt::server::{MAX_FINISHED_JOBS, RenderServer};
+
+const SCENE: &str = r#"{
+    "image": { "width": 8, "height": 6, "samples_per_pixel": 4, "samples_per_pass": 1, "tile_size": 4 },
+    "camera": { "from": [0.0, 0.0, -1.0], "to": [0.0, 0.0, 1.0], "vfov": 90.0 },
+    "materials": { "light": { "type": "light", "color": [1.0, 1.0, 1.0] } },
+    "objects": [{ "type": "sphere", "center": [0.0, 0.0, 3.0], "radius": 1.0, "material": "light" }]
+}"#;
+
+// Many passes, to be cancelled while it renders
+const LONG_SCENE: &str = r#"{
+    "image": { "width": 64, "height": 64, "samples_per_pixel": 65536, "samples_per_pass": 1 },
+    "camera": { "from": [0.0, 0.0, -1.0], "to": [0.0, 0.0, 1.0], "vfov": 90.0 },
+    "materials": { "light": { "type": "light", "color": [1.0, 1.0, 1.0] } },
+    "objects": [{ "type": "sphere", "center": [0.0, 0.0, 3.0], "radius": 1.0, "material": "light" }]
+}"#;
+
+// Inside a white sphere, lit from inside, every path reaches max_depth
+const DEEP_SCENE: &str = r#"{
+    "image": { "width": 4, "height": 4, "samples_per_pixel": 1, "samples_per_pass": 1, "max_depth": 1000 },
+    "camera": { "from": [0.0, 0.0, 0.0], "to": [0.0, 0.0, 1.0], "vfov": 90.0 },
+    "materials": {
+        "white": { "type": "lambertian", "albedo": [1.0, 1.0, 1.0] },
+        "mirror": { "type": "metal", "albedo": [1.0, 1.0, 1.0] },
+        "light": { "type": "light", "color": [1.0, 1.0, 1.0] }
+    },
+    "objects": [
+        { "type": "sphere", "center": [0.0, 0.0, 0.0], "radius": 10.0, "material": "white" },
+        { "type": "sphere", "center": [0.0, 5.0, 0.0], "radius": 1.0, "material": "mirror" },
+        { "type": "sphere", "center": [0.0, -5.0, 0.0], "radius": 0.5, "material": "light" }
+    ]
+}"#;
+
+fn request(server: &RenderServer, method: Method, path: &str, body: &'static str) -> (u16, Vec<u8>) {
+    let mut request: Request = TestRequest::new().with_method(method).with_path(path).with_body(body).into();
+    let response = server.handle(&mut request);
+    let code = response.status_code().0;
+    let mut data = Vec::new();
+    response.into_reader().read_to_end(&mut data).unwrap();
+    (code, data)
+}
+
+fn json(server: &RenderServer, method: Method, path: &str, body: &'static str) -> (u16, Value) {
+    let (code, data) = request(server, method, path, body);
+    (code, serde_json::from_slice(&data).unwrap())
+}
+
+fn submit(server: &RenderServer, scene: &'static str) -> u64 {
+    let (code, job) = json(server, Method::Post, "/jobs", scene);
+    assert_eq!(code, 201, "{}", job);
+    job["id"].as_u64().unwrap()
+}
+
+// The job once in a state that satisfies `until`
+fn wait(server: &RenderServer, id: u64, until: impl Fn(&str) -> bool) -> Value {
+    let start = Instant::now();
+    loop {
+        let (code, job) = json(server, Method::Get, &format!("/jobs/{}", id), "");
+        assert_eq!(code, 200);
+        if until(job["state"].as_str().unwrap()) {
+            return job;
+        }
+        assert!(start.elapsed() < Duration::from_secs(60), "job {} stuck in {}", id, job);
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+fn finished(state: &str) -> bool {
+    matches!(state, "done" | "cancelled" | "failed")
+}
+
+#[test]
+fn submitted_job_renders() {
+    let server = RenderServer::start();
+    let id = submit(&server, SCENE);
+    let job = wait(&server, id, finished);
+    assert_eq!((job["state"].as_str(), job["passes_done"].as_i64(), job["passes"].as_i64()), (Some("done"), Some(4), Some(4)));
+    assert_eq!((job["width"].as_u64(), job["height"].as_u64()), (Some(8), Some(6)));
+
+    let (code, png) = request(&server, Method::Get, &format!("/jobs/{}/image.png", id), "");
+    assert_eq!(code, 200);
+    assert!(png.starts_with(b"\x89PNG"));
+    let (code, list) = json(&server, Method::Get, "/jobs", "");
+    assert_eq!((code, list.as_array().unwrap().len()), (200, 1));
+}
+
+#[test]
+fn bad_scenes_are_refused() {
+    let server = RenderServer::start();
+    for scene in ["{", r#"{ "camera": { "from": [0.0, 0.0, 0.0], "to": [0.0, 0.0, 1.0] }, "materials": {}, "objects": [], "image": { "width": 0 } }"#] {
+        let (code, error) = json(&server, Method::Post, "/jobs", scene);
+        assert_eq!(code, 400);
+        assert!(error["error"].is_string());
+    }
+    assert_eq!(json(&server, Method::Get, "/jobs", "").1, Value::Array(Vec::new()));
+    assert_eq!(json(&server, Method::Get, "/jobs/1", "").0, 404);
+    assert_eq!(json(&server, Method::Put, "/jobs/1", "").0, 405);
+}
+
+#[test]
+fn jobs_are_cancelled_then_forgotten() {
+    let server = RenderServer::start();
+    let running = submit(&server, LONG_SCENE);
+    let queued = submit(&server, LONG_SCENE);
+    wait(&server, running, |state| state == "running");
+
+    // Queued: cancelled right away
+    let (code, job) = json(&server, Method::Delete, &format!("/jobs/{}", queued), "");
+    assert_eq!((code, job["state"].as_str()), (200, Some("cancelled")));
+    // Running: at the next tile
+    json(&server, Method::Delete, &format!("/jobs/{}", running), "");
+    let job = wait(&server, running, finished);
+    assert_eq!(job["state"].as_str(), Some("cancelled"));
+    assert!(job["passes_done"].as_i64().unwrap() < job["passes"].as_i64().unwrap());
+
+    // Finished: removed
+    assert_eq!(json(&server, Method::Delete, &format!("/jobs/{}", running), "").0, 200);
+    assert_eq!(json(&server, Method::Get, &format!("/jobs/{}", running), "").0, 404);
+    assert_eq!(json(&server, Method::Get, "/jobs", "").1.as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn oldest_finished_jobs_are_forgotten() {
+    let server = RenderServer::start();
+    let ids: Vec<u64> = (0..MAX_FINISHED_JOBS + 2).map(|_| submit(&server, SCENE)).collect();
+    wait(&server, *ids.last().unwrap(), finished);
+
+    let (_, list) = json(&server, Method::Get, "/jobs", "");
+    let kept: Vec<u64> = list.as_array().unwrap().iter().map(|job| job["id"].as_u64().unwrap()).collect();
+    assert_eq!(kept, ids[2..]);
+}
+
+#[test]
+fn deep_paths_fit_in_the_stack() {
+    let server = RenderServer::start();
+    let id = submit(&server, DEEP_SCENE);
+    let job = wait(&server, id, finished);
+    assert_eq!(job["state"].as_str(), Some("done"), "{}", job);
+}
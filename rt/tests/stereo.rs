@@ -0,0 +1,74 @@
+// The eyes of a stereo pair do not bleed into each other through the filter
+use std::sync::{Arc, Mutex};
+
+use rt::basics::{Color, Point3, Ray, Vec3};
+use rt::camera::{StereoCamera, StereoLayout};
+use rt::film::{Film, TileMerger};
+use rt::filters::GaussianFilter;
+use rt::materials::DiffuseLight;
+use rt::renderer::{RenderSettings, Renderer};
+use rt::samplers::SamplerKind;
+use rt::scene::Scene;
+use rt::tiles::{TileOrder, tiles};
+use rt::traits::{Camera, Sampler};
+use rt::volumes::{HittableList, Sphere};
+
+const WIDTH: usize = 16;
+const HEIGHT: usize = 10;
+
+// Every ray of the eye from the origin in one direction: the eye sees a constant color
+#[derive(Debug)]
+struct Eye(Vec3);
+
+impl Camera for Eye {
+    fn generate_ray(&self, _u: f64, _v: f64, _sampler: &mut dyn Sampler) -> Option<Ray> {
+        Some(Ray::new(Point3::new(0.0, 0.0, 0.0), self.0))
+    }
+}
+
+// Red ahead for the left eye, green behind for the right one
+fn render(layout: StereoLayout) -> Film {
+    let mut world = HittableList::new();
+    world.add(Box::new(Sphere::new(Point3::new(0.0, 0.0, 10.0), 5.0, Arc::new(DiffuseLight::new(Color::new(1.0, 0.0, 0.0))))));
+    world.add(Box::new(Sphere::new(Point3::new(0.0, 0.0, -10.0), 5.0, Arc::new(DiffuseLight::new(Color::new(0.0, 1.0, 0.0))))));
+    let camera = StereoCamera::new(Box::new(Eye(Vec3::new(0.0, 0.0, 1.0))), Box::new(Eye(Vec3::new(0.0, 0.0, -1.0))), layout);
+    let settings = RenderSettings {
+        width: WIDTH,
+        height: HEIGHT,
+        samples_per_pixel: 4,
+        samples_per_pass: 4,
+        min_samples_per_pixel: 4,
+        noise_threshold: 0.0,
+        max_depth: 4,
+        spectral: false,
+        sampler: SamplerKind::Sobol,
+        seed: 0,
+    };
+    let renderer = Renderer::new(settings, Arc::new(Scene::new(world, Vec::new())), Box::new(camera));
+    // Wider than a pixel, over tiles cut across the seam
+    let film = Film::new(WIDTH, HEIGHT, Arc::new(GaussianFilter::new(2.0, 1.0)));
+    let merger = Mutex::new(TileMerger::new(film));
+    renderer.render_passes(&merger, &tiles(WIDTH, HEIGHT, 3, TileOrder::Scanline), 0..1, |_, _| true);
+    merger.into_inner().unwrap().film
+}
+
+#[test]
+fn eyes_do_not_bleed_into_each_other() {
+    let red = Color::new(1.0, 0.0, 0.0);
+    let green = Color::new(0.0, 1.0, 0.0);
+    for layout in [StereoLayout::SideBySide, StereoLayout::OverUnder] {
+        let film = render(layout);
+        for j in 0..HEIGHT {
+            for i in 0..WIDTH {
+                // Film rows go up, as v: the left eye is on the top half
+                let left = match layout {
+                    StereoLayout::SideBySide => i < WIDTH / 2,
+                    StereoLayout::OverUnder => j >= HEIGHT / 2,
+                };
+                let expected = if left { red } else { green };
+                let pixel = film.pixel(i, j);
+                assert!((pixel - expected).length() < 1e-12, "{:?} pixel ({}, {}) is {} instead of {}", layout, i, j, pixel, expected);
+            }
+        }
+    }
+}
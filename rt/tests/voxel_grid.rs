@@ -0,0 +1,32 @@
+// Malformed voxel grid files are errors, not panics
+mod common;
+
+use std::fs;
+
+use rt::basics::Point3;
+use rt::densities::VoxelGrid;
+use rt::traits::DensityField;
+
+use common::TempFile;
+
+fn load(name: &str, bytes: &[u8]) -> std::io::Result<VoxelGrid> {
+    let file = TempFile::new(&format!("{}.vol", name));
+    fs::write(file.path(), bytes).unwrap();
+    VoxelGrid::load(file.path(), Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 1.0, 1.0))
+}
+
+#[test]
+fn malformed_headers_are_errors() {
+    assert!(load("overflow", b"RTVOL 4294967296 4294967296 2\n").is_err());
+    assert!(load("huge", b"RTVOL 18446744073709551615 1 1\n").is_err());
+    assert!(load("empty", b"RTVOL 0 1 1\n").is_err());
+    assert!(load("short", b"RTVOL 2 2 2\n\0\0\0\0").is_err());
+    assert!(load("magic", b"VOL 1 1 1\n\0\0\0\0").is_err());
+}
+
+#[test]
+fn single_voxel_grid_is_uniform() {
+    let grid = load("single", &[b"RTVOL 1 1 1\n".as_slice(), &2.5f32.to_le_bytes()].concat()).unwrap();
+    assert_eq!(grid.density(Point3::new(0.3, 0.6, 0.9)), 2.5);
+    assert_eq!(grid.max_density(), 2.5);
+}